@@ -0,0 +1,890 @@
+use crate::{AsepriteError, SpriteExt, SpriteFrameLayers, SpriteImageBuffer};
+use flate2::read::ZlibDecoder;
+use image::Rgba;
+use std::{
+    collections::HashMap,
+    io::Read,
+    num::NonZeroUsize,
+    ops::{Range, RangeInclusive},
+    path::Path,
+};
+use yy_typings::{
+    CommonData, FrameId, LayerId, PlaybackSpeed, Sprite, SpriteLayer, SpriteSequenceId,
+    TexturePath, ViewPath,
+};
+
+const FILE_MAGIC: u16 = 0xA5E0;
+const FRAME_MAGIC: u16 = 0xF1FA;
+const HEADER_SIZE: usize = 128;
+const PALETTE_SIZE: usize = 256;
+
+const OLD_PALETTE_CHUNK: u16 = 0x0004;
+const OLD_SMALL_PALETTE_CHUNK: u16 = 0x0011;
+const LAYER_CHUNK: u16 = 0x2004;
+const CEL_CHUNK: u16 = 0x2005;
+const TAGS_CHUNK: u16 = 0x2018;
+const PALETTE_CHUNK: u16 = 0x2019;
+
+/// An Aseprite file, read in so that it can be imported as one or more Sprites with [`import`].
+///
+/// Every layer is read in as a Sprite layer, except for groups and tilemaps. A layer is hidden if
+/// it, or any group it's in, is hidden.
+///
+/// [`import`]: #method.import
+#[derive(Debug, Clone, PartialEq)]
+pub struct AsepriteFile {
+    width: NonZeroUsize,
+    height: NonZeroUsize,
+    layers: Vec<AsepriteLayer>,
+    frames: Vec<AsepriteFrame>,
+    tags: Vec<AsepriteTag>,
+}
+
+/// How the tags of an Aseprite file are imported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AsepriteTags {
+    /// Every frame is imported into a single Sprite, and the tags are listed with it as ranges
+    /// of the keys of its sequence.
+    #[default]
+    Ranges,
+
+    /// Each tag is imported as its own Sprite, named `<name>_<tag>`, with its frames in the
+    /// order of the tag's direction. A file without tags is imported as a single Sprite.
+    ///
+    /// Any character of a tag's name which can't be in a resource name is replaced with `_`.
+    Sprites,
+}
+
+/// A Sprite imported from an Aseprite file, which can be added with [`YypBoss::add_resource`].
+///
+/// [`YypBoss::add_resource`]: ./struct.YypBoss.html#method.add_resource
+#[derive(Debug, Clone, PartialEq)]
+pub struct AsepriteSprite {
+    pub sprite: Sprite,
+    pub associated_data: HashMap<FrameId, SpriteFrameLayers>,
+
+    /// The tags of the file, with the keys of `sprite`'s sequence which they cover. This is empty
+    /// if each tag was imported as its own Sprite.
+    pub tags: Vec<AsepriteSpriteTag>,
+}
+
+/// A tag of an Aseprite file, placed on the sequence of the Sprite it was imported into.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AsepriteSpriteTag {
+    pub tag: AsepriteTag,
+
+    /// From the key of the tag's first frame to the end of its last. Since frames which are
+    /// shown for longer are stretched over several keys, this generally isn't `tag.frames`.
+    pub keys: Range<f32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AsepriteTag {
+    pub name: String,
+    pub frames: RangeInclusive<usize>,
+    pub direction: AsepriteDirection,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AsepriteDirection {
+    Forward,
+    Reverse,
+    PingPong,
+    PingPongReverse,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct AsepriteLayer {
+    name: String,
+    visible: bool,
+    locked: bool,
+    opacity: u8,
+    blend_mode: u16,
+
+    /// Whether this is an image layer. Groups and tilemaps are kept only so that cels can find
+    /// their layer by its index.
+    image: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct AsepriteFrame {
+    duration: u16,
+
+    /// The image of each layer which has a cel in this frame, keyed by its index in `layers`.
+    cels: HashMap<usize, SpriteImageBuffer>,
+}
+
+impl AsepriteFile {
+    /// Reads in the Aseprite file at `path`.
+    pub fn open(path: &Path) -> Result<Self, AsepriteError> {
+        Self::parse(&std::fs::read(path)?)
+    }
+
+    /// Reads in an Aseprite file from its bytes.
+    pub fn parse(bytes: &[u8]) -> Result<Self, AsepriteError> {
+        let mut header = Reader::new(bytes);
+        header.dword()?;
+        if header.word()? != FILE_MAGIC {
+            return Err(AsepriteError::BadMagic);
+        }
+        let frame_count = header.word()? as usize;
+        let width = NonZeroUsize::new(header.word()? as usize).ok_or(AsepriteError::NoSize)?;
+        let height = NonZeroUsize::new(header.word()? as usize).ok_or(AsepriteError::NoSize)?;
+        let color_depth = header.word()?;
+        if matches!(color_depth, 8 | 16 | 32) == false {
+            return Err(AsepriteError::UnsupportedColorDepth(color_depth));
+        }
+        let layer_opacity_valid = header.dword()? & 1 == 1;
+        header.skip(2 + 4 + 4)?;
+        let transparent_index = header.byte()?;
+
+        let mut output = Self {
+            width,
+            height,
+            layers: Vec::new(),
+            frames: Vec::with_capacity(frame_count),
+            tags: Vec::new(),
+        };
+        let mut reader = Reader::new(bytes);
+        reader.skip(HEADER_SIZE)?;
+        let mut pixels = Pixels {
+            color_depth,
+            transparent_index,
+            palette: Vec::new(),
+            new_palette: false,
+        };
+        let mut group_visibility: Vec<bool> = Vec::new();
+        let mut linked_cels = Vec::new();
+
+        for frame_index in 0..frame_count {
+            let frame_size = reader.dword()? as usize;
+            let mut frame = Reader::new(reader.bytes(frame_size.saturating_sub(4))?);
+            if frame.word()? != FRAME_MAGIC {
+                return Err(AsepriteError::BadMagic);
+            }
+            let old_chunk_count = frame.word()? as usize;
+            let duration = frame.word()?;
+            frame.skip(2)?;
+            let chunk_count = match frame.dword()? as usize {
+                0 => old_chunk_count,
+                v => v,
+            };
+
+            let mut cels = HashMap::new();
+            for _ in 0..chunk_count {
+                let chunk_size = frame.dword()? as usize;
+                let chunk_type = frame.word()?;
+                let mut chunk = Reader::new(frame.bytes(chunk_size.saturating_sub(6))?);
+
+                match chunk_type {
+                    LAYER_CHUNK => {
+                        let flags = chunk.word()?;
+                        let layer_type = chunk.word()?;
+                        let child_level = chunk.word()? as usize;
+                        chunk.skip(4)?;
+                        let blend_mode = chunk.word()?;
+                        let opacity = chunk.byte()?;
+                        chunk.skip(3)?;
+                        let name = chunk.string()?;
+
+                        group_visibility.truncate(child_level);
+                        let visible =
+                            flags & 1 == 1 && group_visibility.iter().all(|visible| *visible);
+                        if layer_type == 1 {
+                            group_visibility.push(visible);
+                        }
+
+                        output.layers.push(AsepriteLayer {
+                            name,
+                            visible,
+                            locked: flags & 2 == 0,
+                            opacity: if layer_opacity_valid { opacity } else { 255 },
+                            blend_mode,
+                            image: layer_type == 0,
+                        });
+                    }
+                    CEL_CHUNK => {
+                        let layer_index = chunk.word()? as usize;
+                        let x = chunk.short()? as i64;
+                        let y = chunk.short()? as i64;
+                        let opacity = chunk.byte()?;
+                        let cel_type = chunk.word()?;
+                        chunk.skip(7)?;
+
+                        let is_image = output.layers.get(layer_index).is_some_and(|v| v.image);
+                        if is_image == false {
+                            continue;
+                        }
+
+                        let cel = match cel_type {
+                            // raw and compressed images
+                            0 | 2 => {
+                                let cel_width = chunk.word()? as u32;
+                                let cel_height = chunk.word()? as u32;
+                                let data = if cel_type == 0 {
+                                    chunk.rest().to_vec()
+                                } else {
+                                    // never inflate more than the cel can hold
+                                    let len = cel_width as u64
+                                        * cel_height as u64
+                                        * pixels.bytes_per_pixel() as u64;
+                                    let mut data = Vec::new();
+                                    ZlibDecoder::new(chunk.rest())
+                                        .take(len)
+                                        .read_to_end(&mut data)
+                                        .map_err(|e| AsepriteError::BadCel {
+                                            frame: frame_index,
+                                            reason: e.to_string(),
+                                        })?;
+                                    data
+                                };
+
+                                pixels.image(cel_width, cel_height, &data).ok_or_else(|| {
+                                    AsepriteError::BadCel {
+                                        frame: frame_index,
+                                        reason: "image data is too short".to_string(),
+                                    }
+                                })?
+                            }
+                            // linked to the cel of the same layer in another frame
+                            1 => {
+                                linked_cels.push((
+                                    frame_index,
+                                    layer_index,
+                                    chunk.word()? as usize,
+                                ));
+                                continue;
+                            }
+                            _ => continue,
+                        };
+
+                        let mut image =
+                            SpriteImageBuffer::new(width.get() as u32, height.get() as u32);
+                        for (cel_x, cel_y, pixel) in cel.enumerate_pixels() {
+                            let (x, y) = (x + cel_x as i64, y + cel_y as i64);
+                            if (0..width.get() as i64).contains(&x)
+                                && (0..height.get() as i64).contains(&y)
+                            {
+                                let mut pixel = *pixel;
+                                pixel[3] = (pixel[3] as u32 * opacity as u32 / 255) as u8;
+                                image.put_pixel(x as u32, y as u32, pixel);
+                            }
+                        }
+
+                        cels.insert(layer_index, image);
+                    }
+                    TAGS_CHUNK => {
+                        let tag_count = chunk.word()?;
+                        chunk.skip(8)?;
+                        for _ in 0..tag_count {
+                            let from = chunk.word()? as usize;
+                            let to = chunk.word()? as usize;
+                            let direction = match chunk.byte()? {
+                                1 => AsepriteDirection::Reverse,
+                                2 => AsepriteDirection::PingPong,
+                                3 => AsepriteDirection::PingPongReverse,
+                                _ => AsepriteDirection::Forward,
+                            };
+                            chunk.skip(2 + 6 + 3 + 1)?;
+
+                            output.tags.push(AsepriteTag {
+                                name: chunk.string()?,
+                                frames: from..=to.max(from),
+                                direction,
+                            });
+                        }
+                    }
+                    PALETTE_CHUNK => {
+                        let size = chunk.dword()? as usize;
+                        let first = chunk.dword()? as usize;
+                        let last = chunk.dword()? as usize;
+                        chunk.skip(8)?;
+                        if first > last || last >= PALETTE_SIZE || size > PALETTE_SIZE {
+                            return Err(AsepriteError::BadPalette(frame_index));
+                        }
+
+                        pixels
+                            .palette
+                            .resize(size.max(last + 1), Rgba([0, 0, 0, 0]));
+                        for entry in pixels.palette[first..=last].iter_mut() {
+                            let flags = chunk.word()?;
+                            *entry =
+                                Rgba([chunk.byte()?, chunk.byte()?, chunk.byte()?, chunk.byte()?]);
+                            if flags & 1 == 1 {
+                                chunk.string()?;
+                            }
+                        }
+                        pixels.new_palette = true;
+                    }
+                    OLD_PALETTE_CHUNK | OLD_SMALL_PALETTE_CHUNK if pixels.new_palette == false => {
+                        let scale = |v: u8| match chunk_type {
+                            OLD_SMALL_PALETTE_CHUNK => (v as u32 * 255 / 63) as u8,
+                            _ => v,
+                        };
+
+                        let mut index = 0;
+                        for _ in 0..chunk.word()? {
+                            index += chunk.byte()? as usize;
+                            let count = match chunk.byte()? {
+                                0 => 256,
+                                v => v as usize,
+                            };
+
+                            if index + count > PALETTE_SIZE {
+                                return Err(AsepriteError::BadPalette(frame_index));
+                            }
+                            if pixels.palette.len() < index + count {
+                                pixels.palette.resize(index + count, Rgba([0, 0, 0, 0]));
+                            }
+                            for entry in pixels.palette[index..index + count].iter_mut() {
+                                let (r, g, b) = (chunk.byte()?, chunk.byte()?, chunk.byte()?);
+                                *entry = Rgba([scale(r), scale(g), scale(b), 255]);
+                            }
+                            index += count;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            output.frames.push(AsepriteFrame { duration, cels });
+        }
+
+        for (frame, layer, linked_frame) in linked_cels {
+            let cel = output
+                .frames
+                .get(linked_frame)
+                .and_then(|v| v.cels.get(&layer))
+                .cloned()
+                .ok_or_else(|| AsepriteError::BadCel {
+                    frame,
+                    reason: format!("linked to frame {}, which has no cel", linked_frame),
+                })?;
+
+            output.frames[frame].cels.insert(layer, cel);
+        }
+
+        Ok(output)
+    }
+
+    /// The tags of the file, in the order they're listed in Aseprite.
+    pub fn tags(&self) -> &[AsepriteTag] {
+        &self.tags
+    }
+
+    /// Imports the file as one or more Sprites, depending on `tags`. This only fails if a
+    /// Sprite's name isn't a valid resource name.
+    ///
+    /// The frames of each Sprite are shown for as long as they are in Aseprite, so the playback
+    /// speed is the frames per second of the longest duration which every frame is a multiple of,
+    /// and frames which are shown for longer are stretched over several of those. Each bbox is
+    /// computed from the Sprite's frames, as in Gms2's automatic mode.
+    pub fn import(
+        &self,
+        name: &str,
+        texture_group_id: TexturePath,
+        parent: ViewPath,
+        tags: AsepriteTags,
+    ) -> Result<Vec<AsepriteSprite>, AsepriteError> {
+        if tags == AsepriteTags::Ranges || self.tags.is_empty() {
+            let frames: Vec<usize> = (0..self.frames.len()).collect();
+            let (mut output, keys) = self.sprite(name, texture_group_id, parent, &frames)?;
+            if tags == AsepriteTags::Ranges {
+                output.tags = self
+                    .tags
+                    .iter()
+                    .filter(|tag| *tag.frames.start() < keys.len())
+                    .map(|tag| {
+                        let last = &keys[(*tag.frames.end()).min(keys.len() - 1)];
+                        AsepriteSpriteTag {
+                            tag: tag.clone(),
+                            keys: keys[*tag.frames.start()].start..last.end,
+                        }
+                    })
+                    .collect();
+            }
+
+            return Ok(vec![output]);
+        }
+
+        self.tags
+            .iter()
+            .map(|tag| {
+                let mut frames: Vec<usize> = tag
+                    .frames
+                    .clone()
+                    .filter(|v| *v < self.frames.len())
+                    .collect();
+                if matches!(
+                    tag.direction,
+                    AsepriteDirection::Reverse | AsepriteDirection::PingPongReverse
+                ) {
+                    frames.reverse();
+                }
+                if matches!(
+                    tag.direction,
+                    AsepriteDirection::PingPong | AsepriteDirection::PingPongReverse
+                ) && frames.len() > 2
+                {
+                    let back: Vec<usize> =
+                        frames[1..frames.len() - 1].iter().rev().copied().collect();
+                    frames.extend(back);
+                }
+
+                let tag_name: String = tag
+                    .name
+                    .chars()
+                    .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                    .collect();
+
+                self.sprite(
+                    &format!("{}_{}", name, tag_name),
+                    texture_group_id.clone(),
+                    parent.clone(),
+                    &frames,
+                )
+                .map(|(sprite, _)| sprite)
+            })
+            .collect()
+    }
+
+    /// Imports `frames` as a Sprite, and returns the keys each of them covers in its sequence.
+    fn sprite(
+        &self,
+        name: &str,
+        texture_group_id: TexturePath,
+        parent: ViewPath,
+        frames: &[usize],
+    ) -> Result<(AsepriteSprite, Vec<Range<f32>>), AsepriteError> {
+        if crate::yyp_boss::RNV.is_valid(name) == false {
+            return Err(AsepriteError::BadName(name.to_owned()));
+        }
+
+        let mut sprite =
+            Sprite::new(name, texture_group_id, parent).dimensions(self.width, self.height);
+
+        // Aseprite lists layers from the bottom, and Gms2 from the top
+        let layers: Vec<(usize, LayerId)> = self
+            .layers
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, layer)| layer.image)
+            .map(|(index, _)| (index, LayerId::new()))
+            .collect();
+        if layers.is_empty() == false {
+            sprite.layers = layers
+                .iter()
+                .map(|(index, layer_id)| {
+                    let layer = &self.layers[*index];
+                    SpriteLayer {
+                        visible: layer.visible,
+                        is_locked: layer.locked,
+                        blend_mode: blend_mode(layer.blend_mode),
+                        opacity: layer.opacity as f64 * 100.0 / 255.0,
+                        display_name: layer.name.clone(),
+                        common_data: CommonData::new(*layer_id),
+                    }
+                })
+                .collect();
+        }
+
+        let step = frames
+            .iter()
+            .map(|v| self.frames[*v].duration.max(1) as u64)
+            .reduce(gcd)
+            .unwrap_or(100);
+        sprite.sequence.playback_speed_type = PlaybackSpeed::FramesPerSecond;
+        sprite.sequence.playback_speed = 1000.0 / step as f32;
+
+        let mut associated_data = HashMap::new();
+        let mut keys = Vec::with_capacity(frames.len());
+        let mut key = 0.0;
+        for frame in frames.iter().map(|v| &self.frames[*v]) {
+            let frame_id = FrameId::new();
+            sprite.set_frame(frame_id, SpriteSequenceId::new());
+
+            let length = (frame.duration.max(1) as u64 / step) as f32;
+            let keyframe = sprite.sequence.tracks[0]
+                .keyframes
+                .keyframes
+                .last_mut()
+                .unwrap();
+            keyframe.key = key;
+            keyframe.length = length;
+            keys.push(key..key + length);
+            key += length;
+
+            let frame_layers = sprite
+                .layers
+                .iter()
+                .map(|layer| {
+                    let image = layers
+                        .iter()
+                        .find(|(_, layer_id)| *layer_id == layer.common_data.name)
+                        .and_then(|(index, _)| frame.cels.get(index))
+                        .cloned()
+                        .unwrap_or_else(|| {
+                            SpriteImageBuffer::new(
+                                self.width.get() as u32,
+                                self.height.get() as u32,
+                            )
+                        });
+
+                    (layer.common_data.name, image)
+                })
+                .collect();
+            associated_data.insert(frame_id, frame_layers);
+        }
+        sprite.sequence.length = key;
+        // every frame was given its layers above, so this always sets the bbox
+        sprite.set_automatic_bbox(&associated_data, 0);
+
+        let output = AsepriteSprite {
+            sprite,
+            associated_data,
+            tags: Vec::new(),
+        };
+
+        Ok((output, keys))
+    }
+}
+
+/// Aseprite's blend modes as Gms2's. Blend modes which Gms2 doesn't have are imported as normal.
+fn blend_mode(aseprite: u16) -> usize {
+    match aseprite {
+        16 => 1, // addition
+        17 => 2, // subtract
+        1 => 3,  // multiply
+        _ => 0,
+    }
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// How the pixels of cels are stored.
+struct Pixels {
+    color_depth: u16,
+    transparent_index: u8,
+    palette: Vec<Rgba<u8>>,
+    new_palette: bool,
+}
+
+impl Pixels {
+    fn bytes_per_pixel(&self) -> usize {
+        self.color_depth as usize / 8
+    }
+
+    fn image(&self, width: u32, height: u32, data: &[u8]) -> Option<SpriteImageBuffer> {
+        let bytes_per_pixel = self.bytes_per_pixel();
+        if data.len() < width as usize * height as usize * bytes_per_pixel {
+            return None;
+        }
+
+        Some(SpriteImageBuffer::from_fn(width, height, |x, y| {
+            let i = (y as usize * width as usize + x as usize) * bytes_per_pixel;
+            match self.color_depth {
+                32 => Rgba([data[i], data[i + 1], data[i + 2], data[i + 3]]),
+                16 => Rgba([data[i], data[i], data[i], data[i + 1]]),
+                _ if data[i] == self.transparent_index => Rgba([0, 0, 0, 0]),
+                _ => self
+                    .palette
+                    .get(data[i] as usize)
+                    .copied()
+                    .unwrap_or(Rgba([0, 0, 0, 0])),
+            }
+        }))
+    }
+}
+
+/// Reads the little-endian values which Aseprite files are made of.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], AsepriteError> {
+        let output = self
+            .bytes
+            .get(self.position..self.position + len)
+            .ok_or(AsepriteError::UnexpectedEnd)?;
+        self.position += len;
+
+        Ok(output)
+    }
+
+    fn rest(&mut self) -> &'a [u8] {
+        let output = &self.bytes[self.position..];
+        self.position = self.bytes.len();
+
+        output
+    }
+
+    fn skip(&mut self, len: usize) -> Result<(), AsepriteError> {
+        self.bytes(len).map(|_| ())
+    }
+
+    fn byte(&mut self) -> Result<u8, AsepriteError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn word(&mut self) -> Result<u16, AsepriteError> {
+        let bytes = self.bytes(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn short(&mut self) -> Result<i16, AsepriteError> {
+        self.word().map(|v| v as i16)
+    }
+
+    fn dword(&mut self) -> Result<u32, AsepriteError> {
+        let bytes = self.bytes(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn string(&mut self) -> Result<String, AsepriteError> {
+        let len = self.word()? as usize;
+        Ok(String::from_utf8_lossy(self.bytes(len)?).into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::{write::ZlibEncoder, Compression};
+    use std::io::Write;
+
+    const RED: Rgba<u8> = Rgba([255, 0, 0, 255]);
+    const BLUE: Rgba<u8> = Rgba([0, 0, 255, 255]);
+
+    fn string(value: &str) -> Vec<u8> {
+        let mut output = (value.len() as u16).to_le_bytes().to_vec();
+        output.extend(value.as_bytes());
+        output
+    }
+
+    fn chunk(chunk_type: u16, data: Vec<u8>) -> Vec<u8> {
+        let mut output = ((data.len() + 6) as u32).to_le_bytes().to_vec();
+        output.extend(chunk_type.to_le_bytes());
+        output.extend(data);
+        output
+    }
+
+    fn layer(flags: u16, layer_type: u16, child_level: u16, name: &str) -> Vec<u8> {
+        let mut data = [flags, layer_type, child_level, 0, 0, 0]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect::<Vec<u8>>();
+        data.extend([255, 0, 0, 0]);
+        data.extend(string(name));
+        chunk(LAYER_CHUNK, data)
+    }
+
+    fn cel(layer: u16, x: i16, y: i16, cel_type: u16, data: Vec<u8>) -> Vec<u8> {
+        let mut output = [layer, x as u16, y as u16]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect::<Vec<u8>>();
+        output.push(255);
+        output.extend(cel_type.to_le_bytes());
+        output.extend([0; 7]);
+        output.extend(data);
+        chunk(CEL_CHUNK, output)
+    }
+
+    fn image(width: u16, height: u16, pixel: Rgba<u8>) -> Vec<u8> {
+        let mut output = width.to_le_bytes().to_vec();
+        output.extend(height.to_le_bytes());
+        output.extend((0..width * height).flat_map(|_| pixel.0));
+        output
+    }
+
+    fn frame(duration: u16, chunks: Vec<Vec<u8>>) -> Vec<u8> {
+        let data: Vec<u8> = chunks.concat();
+        let mut output = ((data.len() + 16) as u32).to_le_bytes().to_vec();
+        output.extend(FRAME_MAGIC.to_le_bytes());
+        output.extend((chunks.len() as u16).to_le_bytes());
+        output.extend(duration.to_le_bytes());
+        output.extend([0, 0]);
+        output.extend((chunks.len() as u32).to_le_bytes());
+        output.extend(data);
+        output
+    }
+
+    /// A 4x2 file with a background layer, and a hat in a hidden group.
+    fn file() -> Vec<u8> {
+        let mut hat = image(1, 1, BLUE)[..4].to_vec();
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&BLUE.0).unwrap();
+        hat.extend(encoder.finish().unwrap());
+
+        let mut tags = 2u16.to_le_bytes().to_vec();
+        tags.extend([0; 8]);
+        for (from, to, direction, name) in [(1u16, 2u16, 0u8, "walk"), (0, 1, 1, "blink")] {
+            tags.extend(from.to_le_bytes());
+            tags.extend(to.to_le_bytes());
+            tags.push(direction);
+            tags.extend([0; 12]);
+            tags.extend(string(name));
+        }
+
+        file_of(vec![
+            frame(
+                100,
+                vec![
+                    layer(3, 0, 0, "background"),
+                    layer(2, 1, 0, "group"),
+                    layer(3, 0, 1, "hat"),
+                    chunk(TAGS_CHUNK, tags),
+                    cel(0, 0, 0, 0, image(4, 2, RED)),
+                    cel(2, 1, 1, 2, hat),
+                ],
+            ),
+            frame(100, vec![cel(0, 0, 0, 1, 0u16.to_le_bytes().to_vec())]),
+            frame(200, vec![]),
+        ])
+    }
+
+    /// A 4x2 file with 32 bit color and the given frames.
+    fn file_of(frames: Vec<Vec<u8>>) -> Vec<u8> {
+        let frame_count = frames.len() as u16;
+        let frames = frames.concat();
+
+        let mut header = vec![0; HEADER_SIZE];
+        header[..4].copy_from_slice(&((HEADER_SIZE + frames.len()) as u32).to_le_bytes());
+        for (i, value) in [FILE_MAGIC, frame_count, 4, 2, 32, 1].iter().enumerate() {
+            header[4 + i * 2..6 + i * 2].copy_from_slice(&value.to_le_bytes());
+        }
+
+        [header, frames].concat()
+    }
+
+    #[test]
+    fn import_ranges() {
+        let file = AsepriteFile::parse(&file()).unwrap();
+        let mut sprites = file
+            .import(
+                "spr_test",
+                TexturePath::default(),
+                ViewPath::default(),
+                AsepriteTags::Ranges,
+            )
+            .unwrap();
+        assert_eq!(sprites.len(), 1);
+        let AsepriteSprite {
+            sprite,
+            associated_data,
+            tags,
+        } = sprites.remove(0);
+
+        let tag_names: Vec<&str> = tags.iter().map(|v| v.tag.name.as_str()).collect();
+        assert_eq!(tag_names, ["walk", "blink"]);
+        assert_eq!(tags[1].tag.direction, AsepriteDirection::Reverse);
+
+        // walk ends on the frame which is shown for twice as long
+        assert_eq!(tags[0].keys, 1.0..4.0);
+        assert_eq!(tags[1].keys, 0.0..2.0);
+        assert_eq!((sprite.width.get(), sprite.height.get()), (4, 2));
+        assert_eq!((sprite.bbox_right, sprite.bbox_bottom), (3, 1));
+
+        // the top layer comes first, and is hidden by its group
+        let names: Vec<&str> = sprite
+            .layers
+            .iter()
+            .map(|v| v.display_name.as_str())
+            .collect();
+        assert_eq!(names, ["hat", "background"]);
+        assert!(sprite.layers[0].visible == false);
+        assert!(sprite.layers[1].visible);
+
+        // the last frame is shown for twice as long
+        assert_eq!(sprite.sequence.playback_speed, 10.0);
+        assert_eq!(sprite.sequence.length, 4.0);
+        let keyframes: Vec<(f32, f32)> = sprite.sequence.tracks[0]
+            .keyframes
+            .keyframes
+            .iter()
+            .map(|v| (v.key, v.length))
+            .collect();
+        assert_eq!(keyframes, [(0.0, 1.0), (1.0, 1.0), (2.0, 2.0)]);
+
+        let image = |frame: usize, layer: usize| {
+            &associated_data[&sprite.frames[frame].name][&sprite.layers[layer].common_data.name]
+        };
+        assert_eq!(*image(0, 0).get_pixel(1, 1), BLUE);
+        assert_eq!(*image(0, 0).get_pixel(0, 0), Rgba([0, 0, 0, 0]));
+        // linked to the first frame
+        assert_eq!(*image(1, 1).get_pixel(3, 1), RED);
+        assert_eq!(*image(2, 1).get_pixel(3, 1), Rgba([0, 0, 0, 0]));
+    }
+
+    #[test]
+    fn import_tags_as_sprites() {
+        let mut file = AsepriteFile::parse(&file()).unwrap();
+        file.tags[0].name = "walk left".to_string();
+        let import = |file: &AsepriteFile, name: &str| {
+            file.import(
+                name,
+                TexturePath::default(),
+                ViewPath::default(),
+                AsepriteTags::Sprites,
+            )
+        };
+        let sprites = import(&file, "spr_test").unwrap();
+
+        let names: Vec<&str> = sprites
+            .iter()
+            .map(|v| v.sprite.common_data.name.as_str())
+            .collect();
+        assert_eq!(names, ["spr_test_walk_left", "spr_test_blink"]);
+        assert!(matches!(
+            import(&file, "spr test"),
+            Err(AsepriteError::BadName(_))
+        ));
+
+        let walk = &sprites[0].sprite;
+        assert_eq!(walk.frames.len(), 2);
+        assert_eq!(walk.sequence.length, 3.0);
+
+        // blink runs backwards, so it starts on the linked frame
+        let blink = &sprites[1];
+        let background = blink.sprite.layers[1].common_data.name;
+        let first = &blink.associated_data[&blink.sprite.frames[0].name];
+        assert_eq!(*first[&background].get_pixel(0, 0), RED);
+        assert!(sprites.iter().all(|v| v.tags.is_empty()));
+
+        assert!(matches!(
+            AsepriteFile::parse(&[0; HEADER_SIZE]),
+            Err(AsepriteError::BadMagic)
+        ));
+    }
+
+    #[test]
+    fn bad_palettes() {
+        let palette = |first: u32, last: u32| {
+            let data = [last + 1, first, last, 0, 0]
+                .iter()
+                .flat_map(|v| v.to_le_bytes())
+                .collect();
+            file_of(vec![frame(100, vec![chunk(PALETTE_CHUNK, data)])])
+        };
+
+        for (first, last) in [(2, 1), (0, 256), (0, u32::MAX - 1)] {
+            assert!(matches!(
+                AsepriteFile::parse(&palette(first, last)),
+                Err(AsepriteError::BadPalette(0))
+            ));
+        }
+    }
+}
@@ -0,0 +1,261 @@
+use crate::{GroupError, YyResourceHandler};
+use std::collections::BTreeMap;
+use yy_typings::{AudioGroup, AudioGroupId, AudioGroupPath, CommonData, ResourceVersion, Sound};
+
+/// The name of the audio group which every project has, and which new sounds use.
+pub const DEFAULT_AUDIO_GROUP: &str = "audiogroup_default";
+
+/// Manages the Audio Groups of a project.
+///
+/// Changes are only written into the Yyp when the YypBoss is serialized. Renaming or removing
+/// a group also edits every Sound which used it.
+#[derive(Debug, PartialEq, Eq, Default, Clone)]
+pub struct AudioGroupManager {
+    audio_groups: Vec<AudioGroup>,
+}
+
+impl AudioGroupManager {
+    pub(crate) fn new(audio_groups: &[AudioGroup]) -> Self {
+        Self {
+            audio_groups: audio_groups.to_vec(),
+        }
+    }
+
+    /// Returns every audio group in the project.
+    pub fn audio_groups(&self) -> &[AudioGroup] {
+        &self.audio_groups
+    }
+
+    /// Gets an audio group by name.
+    pub fn get(&self, name: &str) -> Option<&AudioGroup> {
+        self.audio_groups
+            .iter()
+            .find(|v| v.common_data.name == name)
+    }
+
+    /// Gets the id of an audio group, which sounds use to refer to it.
+    pub fn audio_group_id(&self, name: &str) -> Option<AudioGroupId> {
+        self.get(name).map(|v| Self::id_of(&v.common_data.name))
+    }
+
+    /// Creates a new audio group, which is built for every platform, returning its id.
+    pub fn create(&mut self, name: &str) -> Result<AudioGroupId, GroupError> {
+        self.can_use_name(name)?;
+
+        self.audio_groups.push(AudioGroup {
+            common_data: CommonData {
+                resource_version: ResourceVersion::new(1, 3),
+                ..CommonData::new(name.to_owned())
+            },
+            targets: -1,
+        });
+
+        Ok(Self::id_of(name))
+    }
+
+    /// Sets the platforms which an audio group is built for, as a bitmask. `-1` is every platform.
+    pub fn set_targets(&mut self, name: &str, targets: isize) -> Result<(), GroupError> {
+        let position = self.position(name)?;
+        self.audio_groups[position].targets = targets;
+
+        Ok(())
+    }
+
+    /// Renames an audio group. Every sound which used the old name is moved to the new name.
+    pub fn rename(
+        &mut self,
+        name: &str,
+        new_name: &str,
+        sounds: &mut YyResourceHandler<Sound>,
+    ) -> Result<(), GroupError> {
+        if name == DEFAULT_AUDIO_GROUP {
+            return Err(GroupError::DefaultGroup);
+        }
+        let position = self.position(name)?;
+        self.can_use_name(new_name)?;
+
+        self.audio_groups[position].common_data.name = new_name.to_owned();
+        Self::reassign_sounds(name, Self::id_of(new_name), sounds)
+    }
+
+    /// Removes an audio group. Every sound which used it is moved to the default group.
+    ///
+    /// If the project has no default group, nothing is changed and
+    /// [`GroupError::NoDefaultGroup`] is returned, since its sounds would have nowhere to go.
+    ///
+    /// [`GroupError::NoDefaultGroup`]: ./enum.GroupError.html#variant.NoDefaultGroup
+    pub fn remove(
+        &mut self,
+        name: &str,
+        sounds: &mut YyResourceHandler<Sound>,
+    ) -> Result<AudioGroup, GroupError> {
+        if name == DEFAULT_AUDIO_GROUP {
+            return Err(GroupError::DefaultGroup);
+        }
+        let position = self.position(name)?;
+        let default = self
+            .audio_group_id(DEFAULT_AUDIO_GROUP)
+            .ok_or(GroupError::NoDefaultGroup)?;
+
+        let audio_group = self.audio_groups.remove(position);
+        Self::reassign_sounds(name, default, sounds)?;
+
+        Ok(audio_group)
+    }
+
+    /// Lists the names of the sounds in each audio group, keyed by the group's name. Every
+    /// group is listed, even if it is empty, and the sounds are sorted by name.
+    ///
+    /// Sounds which refer to a group which doesn't exist are listed under that name too,
+    /// so they aren't lost when rebalancing.
+    pub fn sounds_by_group(
+        &self,
+        sounds: &YyResourceHandler<Sound>,
+    ) -> BTreeMap<String, Vec<String>> {
+        let mut output: BTreeMap<String, Vec<String>> = self
+            .audio_groups
+            .iter()
+            .map(|v| (v.common_data.name.clone(), vec![]))
+            .collect();
+
+        for sound_name in sounds.names() {
+            let sound = match sounds.get(sound_name) {
+                Some(v) => v,
+                None => continue,
+            };
+            output
+                .entry(sound.yy_resource.audio_group_id.name.clone())
+                .or_default()
+                .push(sound_name.clone());
+        }
+
+        for sound_names in output.values_mut() {
+            sound_names.sort();
+        }
+
+        output
+    }
+
+    /// Writes the audio groups into the Yyp's listing.
+    pub(crate) fn serialize(&self, audio_groups: &mut Vec<AudioGroup>) {
+        *audio_groups = self.audio_groups.clone();
+    }
+
+    fn reassign_sounds(
+        name: &str,
+        new_id: AudioGroupId,
+        sounds: &mut YyResourceHandler<Sound>,
+    ) -> Result<(), GroupError> {
+        sounds.load_all();
+        let sound_names: Vec<String> = sounds
+            .resources()
+            .iter()
+            .filter(|(_, v)| v.yy_resource.audio_group_id.name == name)
+            .map(|(k, _)| k.clone())
+            .collect();
+
+        for sound_name in sound_names {
+            sounds
+                .edit_yy(&sound_name, |v| v.audio_group_id = new_id.clone())
+                .map_err(|e| GroupError::Reassign(sound_name.clone(), e.to_string()))?;
+        }
+
+        Ok(())
+    }
+
+    fn id_of(name: &str) -> AudioGroupId {
+        AudioGroupId {
+            name: name.to_owned(),
+            path: AudioGroupPath(format!("audiogroups/{}", name)),
+        }
+    }
+
+    fn can_use_name(&self, name: &str) -> Result<(), GroupError> {
+        if crate::yyp_boss::RNV.is_valid(name) == false {
+            return Err(GroupError::BadName);
+        }
+
+        if self.get(name).is_some() {
+            return Err(GroupError::NameCollision);
+        }
+
+        Ok(())
+    }
+
+    fn position(&self, name: &str) -> Result<usize, GroupError> {
+        self.audio_groups
+            .iter()
+            .position(|v| v.common_data.name == name)
+            .ok_or(GroupError::NotFound)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sound(name: &str, audio_group_id: AudioGroupId) -> Sound {
+        Sound {
+            common_data: CommonData::new(name.to_owned()),
+            audio_group_id,
+            ..Sound::default()
+        }
+    }
+
+    #[test]
+    fn rename_remove_and_list() {
+        let mut manager = AudioGroupManager::default();
+        let default = manager.create(DEFAULT_AUDIO_GROUP).unwrap();
+        let music = manager.create("audiogroup_music").unwrap();
+        assert_eq!(
+            manager.create("audiogroup_music"),
+            Err(GroupError::NameCollision)
+        );
+
+        let mut sounds = YyResourceHandler::new();
+        sounds.set(sound("snd_theme", music.clone()), Some(vec![]));
+        sounds.set(sound("snd_boss", music), Some(vec![]));
+        sounds.set(sound("snd_jump", default.clone()), Some(vec![]));
+
+        manager
+            .rename("audiogroup_music", "audiogroup_bgm", &mut sounds)
+            .unwrap();
+        assert_eq!(
+            sounds.get("snd_theme").unwrap().yy_resource.audio_group_id,
+            manager.audio_group_id("audiogroup_bgm").unwrap()
+        );
+
+        let listing = manager.sounds_by_group(&sounds);
+        assert_eq!(listing["audiogroup_bgm"], vec!["snd_boss", "snd_theme"]);
+        assert_eq!(listing[DEFAULT_AUDIO_GROUP], vec!["snd_jump"]);
+
+        manager.remove("audiogroup_bgm", &mut sounds).unwrap();
+        assert_eq!(
+            sounds.get("snd_boss").unwrap().yy_resource.audio_group_id,
+            default
+        );
+        assert_eq!(manager.sounds_by_group(&sounds).len(), 1);
+        assert_eq!(
+            manager.remove(DEFAULT_AUDIO_GROUP, &mut sounds),
+            Err(GroupError::DefaultGroup)
+        );
+    }
+    #[test]
+    fn remove_without_default() {
+        let mut manager = AudioGroupManager::default();
+        let music = manager.create("audiogroup_music").unwrap();
+
+        let mut sounds = YyResourceHandler::new();
+        sounds.set(sound("snd_theme", music.clone()), Some(vec![]));
+
+        assert_eq!(
+            manager.remove("audiogroup_music", &mut sounds),
+            Err(GroupError::NoDefaultGroup)
+        );
+        assert!(manager.get("audiogroup_music").is_some());
+        assert_eq!(
+            sounds.get("snd_theme").unwrap().yy_resource.audio_group_id,
+            music
+        );
+    }
+}
@@ -5,14 +5,16 @@ mod output;
 pub use output::*;
 
 use crate::{
-    folders::FolderGraphError, utils, ResourceManipulationError, SerializedData,
+    folders::FolderGraphError, utils, GroupError, ResourceManipulationError, SerializedData,
     SerializedDataError,
 };
-use crate::{Resource, YyResource, YypBoss};
+use crate::{
+    typings::{AnimationCurve, Extension, Font, Path, Room, Sequence, Timeline},
+    Resource, YyResource, YypBoss,
+};
 use camino::{Utf8Path, Utf8PathBuf};
 use yy_typings::{
-    AnimationCurve, Extension, Font, Note, Path, Room, Script, Sequence, Shader, Sound, Sprite,
-    TileSet, Timeline, TrailingCommaUtility, {EventType, Object},
+    Note, Script, Shader, Sound, Sprite, TileSet, TrailingCommaUtility, {EventType, Object},
 };
 
 pub fn parse_command(
@@ -20,7 +22,16 @@ pub fn parse_command(
     working_directory: &Utf8Path,
     yyp_boss: &mut YypBoss,
 ) -> Output {
-    let command_output = match command {
+    let command_output = run_command(command, working_directory, yyp_boss);
+    Output::Command(command_output.unwrap_or_else(CommandOutput::error))
+}
+
+fn run_command(
+    command: Command,
+    working_directory: &Utf8Path,
+    yyp_boss: &mut YypBoss,
+) -> Result<CommandOutput, YypBossError> {
+    match command {
         Command::Resource(resource_command) => match resource_command.command_type {
             ResourceCommandType::Add(new_resource) => match resource_command.resource {
                 Resource::Sprite => add::<Sprite>(yyp_boss, working_directory, new_resource),
@@ -40,6 +51,48 @@ pub fn parse_command(
                 Resource::TileSet => add::<TileSet>(yyp_boss, working_directory, new_resource),
                 Resource::Timeline => add::<Timeline>(yyp_boss, working_directory, new_resource),
             },
+            ResourceCommandType::Replace(new_resource) => match resource_command.resource {
+                Resource::Sprite => replace::<Sprite>(yyp_boss, working_directory, new_resource),
+                Resource::Script => replace::<Script>(yyp_boss, working_directory, new_resource),
+                Resource::Object => replace::<Object>(yyp_boss, working_directory, new_resource),
+                Resource::Note => replace::<Note>(yyp_boss, working_directory, new_resource),
+                Resource::Shader => replace::<Shader>(yyp_boss, working_directory, new_resource),
+                Resource::AnimationCurve => {
+                    replace::<AnimationCurve>(yyp_boss, working_directory, new_resource)
+                }
+                Resource::Extension => {
+                    replace::<Extension>(yyp_boss, working_directory, new_resource)
+                }
+                Resource::Font => replace::<Font>(yyp_boss, working_directory, new_resource),
+                Resource::Room => replace::<Room>(yyp_boss, working_directory, new_resource),
+                Resource::Path => replace::<Path>(yyp_boss, working_directory, new_resource),
+                Resource::Sequence => {
+                    replace::<Sequence>(yyp_boss, working_directory, new_resource)
+                }
+                Resource::Sound => replace::<Sound>(yyp_boss, working_directory, new_resource),
+                Resource::TileSet => replace::<TileSet>(yyp_boss, working_directory, new_resource),
+                Resource::Timeline => {
+                    replace::<Timeline>(yyp_boss, working_directory, new_resource)
+                }
+            },
+            ResourceCommandType::Set(new_resource) => match resource_command.resource {
+                Resource::Sprite => set::<Sprite>(yyp_boss, working_directory, new_resource),
+                Resource::Script => set::<Script>(yyp_boss, working_directory, new_resource),
+                Resource::Object => set::<Object>(yyp_boss, working_directory, new_resource),
+                Resource::Note => set::<Note>(yyp_boss, working_directory, new_resource),
+                Resource::Shader => set::<Shader>(yyp_boss, working_directory, new_resource),
+                Resource::AnimationCurve => {
+                    set::<AnimationCurve>(yyp_boss, working_directory, new_resource)
+                }
+                Resource::Extension => set::<Extension>(yyp_boss, working_directory, new_resource),
+                Resource::Font => set::<Font>(yyp_boss, working_directory, new_resource),
+                Resource::Room => set::<Room>(yyp_boss, working_directory, new_resource),
+                Resource::Path => set::<Path>(yyp_boss, working_directory, new_resource),
+                Resource::Sequence => set::<Sequence>(yyp_boss, working_directory, new_resource),
+                Resource::Sound => set::<Sound>(yyp_boss, working_directory, new_resource),
+                Resource::TileSet => set::<TileSet>(yyp_boss, working_directory, new_resource),
+                Resource::Timeline => set::<Timeline>(yyp_boss, working_directory, new_resource),
+            },
             ResourceCommandType::Remove { identifier } => match resource_command.resource {
                 Resource::Sprite => remove::<Sprite>(yyp_boss, working_directory, identifier),
                 Resource::Script => remove::<Script>(yyp_boss, working_directory, identifier),
@@ -102,15 +155,13 @@ pub fn parse_command(
                 Resource::Shader => get_resource::<Shader>(yyp_boss, identifier),
                 Resource::Room => get_resource::<Room>(yyp_boss, identifier),
                 Resource::TileSet => get_resource::<TileSet>(yyp_boss, identifier),
-                Resource::AnimationCurve
-                | Resource::Extension
-                | Resource::Font
-                | Resource::Path
-                | Resource::Sequence
-                | Resource::Sound
-                | Resource::Timeline => Err(YypBossError::ResourceManipulation {
-                    data: ResourceManipulationError::ResourceCannotBeManipulated.to_string(),
-                }),
+                Resource::Font => get_resource::<Font>(yyp_boss, identifier),
+                Resource::Path => get_resource::<Path>(yyp_boss, identifier),
+                Resource::Sequence => get_resource::<Sequence>(yyp_boss, identifier),
+                Resource::Timeline => get_resource::<Timeline>(yyp_boss, identifier),
+                Resource::Extension => get_resource::<Extension>(yyp_boss, identifier),
+                Resource::AnimationCurve => get_resource::<AnimationCurve>(yyp_boss, identifier),
+                Resource::Sound => get_resource::<Sound>(yyp_boss, identifier),
             },
             ResourceCommandType::GetAssociatedData { identifier, force } => {
                 match resource_command.resource {
@@ -159,15 +210,50 @@ pub fn parse_command(
                         force,
                     ),
 
-                    Resource::AnimationCurve
-                    | Resource::Extension
-                    | Resource::Font
-                    | Resource::Path
-                    | Resource::Sequence
-                    | Resource::Sound
-                    | Resource::Timeline => Err(YypBossError::ResourceManipulation {
-                        data: ResourceManipulationError::ResourceCannotBeManipulated.to_string(),
-                    }),
+                    Resource::Font => ensure_associated_data::<Font>(
+                        yyp_boss,
+                        working_directory,
+                        identifier,
+                        force,
+                    ),
+
+                    Resource::Path => ensure_associated_data::<Path>(
+                        yyp_boss,
+                        working_directory,
+                        identifier,
+                        force,
+                    ),
+
+                    Resource::Sequence => ensure_associated_data::<Sequence>(
+                        yyp_boss,
+                        working_directory,
+                        identifier,
+                        force,
+                    ),
+                    Resource::Timeline => ensure_associated_data::<Timeline>(
+                        yyp_boss,
+                        working_directory,
+                        identifier,
+                        force,
+                    ),
+                    Resource::Extension => ensure_associated_data::<Extension>(
+                        yyp_boss,
+                        working_directory,
+                        identifier,
+                        force,
+                    ),
+                    Resource::AnimationCurve => ensure_associated_data::<AnimationCurve>(
+                        yyp_boss,
+                        working_directory,
+                        identifier,
+                        force,
+                    ),
+                    Resource::Sound => ensure_associated_data::<Sound>(
+                        yyp_boss,
+                        working_directory,
+                        identifier,
+                        force,
+                    ),
                 }
             }
             ResourceCommandType::Exists { identifier } => Ok(CommandOutput::ok_exists(
@@ -178,7 +264,7 @@ pub fn parse_command(
         },
         Command::VirtualFileSystem(vfs_command) => match vfs_command {
             VfsCommand::MoveFolder { folder, new_parent } => {
-                match yyp_boss.vfs.move_folder(folder, &new_parent) {
+                match yyp_boss.move_folder(folder, &new_parent) {
                     Ok(()) => Ok(CommandOutput::ok()),
                     Err(e) => Err(YypBossError::ResourceManipulation {
                         data: ResourceManipulationError::FolderGraphError(e).to_string(),
@@ -189,7 +275,7 @@ pub fn parse_command(
             VfsCommand::CreateFolder {
                 folder_name,
                 parent_folder,
-            } => match yyp_boss.vfs.new_folder_end(&parent_folder, &folder_name) {
+            } => match yyp_boss.new_folder_end(&parent_folder, &folder_name) {
                 Ok(v) => Ok(CommandOutput::ok_created_folder(v)),
                 Err(e) => Err(YypBossError::ResourceManipulation {
                     data: ResourceManipulationError::FolderGraphError(e).to_string(),
@@ -215,7 +301,7 @@ pub fn parse_command(
                         }),
                     }
                 } else {
-                    match yyp_boss.vfs.remove_empty_folder(&folder) {
+                    match yyp_boss.remove_empty_folder(&folder) {
                         Ok(()) => Ok(CommandOutput::ok()),
                         Err(e) => Err(YypBossError::FolderGraphError {
                             data: e.to_string(),
@@ -224,7 +310,7 @@ pub fn parse_command(
                 }
             }
             VfsCommand::RenameFolder { folder, new_name } => {
-                match yyp_boss.vfs.rename_folder(&folder, new_name) {
+                match yyp_boss.rename_folder(&folder, new_name) {
                     Ok(()) => Ok(CommandOutput::ok()),
                     Err(e) => Err(YypBossError::FolderGraphError {
                         data: e.to_string(),
@@ -258,6 +344,114 @@ pub fn parse_command(
                 }),
             },
         },
+        Command::IncludedFiles(included_files_command) => match included_files_command {
+            IncludedFilesCommand::Add { path, data } => read_included_file(data, working_directory)
+                .and_then(|data| match yyp_boss.add_included_file(&path, data) {
+                    Ok(()) => Ok(CommandOutput::ok()),
+                    Err(e) => Err(YypBossError::ResourceManipulation {
+                        data: e.to_string(),
+                    }),
+                }),
+            IncludedFilesCommand::Remove { path } => match yyp_boss.remove_included_file(&path) {
+                Ok(_) => Ok(CommandOutput::ok()),
+                Err(e) => Err(YypBossError::ResourceManipulation {
+                    data: e.to_string(),
+                }),
+            },
+            IncludedFilesCommand::Rename { path, new_name } => {
+                match yyp_boss.rename_included_file(&path, &new_name) {
+                    Ok(()) => Ok(CommandOutput::ok()),
+                    Err(e) => Err(YypBossError::ResourceManipulation {
+                        data: e.to_string(),
+                    }),
+                }
+            }
+            IncludedFilesCommand::Move {
+                path,
+                new_directory,
+            } => match yyp_boss.move_included_file(&path, &new_directory) {
+                Ok(()) => Ok(CommandOutput::ok()),
+                Err(e) => Err(YypBossError::ResourceManipulation {
+                    data: e.to_string(),
+                }),
+            },
+            IncludedFilesCommand::Exists { path } => Ok(CommandOutput::ok_exists(
+                yyp_boss.included_files.exists(&path),
+            )),
+        },
+        Command::TextureGroups(texture_group_command) => {
+            let result = match texture_group_command {
+                TextureGroupCommand::Create { name, parent } => yyp_boss
+                    .edit_texture_groups(None, |texture_groups, _, _| {
+                        texture_groups.create(&name, parent.as_deref())
+                    })
+                    .map(|_| CommandOutput::ok()),
+                TextureGroupCommand::Rename { name, new_name } => yyp_boss
+                    .edit_texture_groups(Some(&name), |texture_groups, sprites, tilesets| {
+                        texture_groups.rename(&name, &new_name, sprites, tilesets)
+                    })
+                    .map(|_| CommandOutput::ok()),
+                TextureGroupCommand::Remove { name } => yyp_boss
+                    .edit_texture_groups(Some(&name), |texture_groups, sprites, tilesets| {
+                        texture_groups.remove(&name, sprites, tilesets)
+                    })
+                    .map(|_| CommandOutput::ok()),
+                TextureGroupCommand::SetParent { name, parent } => yyp_boss
+                    .edit_texture_groups(None, |texture_groups, _, _| {
+                        texture_groups.set_parent(&name, parent.as_deref())
+                    })
+                    .map(|_| CommandOutput::ok()),
+                TextureGroupCommand::SetOptions { name, options } => yyp_boss
+                    .edit_texture_groups(None, |texture_groups, _, _| {
+                        texture_groups.set_options(&name, options)
+                    })
+                    .map(|_| CommandOutput::ok()),
+                TextureGroupCommand::Get { name } => yyp_boss
+                    .texture_groups
+                    .get(&name)
+                    .ok_or(GroupError::NotFound)
+                    .map(|v| CommandOutput::ok_resource(json_output(v))),
+                TextureGroupCommand::GetAll => Ok(CommandOutput::ok_resource(json_output(
+                    yyp_boss.texture_groups.texture_groups(),
+                ))),
+            };
+
+            result.map_err(|e| YypBossError::ResourceManipulation {
+                data: e.to_string(),
+            })
+        }
+        Command::AudioGroups(audio_group_command) => {
+            let result = match audio_group_command {
+                AudioGroupCommand::Create { name } => yyp_boss
+                    .edit_audio_groups(None, |audio_groups, _| audio_groups.create(&name))
+                    .map(|_| CommandOutput::ok()),
+                AudioGroupCommand::Rename { name, new_name } => yyp_boss
+                    .edit_audio_groups(Some(&name), |audio_groups, sounds| {
+                        audio_groups.rename(&name, &new_name, sounds)
+                    })
+                    .map(|_| CommandOutput::ok()),
+                AudioGroupCommand::Remove { name } => yyp_boss
+                    .edit_audio_groups(Some(&name), |audio_groups, sounds| {
+                        audio_groups.remove(&name, sounds)
+                    })
+                    .map(|_| CommandOutput::ok()),
+                AudioGroupCommand::SetTargets { name, targets } => yyp_boss
+                    .edit_audio_groups(None, |audio_groups, _| {
+                        audio_groups.set_targets(&name, targets)
+                    })
+                    .map(|_| CommandOutput::ok()),
+                AudioGroupCommand::GetAll => Ok(CommandOutput::ok_resource(json_output(
+                    yyp_boss.audio_groups.audio_groups(),
+                ))),
+                AudioGroupCommand::SoundsByGroup => Ok(CommandOutput::ok_resource(json_output(
+                    &yyp_boss.audio_groups.sounds_by_group(&yyp_boss.sounds),
+                ))),
+            };
+
+            result.map_err(|e| YypBossError::ResourceManipulation {
+                data: e.to_string(),
+            })
+        }
         Command::Utilities(util) => match util {
             UtilityCommand::ProjectInfo => {
                 Ok(CommandOutput::ok_metadata(yyp_boss.project_metadata()))
@@ -271,15 +465,13 @@ pub fn parse_command(
                 Resource::Shader => create_yy::<Shader>(create_data),
                 Resource::Room => create_yy::<Room>(create_data),
                 Resource::TileSet => create_yy::<TileSet>(create_data),
-                Resource::AnimationCurve
-                | Resource::Extension
-                | Resource::Font
-                | Resource::Path
-                | Resource::Sequence
-                | Resource::Sound
-                | Resource::Timeline => Err(YypBossError::ResourceManipulation {
-                    data: ResourceManipulationError::ResourceCannotBeManipulated.to_string(),
-                }),
+                Resource::Font => create_yy::<Font>(create_data),
+                Resource::Path => create_yy::<Path>(create_data),
+                Resource::Sequence => create_yy::<Sequence>(create_data),
+                Resource::Timeline => create_yy::<Timeline>(create_data),
+                Resource::Extension => create_yy::<Extension>(create_data),
+                Resource::AnimationCurve => create_yy::<AnimationCurve>(create_data),
+                Resource::Sound => create_yy::<Sound>(create_data),
             },
             UtilityCommand::PrettyEventNames { event_names: v } => {
                 let mut output = v
@@ -306,7 +498,7 @@ pub fn parse_command(
                 Ok(event_type) => {
                     match yyp_boss.ensure_associated_data_is_loaded::<Object>(&identifier, false) {
                         Ok(()) => {
-                            if yyp_boss.objects.add_event(&identifier, event_type) {
+                            if yyp_boss.add_object_event(&identifier, event_type) {
                                 Ok(CommandOutput::ok())
                             } else {
                                 Err(YypBossError::ResourceManipulation {
@@ -337,7 +529,7 @@ pub fn parse_command(
                 Ok(event_type) => {
                     match yyp_boss.ensure_associated_data_is_loaded::<Object>(&identifier, false) {
                         Ok(()) => {
-                            if yyp_boss.objects.remove_event(&identifier, event_type) {
+                            if yyp_boss.remove_object_event(&identifier, event_type) {
                                 Ok(CommandOutput::ok())
                             } else {
                                 Err(YypBossError::ResourceManipulation {
@@ -411,14 +603,86 @@ pub fn parse_command(
                     .is_ok(),
             )),
         },
-        Command::Serialize => match yyp_boss.serialize() {
-            Ok(()) => Ok(CommandOutput::ok()),
+        Command::Batch { commands } => run_batch(commands, working_directory, yyp_boss),
+        Command::Undo => match yyp_boss.undo() {
+            Ok(_) => Ok(CommandOutput::ok()),
+            Err(e) => Err(YypBossError::ResourceManipulation {
+                data: e.to_string(),
+            }),
+        },
+        Command::Redo => match yyp_boss.redo() {
+            Ok(_) => Ok(CommandOutput::ok()),
+            Err(e) => Err(YypBossError::ResourceManipulation {
+                data: e.to_string(),
+            }),
+        },
+        Command::Serialize { conflicts } => match yyp_boss.serialize_with(conflicts) {
+            Ok(report) => Ok(CommandOutput::ok_serialize_report(report)),
             Err(e) => Err(YypBossError::CouldNotSerializeYypBoss {
                 data: e.to_string(),
             }),
         },
-    };
-    Output::Command(command_output.unwrap_or_else(CommandOutput::error))
+        Command::SerializePlan => match yyp_boss.serialize_plan() {
+            Ok(plan) => Ok(CommandOutput::ok_serialize_plan(plan)),
+            Err(e) => Err(YypBossError::CouldNotSerializeYypBoss {
+                data: e.to_string(),
+            }),
+        },
+    }
+}
+
+fn run_batch(
+    commands: Vec<Command>,
+    working_directory: &Utf8Path,
+    yyp_boss: &mut YypBoss,
+) -> Result<CommandOutput, YypBossError> {
+    /// Why a command can't be in a batch, if it can't.
+    fn disallowed(command: &Command) -> Option<&'static str> {
+        match command {
+            Command::Serialize { .. } => Some("a batch cannot serialize the project"),
+            Command::SerializePlan => Some("a batch cannot plan a serialization"),
+            Command::Undo | Command::Redo => Some("a batch cannot undo or redo"),
+            Command::Batch { commands } => commands.iter().find_map(disallowed),
+            _ => None,
+        }
+    }
+
+    // we can't roll back the disk, or the undo stack, so don't even start
+    if let Some((index, reason)) = commands
+        .iter()
+        .enumerate()
+        .find_map(|(index, command)| disallowed(command).map(|v| (index, v)))
+    {
+        return Err(YypBossError::BatchFailed {
+            index,
+            error: Box::new(YypBossError::CouldNotReadCommand {
+                data: reason.to_string(),
+            }),
+        });
+    }
+
+    let mut outputs = Vec::with_capacity(commands.len());
+
+    // so the whole batch is undone at once, or rolled back if it fails
+    yyp_boss.begin_undo_step();
+    for (index, command) in commands.into_iter().enumerate() {
+        match run_command(command, working_directory, yyp_boss) {
+            Ok(output) => outputs.push(output),
+            Err(e) => {
+                if let Err(e) = yyp_boss.abort_undo_step() {
+                    log::error!("couldn't roll back the batch...{}", e);
+                }
+
+                return Err(YypBossError::BatchFailed {
+                    index,
+                    error: Box::new(e),
+                });
+            }
+        }
+    }
+    yyp_boss.end_undo_step();
+
+    Ok(CommandOutput::ok_batch(outputs))
 }
 
 fn add<T: YyResource>(
@@ -438,20 +702,58 @@ fn add<T: YyResource>(
     }
 }
 
+fn replace<T: YyResource>(
+    yyp_boss: &mut YypBoss,
+    working_directory: &Utf8Path,
+    new_resource: NewResource,
+) -> Result<CommandOutput, YypBossError> {
+    let (yy_file, associated_data) =
+        read_new_resource::<T>(new_resource, yyp_boss.tcu(), working_directory)?;
+
+    match yyp_boss.replace_resource(yy_file, associated_data) {
+        Ok(old) => resource_output(old, working_directory),
+        Err(e) => Err(YypBossError::ResourceManipulation {
+            data: e.to_string(),
+        }),
+    }
+}
+
+fn set<T: YyResource>(
+    yyp_boss: &mut YypBoss,
+    working_directory: &Utf8Path,
+    new_resource: NewResource,
+) -> Result<CommandOutput, YypBossError> {
+    let (yy_file, associated_data) =
+        read_new_resource::<T>(new_resource, yyp_boss.tcu(), working_directory)?;
+
+    match yyp_boss.set_resource(yy_file, associated_data) {
+        Ok(Some(old)) => resource_output(old, working_directory),
+        Ok(None) => Ok(CommandOutput::ok()),
+        Err(e) => Err(YypBossError::ResourceManipulation {
+            data: e.to_string(),
+        }),
+    }
+}
+
+fn resource_output<T: YyResource>(
+    (yy, assoc): (T, Option<T::AssociatedData>),
+    working_directory: &Utf8Path,
+) -> Result<CommandOutput, YypBossError> {
+    match serialize_yy_data_for_output(&yy, working_directory, assoc.as_ref()) {
+        Ok((yy, assoc)) => Ok(CommandOutput::ok_datum(yy, assoc)),
+        Err(e) => Err(YypBossError::CouldNotOutputData {
+            data: e.to_string(),
+        }),
+    }
+}
+
 fn remove<T: YyResource>(
     yyp_boss: &mut YypBoss,
     working_directory: &Utf8Path,
     resource_name: String,
 ) -> Result<CommandOutput, YypBossError> {
     match yyp_boss.remove_resource::<T>(&resource_name) {
-        Ok(output) => {
-            match serialize_yy_data_for_output(&output.0, working_directory, output.1.as_ref()) {
-                Ok((yy, assoc)) => Ok(CommandOutput::ok_datum(yy, assoc)),
-                Err(e) => Err(YypBossError::CouldNotOutputData {
-                    data: e.to_string(),
-                }),
-            }
-        }
+        Ok(output) => resource_output(output, working_directory),
         Err(e) => Err(YypBossError::ResourceManipulation {
             data: e.to_string(),
         }),
@@ -535,6 +837,30 @@ fn read_new_resource<T: YyResource>(
     Ok((value, associated_data))
 }
 
+fn json_output<T: serde::Serialize + ?Sized>(value: &T) -> SerializedData {
+    SerializedData::Value {
+        data: serde_json::to_string_pretty(value).unwrap(),
+    }
+}
+
+fn read_included_file(
+    data: SerializedData,
+    working_directory: &Utf8Path,
+) -> Result<Vec<u8>, YypBossError> {
+    match data {
+        SerializedData::Value { data } => Ok(data.into_bytes()),
+        SerializedData::Filepath { data } => {
+            let data = Utf8PathBuf::from_path_buf(data).expect("non-utf8 path");
+            std::fs::read(working_directory.join(data)).map_err(|e| {
+                YypBossError::AssociatedDataParseError {
+                    data: e.to_string(),
+                }
+            })
+        }
+        SerializedData::DefaultValue => Ok(vec![]),
+    }
+}
+
 fn serialize_yy_data_for_output<T: YyResource>(
     yy: &T,
     working_directory: &Utf8Path,
@@ -577,3 +903,166 @@ fn create_yy<T: YyResource>(cr: CreateCommand) -> Result<CommandOutput, YypBossE
         data: serde_json::to_string_pretty(&yy).unwrap(),
     }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ConflictResolution;
+    use std::path::PathBuf;
+
+    #[test]
+    fn sounds() {
+        let mut yyp_boss = YypBoss::default();
+        let create = Command::Utilities(UtilityCommand::Create(CreateCommand {
+            resource: Resource::Sound,
+            name: Some("snd_a".to_string()),
+            parent: None,
+        }));
+        let sound = match run_command(create, Utf8Path::new(""), &mut yyp_boss)
+            .unwrap()
+            .resource
+        {
+            Some(SerializedData::Value { data }) => serde_json::from_str::<Sound>(&data).unwrap(),
+            other => panic!("expected a sound, got {:?}", other),
+        };
+        yyp_boss.add_resource(sound, vec![0, 1, 2]).unwrap();
+
+        let get = Command::Resource(ResourceCommand {
+            command_type: ResourceCommandType::Get {
+                identifier: "snd_a".to_string(),
+            },
+            resource: Resource::Sound,
+        });
+        let output = run_command(get, Utf8Path::new(""), &mut yyp_boss).unwrap();
+        assert!(output.resource.is_some());
+    }
+
+    #[test]
+    fn batch_rolls_back() {
+        let mut yyp_boss = YypBoss::default();
+        let add = |path: &str| {
+            Command::IncludedFiles(IncludedFilesCommand::Add {
+                path: PathBuf::from(path),
+                data: SerializedData::DefaultValue,
+            })
+        };
+
+        let output = run_command(
+            Command::Batch {
+                commands: vec![add("a.txt"), add("b.txt"), add("a.txt")],
+            },
+            Utf8Path::new(""),
+            &mut yyp_boss,
+        );
+        match output {
+            Err(YypBossError::BatchFailed { index, .. }) => assert_eq!(index, 2),
+            other => panic!("expected the batch to fail, got {:?}", other),
+        }
+        assert!(yyp_boss.included_files.files().is_empty());
+
+        let output = run_command(
+            Command::Batch {
+                commands: vec![add("a.txt"), add("b.txt")],
+            },
+            Utf8Path::new(""),
+            &mut yyp_boss,
+        )
+        .unwrap();
+        assert_eq!(output.batch_outputs.unwrap().len(), 2);
+        assert_eq!(yyp_boss.included_files.files().len(), 2);
+
+        let output = run_command(
+            Command::Batch {
+                commands: vec![
+                    add("c.txt"),
+                    Command::Serialize {
+                        conflicts: ConflictResolution::default(),
+                    },
+                ],
+            },
+            Utf8Path::new(""),
+            &mut yyp_boss,
+        );
+        assert!(matches!(
+            output,
+            Err(YypBossError::BatchFailed { index: 1, .. })
+        ));
+        assert_eq!(yyp_boss.included_files.files().len(), 2);
+
+        let output = run_command(
+            Command::Batch {
+                commands: vec![add("c.txt"), Command::SerializePlan],
+            },
+            Utf8Path::new(""),
+            &mut yyp_boss,
+        );
+        assert!(matches!(
+            output,
+            Err(YypBossError::BatchFailed { index: 1, .. })
+        ));
+        assert_eq!(yyp_boss.included_files.files().len(), 2);
+
+        // groups and every kind of change to included files roll back too
+        let files = yyp_boss.included_files.files().to_vec();
+        let create_group = Command::TextureGroups(TextureGroupCommand::Create {
+            name: "tg_ui".to_string(),
+            parent: None,
+        });
+        let output = run_command(
+            Command::Batch {
+                commands: vec![
+                    create_group.clone(),
+                    Command::IncludedFiles(IncludedFilesCommand::Rename {
+                        path: PathBuf::from("a.txt"),
+                        new_name: "c.txt".to_string(),
+                    }),
+                    Command::IncludedFiles(IncludedFilesCommand::Remove {
+                        path: PathBuf::from("b.txt"),
+                    }),
+                    add("c.txt"),
+                ],
+            },
+            Utf8Path::new(""),
+            &mut yyp_boss,
+        );
+        assert!(matches!(
+            output,
+            Err(YypBossError::BatchFailed { index: 3, .. })
+        ));
+        assert!(yyp_boss.texture_groups.texture_groups().is_empty());
+        assert_eq!(yyp_boss.included_files.files(), files);
+
+        // and a batch which succeeds is undone as one step
+        run_command(
+            Command::Batch {
+                commands: vec![
+                    create_group,
+                    Command::IncludedFiles(IncludedFilesCommand::Remove {
+                        path: PathBuf::from("a.txt"),
+                    }),
+                ],
+            },
+            Utf8Path::new(""),
+            &mut yyp_boss,
+        )
+        .unwrap();
+        assert_eq!(yyp_boss.included_files.files().len(), 1);
+        run_command(Command::Undo, Utf8Path::new(""), &mut yyp_boss).unwrap();
+        assert!(yyp_boss.texture_groups.texture_groups().is_empty());
+        assert_eq!(yyp_boss.included_files.files(), files);
+
+        let output = run_command(
+            Command::Batch {
+                commands: vec![Command::Batch {
+                    commands: vec![Command::Redo],
+                }],
+            },
+            Utf8Path::new(""),
+            &mut yyp_boss,
+        );
+        assert!(matches!(
+            output,
+            Err(YypBossError::BatchFailed { index: 0, .. })
+        ));
+    }
+}
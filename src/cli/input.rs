@@ -1,7 +1,8 @@
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 use yy_typings::{ViewPath, ViewPathLocation};
 
-use crate::{Resource, SerializedData};
+use crate::{ConflictResolution, Resource, SerializedData, TextureGroupOptions};
 
 /// The type of command to give, pertaining to each of the general areas the YyBoss can give.
 ///
@@ -25,14 +26,76 @@ pub enum Command {
     /// [`VfsCommand`]: ./enum.VfsCommand.html
     VirtualFileSystem(VfsCommand),
 
+    /// A command type pertaining to Included Files. To see the subcommand for Included Files, see
+    /// [`IncludedFilesCommand`].
+    ///
+    /// [`IncludedFilesCommand`]: ./enum.IncludedFilesCommand.html
+    IncludedFiles(IncludedFilesCommand),
+
+    /// A command type pertaining to Texture Groups. To see the subcommand for Texture Groups, see
+    /// [`TextureGroupCommand`].
+    ///
+    /// [`TextureGroupCommand`]: ./enum.TextureGroupCommand.html
+    TextureGroups(TextureGroupCommand),
+
+    /// A command type pertaining to Audio Groups. To see the subcommand for Audio Groups, see
+    /// [`AudioGroupCommand`].
+    ///
+    /// [`AudioGroupCommand`]: ./enum.AudioGroupCommand.html
+    AudioGroups(AudioGroupCommand),
+
     /// Various utilities to aid developers working with files. These commands help work with generated data.
     ///
     /// [`CreateCommand`]: ./struct.CreateCommand.html
     Utilities(UtilityCommand),
 
+    /// Runs a list of commands in order, all-or-nothing. If any command fails, every change the
+    /// batch made is rolled back, and the error gives the index of the failing command.
+    ///
+    /// On success, the output of each command is returned in `batchOutputs`.
+    ///
+    /// ## Errors
+    /// A batch cannot contain a [`Serialize`] command, since writing to disk cannot be rolled back,
+    /// a [`SerializePlan`] command, which reads the whole project, or an [`Undo`] or [`Redo`]
+    /// command.
+    ///
+    /// [`Serialize`]: #variant.Serialize
+    /// [`SerializePlan`]: #variant.SerializePlan
+    /// [`Undo`]: #variant.Undo
+    /// [`Redo`]: #variant.Redo
+    Batch { commands: Vec<Command> },
+
+    /// Undoes the last change made to the project, such as adding, removing, renaming or moving a
+    /// resource, or any change to a folder, a texture or audio group, an included file or an
+    /// object's events. A [`Batch`] is undone as a whole.
+    ///
+    /// If there is nothing to undo, this does nothing.
+    ///
+    /// [`Batch`]: #variant.Batch
+    Undo,
+
+    /// Redoes the last change which was undone. Any other change clears what can be redone.
+    ///
+    /// If there is nothing to redo, this does nothing.
+    Redo,
+
     /// A command type to serialize current changes. This currently serializes all changes which the YypBoss
     /// tracks, including Assets and Pipelines.
-    Serialize,
+    ///
+    /// If a resource changed on disk since it was loaded, `conflicts` decides what happens to it. By
+    /// default, nothing is written and an error is returned. What happened to each such resource is
+    /// returned in `serializeReport`.
+    Serialize {
+        #[serde(default)]
+        conflicts: ConflictResolution,
+    },
+
+    /// Returns what [`Serialize`] would do if it was run now, in `serializePlan`, without changing
+    /// anything. This lists the files each kind of resource would write or delete, and the changes
+    /// to the Yyp.
+    ///
+    /// [`Serialize`]: #variant.Serialize
+    SerializePlan,
 }
 
 /// A resource command, which will allow users to read and write resources
@@ -76,6 +139,33 @@ pub enum ResourceCommandType {
     /// If it succeeds, it will return without any extra data, like a `void`.
     Add(NewResource),
 
+    /// Replaces a resource in the project. The resource keeps its place in the Asset Tree, and
+    /// files which only the old resource used, such as old frames, are removed on serialization.
+    ///
+    /// ## Errors
+    /// If there isn't a resource by that name of the type given, this command will abort and
+    /// return an error.
+    ///
+    /// ## Returns
+    /// If this command succeeds, it will return the old resource and its associated data,
+    /// if it was loaded.
+    Replace(NewResource),
+
+    /// Adds a resource to the project, or replaces it if it already exists, as in [`Add`]
+    /// and [`Replace`].
+    ///
+    /// ## Errors
+    /// If there is a resource of a different type by that name, or the name is invalid, this
+    /// command will abort and return an error.
+    ///
+    /// ## Returns
+    /// If a resource was replaced, it will return the old resource and its associated data,
+    /// if it was loaded. Otherwise, it will return without any extra data, like a `void`.
+    ///
+    /// [`Add`]: #variant.Add
+    /// [`Replace`]: #variant.Replace
+    Set(NewResource),
+
     /// Removes and returns the resource.
     ///
     /// ## Errors
@@ -164,7 +254,7 @@ pub enum ResourceCommandType {
 /// ## Types of Each Field
 ///|   Resource Type  |   new_resource     | associated_data   |
 ///|------------------|-----------------------|----------------------|
-///| [`Sprite`]       |  [`Sprite Yy File`]   | [`HashMap`]<[`Frame Uuid`], [`SpriteImageBuffer`]>  |
+///| [`Sprite`]       |  [`Sprite Yy File`]   | [`HashMap`]<[`Frame Uuid`], [`SpriteFrameLayers`]>  |
 ///| [`Object`]       |  [`Object Yy File`]   | [`HashMap`]<[`EventType`], [`String`]>              |
 ///| [`Script`]       |  [`Script Yy File`]   | String                                       |
 ///| [`Shader`]       |  [`Shader Yy File`]   | [`ShaderScriptType`] |
@@ -190,6 +280,7 @@ pub enum ResourceCommandType {
 /// [`Frame Uuid`]: ../../yy_typings/sprite_yy/struct.Frame.html#structfield.name
 /// [`EventType`]: ../../yy_typings/sprite_yy/object_yy/enum.EventType.html
 /// [`ShaderScriptType`]: ./error.html
+/// [`SpriteFrameLayers`]: ../type.SpriteFrameLayers.html
 /// [`HashMap`]: ../../../std/collects/struct.HashMap.html
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -318,6 +409,209 @@ pub enum VfsCommand {
     GetPathType { path: ViewPath },
 }
 
+/// The Included Files command type to run. Every path is relative to the `datafiles` directory,
+/// so a file at `datafiles/localisation/en.csv` is given as `localisation/en.csv`.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(tag = "subCommand")]
+pub enum IncludedFilesCommand {
+    /// Adds a new Included File.
+    ///
+    /// ## Errors
+    /// If there is already a file at the path, or if the path leaves the `datafiles` directory,
+    /// this command aborts and returns an error.
+    Add {
+        /// The path of the new file.
+        path: PathBuf,
+        /// The contents of the new file. A `Value` is written as utf8, a `Filepath` is copied from
+        /// the working directory, and a `DefaultValue` makes an empty file.
+        data: SerializedData,
+    },
+
+    /// Removes an Included File.
+    ///
+    /// ## Errors
+    /// If there isn't a file at the path, this command aborts and returns an error.
+    Remove {
+        /// The path of the file to remove.
+        path: PathBuf,
+    },
+
+    /// Renames an Included File, keeping it in the same directory.
+    ///
+    /// ## Errors
+    /// If there isn't a file at the path, or if the new name is taken, this command
+    /// aborts and returns an error.
+    #[serde(rename_all = "camelCase")]
+    Rename {
+        /// The path of the file to rename.
+        path: PathBuf,
+        /// The new file name, such as `en_US.csv`.
+        new_name: String,
+    },
+
+    /// Moves an Included File into another directory, which is created if needed.
+    ///
+    /// ## Errors
+    /// If there isn't a file at the path, or if there is already a file of the same name in
+    /// the new directory, this command aborts and returns an error.
+    #[serde(rename_all = "camelCase")]
+    Move {
+        /// The path of the file to move.
+        path: PathBuf,
+        /// The directory to move the file into. An empty path is the `datafiles` directory itself.
+        new_directory: PathBuf,
+    },
+
+    /// Returns a boolean indicating if an Included File exists at the path.
+    ///
+    /// ## Errors
+    /// This command is infallible.
+    Exists {
+        /// The path of the file to check.
+        path: PathBuf,
+    },
+}
+
+/// The Texture Group command type to run.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(tag = "subCommand")]
+pub enum TextureGroupCommand {
+    /// Creates a new Texture Group.
+    ///
+    /// ## Errors
+    /// If the name is taken or invalid, or if the parent does not exist, this command aborts
+    /// and returns an error.
+    Create {
+        /// The name of the new group.
+        name: String,
+        /// The parent of the new group, if it has one.
+        parent: Option<String>,
+    },
+
+    /// Renames a Texture Group. Every Sprite and TileSet in the group is moved to the new name.
+    ///
+    /// ## Errors
+    /// If the group does not exist, is the `Default` group, or if the new name is taken or
+    /// invalid, this command aborts and returns an error.
+    #[serde(rename_all = "camelCase")]
+    Rename {
+        /// The name of the group to rename.
+        name: String,
+        /// The new name of the group.
+        new_name: String,
+    },
+
+    /// Removes a Texture Group. Every Sprite and TileSet in the group is moved to the group's
+    /// parent, or to the `Default` group if it had no parent.
+    ///
+    /// ## Errors
+    /// If the group does not exist or is the `Default` group, this command aborts and returns
+    /// an error.
+    Remove {
+        /// The name of the group to remove.
+        name: String,
+    },
+
+    /// Sets the parent of a Texture Group. A `null` parent removes the group's parent.
+    ///
+    /// ## Errors
+    /// If either group does not exist, or if the parent is the group itself or one of its
+    /// children, this command aborts and returns an error.
+    SetParent {
+        /// The name of the group to edit.
+        name: String,
+        /// The new parent of the group.
+        parent: Option<String>,
+    },
+
+    /// Sets the options of a Texture Group. Options which are not given are left alone.
+    ///
+    /// ## Errors
+    /// If the group does not exist, this command aborts and returns an error.
+    SetOptions {
+        /// The name of the group to edit.
+        name: String,
+        /// The options to set.
+        options: TextureGroupOptions,
+    },
+
+    /// Returns a Texture Group as JSON in the `resource` field.
+    ///
+    /// ## Errors
+    /// If the group does not exist, this command aborts and returns an error.
+    Get {
+        /// The name of the group.
+        name: String,
+    },
+
+    /// Returns every Texture Group as a JSON array in the `resource` field.
+    ///
+    /// ## Errors
+    /// This command is infallible.
+    GetAll,
+}
+
+/// The Audio Group command type to run.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(tag = "subCommand")]
+pub enum AudioGroupCommand {
+    /// Creates a new Audio Group, which is built for every platform.
+    ///
+    /// ## Errors
+    /// If the name is taken or invalid, this command aborts and returns an error.
+    Create {
+        /// The name of the new group.
+        name: String,
+    },
+
+    /// Renames an Audio Group. Every Sound in the group is moved to the new name.
+    ///
+    /// ## Errors
+    /// If the group does not exist, is the default group, or if the new name is taken or
+    /// invalid, this command aborts and returns an error.
+    #[serde(rename_all = "camelCase")]
+    Rename {
+        /// The name of the group to rename.
+        name: String,
+        /// The new name of the group.
+        new_name: String,
+    },
+
+    /// Removes an Audio Group. Every Sound in the group is moved to the default group.
+    ///
+    /// ## Errors
+    /// If the group does not exist or is the default group, this command aborts and returns
+    /// an error.
+    Remove {
+        /// The name of the group to remove.
+        name: String,
+    },
+
+    /// Sets the platforms which an Audio Group is built for, as a bitmask. `-1` is every platform.
+    ///
+    /// ## Errors
+    /// If the group does not exist, this command aborts and returns an error.
+    SetTargets {
+        /// The name of the group to edit.
+        name: String,
+        /// The platform bitmask.
+        targets: isize,
+    },
+
+    /// Returns every Audio Group as a JSON array in the `resource` field.
+    ///
+    /// ## Errors
+    /// This command is infallible.
+    GetAll,
+
+    /// Returns a JSON object in the `resource` field, mapping each Audio Group's name to the
+    /// names of the Sounds in it.
+    ///
+    /// ## Errors
+    /// This command is infallible.
+    SoundsByGroup,
+}
+
 /// Utilities for the YypBoss to run. None of these commands will ever return an error.
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 #[serde(tag = "subCommand")]
@@ -406,6 +700,16 @@ mod tests {
             resource: Resource::Script,
         }));
 
+        harness(Command::Resource(ResourceCommand {
+            command_type: ResourceCommandType::Replace(NewResource {
+                new_resource: SerializedData::Value {
+                    data: "Hello".to_string(),
+                },
+                associated_data: SerializedData::DefaultValue,
+            }),
+            resource: Resource::Sprite,
+        }));
+
         harness(Command::Resource(ResourceCommand {
             command_type: ResourceCommandType::Get {
                 identifier: "Something".to_string(),
@@ -439,8 +743,46 @@ mod tests {
         }));
 
         harness(Command::VirtualFileSystem(VfsCommand::GetFullVfs));
+        harness(Command::IncludedFiles(IncludedFilesCommand::Rename {
+            path: PathBuf::from("localisation/en.csv"),
+            new_name: "en_US.csv".to_string(),
+        }));
+        harness(Command::TextureGroups(TextureGroupCommand::SetOptions {
+            name: "Crops".to_string(),
+            options: TextureGroupOptions {
+                autocrop: Some(false),
+                targets: Some(-1),
+                ..TextureGroupOptions::default()
+            },
+        }));
+        harness(Command::AudioGroups(AudioGroupCommand::Rename {
+            name: "audiogroup_music".to_string(),
+            new_name: "audiogroup_bgm".to_string(),
+        }));
+        harness(Command::Batch {
+            commands: vec![
+                Command::IncludedFiles(IncludedFilesCommand::Exists {
+                    path: PathBuf::from("localisation/en.csv"),
+                }),
+                Command::Batch { commands: vec![] },
+            ],
+        });
         harness(Command::VirtualFileSystem(VfsCommand::GetPathType {
             path: ViewPath::default(),
         }));
+        harness(Command::Undo);
+        harness(Command::Redo);
+        harness(Command::SerializePlan);
+        harness(Command::Serialize {
+            conflicts: ConflictResolution::Merge,
+        });
+
+        let command: Command = serde_json::from_str(r#"{ "type": "Serialize" }"#).unwrap();
+        assert_eq!(
+            command,
+            Command::Serialize {
+                conflicts: ConflictResolution::Refuse
+            }
+        );
     }
 }
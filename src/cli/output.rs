@@ -1,10 +1,9 @@
-use log::error;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use thiserror::Error;
 use crate::{
     folders::{FlatFolderGraph, Item},
-    ProjectMetadata, SerializedData,
+    ProjectMetadata, SerializePlan, SerializeReport, SerializedData,
 };
 use yy_typings::ViewPath;
 
@@ -72,6 +71,15 @@ pub struct CommandOutput {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub project_metadata: Option<ProjectMetadata>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub batch_outputs: Option<Vec<CommandOutput>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub serialize_plan: Option<SerializePlan>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub serialize_report: Option<SerializeReport>,
 }
 
 impl CommandOutput {
@@ -178,6 +186,30 @@ impl CommandOutput {
             ..Self::default()
         }
     }
+
+    pub fn ok_batch(outputs: Vec<CommandOutput>) -> Self {
+        Self {
+            success: true,
+            batch_outputs: Some(outputs),
+            ..Self::default()
+        }
+    }
+
+    pub fn ok_serialize_plan(plan: SerializePlan) -> Self {
+        Self {
+            success: true,
+            serialize_plan: Some(plan),
+            ..Self::default()
+        }
+    }
+
+    pub fn ok_serialize_report(report: SerializeReport) -> Self {
+        Self {
+            success: true,
+            serialize_report: Some(report),
+            ..Self::default()
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -209,6 +241,12 @@ pub enum YypBossError {
     #[error("could not serialize yypboss...coarse error {}", .data)]
     CouldNotSerializeYypBoss { data: String },
 
+    #[error("command {} of the batch failed, so the batch was rolled back. error: {}", .index, .error)]
+    BatchFailed {
+        index: usize,
+        error: Box<YypBossError>,
+    },
+
     #[error("internal error -- command could not be executed. error is fatal: {}", .fatal)]
     InternalError { fatal: bool },
 }
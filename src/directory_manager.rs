@@ -1,7 +1,7 @@
 use super::errors::StartupError;
 use std::path::{Path, PathBuf};
 
-#[derive(Debug, PartialEq, Eq, Default)]
+#[derive(Debug, PartialEq, Eq, Default, Clone)]
 pub struct DirectoryManager {
     /// The path to the folder which houses the Yyp and the various other folders
     /// that Gms2 projects need, such as `sprites` or `objects`.
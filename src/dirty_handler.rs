@@ -1,8 +1,5 @@
 use crate::FileHolder;
-use std::{
-    borrow::Borrow,
-    collections::{hash_map::Drain, HashMap},
-};
+use std::{borrow::Borrow, collections::HashMap};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Ord, PartialOrd, Hash)]
 pub enum DirtyState {
@@ -55,9 +52,9 @@ impl<R: std::hash::Hash + Eq + Clone, A: Default> DirtyHandler<R, A> {
         F: Fn(DirtyValueHolder<'a, A>),
     {
         let dirty_state = match self.resources_to_reserialize.remove(&new_value) {
+            // nothing was ever written, so there's nothing to clean up
             Some(DirtyState::New) => DirtyState::New,
-            Some(DirtyState::Edit) => DirtyState::Edit,
-            None => {
+            Some(DirtyState::Edit) | None => {
                 let inner = self
                     .associated_values
                     .as_mut()
@@ -97,23 +94,36 @@ impl<R: std::hash::Hash + Eq + Clone, A: Default> DirtyHandler<R, A> {
         }
     }
 
-    pub fn drain_all(&mut self) -> DirtyDrain<'_, R, A> {
-        DirtyDrain {
-            resources_to_reserialize: self.resources_to_reserialize.drain(),
-            resources_to_remove: self.resources_to_remove.drain(),
-            associated_values: self.associated_values.as_mut().map(|v| v.drain()),
-        }
-    }
-
-    #[allow(dead_code)]
     pub fn resources_to_reserialize(&self) -> &HashMap<R, DirtyState> {
         &self.resources_to_reserialize
     }
 
-    #[allow(dead_code)]
     pub fn resources_to_remove(&self) -> &HashMap<R, DirtyState> {
         &self.resources_to_remove
     }
+
+    pub fn associated_values(&self) -> Option<&HashMap<R, A>> {
+        self.associated_values.as_ref()
+    }
+
+    /// Forgets every change except those to the values for which `keep` returns `true`, once the
+    /// rest have been written.
+    pub fn retain(&mut self, keep: impl Fn(&R) -> bool) {
+        self.resources_to_reserialize.retain(|k, _| keep(k));
+        self.resources_to_remove.retain(|k, _| keep(k));
+        if let Some(associated_values) = &mut self.associated_values {
+            associated_values.retain(|k, _| keep(k));
+        }
+    }
+
+    /// Forgets every change, once they've all been written.
+    pub fn clear(&mut self) {
+        self.resources_to_reserialize.clear();
+        self.resources_to_remove.clear();
+        if let Some(associated_values) = &mut self.associated_values {
+            associated_values.clear();
+        }
+    }
 }
 
 pub struct DirtyValueHolder<'a, A>(&'a mut A);
@@ -123,12 +133,6 @@ impl<'a> FileHolder for DirtyValueHolder<'a, Vec<std::path::PathBuf>> {
     }
 }
 
-pub struct DirtyDrain<'a, R: std::hash::Hash + Eq + Clone, A> {
-    pub resources_to_reserialize: Drain<'a, R, DirtyState>,
-    pub resources_to_remove: Drain<'a, R, DirtyState>,
-    pub associated_values: Option<Drain<'a, R, A>>,
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -181,6 +185,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn replace_edited() {
+        let mut dirty_handler = dirty_handler();
+
+        dirty_handler.edit(a());
+        dirty_handler.replace_associated(a(), |v| *v.0 += 1);
+        dirty_handler.replace_associated(a(), |v| *v.0 += 1);
+
+        assert_eq!(
+            dirty_handler.resources_to_reserialize,
+            hashmap! {
+                a() => DirtyState::Edit,
+            }
+        );
+        assert_eq!(
+            dirty_handler.associated_values,
+            Some(hashmap! {
+                a() => 2
+            })
+        );
+
+        // new resources have nothing on disk to clean up
+        dirty_handler.add("b".to_string());
+        dirty_handler.replace_associated("b".to_string(), |v| *v.0 += 1);
+        assert_eq!(dirty_handler.associated_values.unwrap().get("b"), None);
+    }
+
     #[test]
     fn remove() {
         let mut dirty_handler = dirty_handler();
@@ -34,6 +34,9 @@ pub enum StartupError {
         error: String,
     },
 
+    #[error("couldn't recover an interrupted serialization -- {0}")]
+    InterruptedSerialization(String),
+
     #[error("a working directory path was given, but it was invalid")]
     BadWorkingDirectoryPath,
 
@@ -62,6 +65,30 @@ pub enum ResourceManipulationError {
     ResourceCannotBeManipulated,
 }
 
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum GroupError {
+    #[error("group already exists")]
+    NameCollision,
+
+    #[error("bad name given")]
+    BadName,
+
+    #[error("cannot find that group")]
+    NotFound,
+
+    #[error("the default group cannot be renamed or removed")]
+    DefaultGroup,
+
+    #[error("a group cannot be parented to itself or to one of its children")]
+    ParentCycle,
+
+    #[error("there is no default group to move the group's assets to")]
+    NoDefaultGroup,
+
+    #[error("couldn't move {0} to another group -- {1}")]
+    Reassign(String, String),
+}
+
 #[derive(Debug, Error)]
 pub enum YyResourceHandlerError {
     #[error(transparent)]
@@ -73,6 +100,52 @@ pub enum YyResourceHandlerError {
     #[error("the given resource was not found or managed on the type")]
     ResourceNotFound,
 
+    #[error("the given frame was not found on the sprite")]
+    FrameNotFound,
+
     #[error("we cannot force serialization because the associated data could not be found")]
     CannotForceSerialization,
+
+    #[error("name is already used")]
+    NameCollision,
+
+    #[error("bad name given")]
+    BadName,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("resources changed on disk since they were loaded -- {0:?}")]
+pub struct ConflictError(pub Vec<crate::ExternalResource>);
+
+/// Every error we ran into while staging a serialization. We keep staging after the first error,
+/// so that they can all be fixed at once.
+#[derive(Debug, Error)]
+#[error("couldn't serialize -- {}", .0.iter().map(|e| e.to_string()).collect::<Vec<_>>().join("; "))]
+pub struct SerializeErrors(pub Vec<anyhow::Error>);
+
+#[derive(Debug, Error)]
+pub enum AsepriteError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error("not an aseprite file")]
+    BadMagic,
+
+    #[error("aseprite file ended early")]
+    UnexpectedEnd,
+
+    #[error("aseprite file has no size")]
+    NoSize,
+
+    #[error("unsupported color depth -- {0}")]
+    UnsupportedColorDepth(u16),
+
+    #[error("bad cel in frame {frame} -- {reason}")]
+    BadCel { frame: usize, reason: String },
+
+    #[error("bad palette in frame {0}")]
+    BadPalette(usize),
+
+    #[error("{0} is not a valid resource name")]
+    BadName(String),
 }
@@ -0,0 +1,257 @@
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeMap, BTreeSet},
+    fs,
+    hash::{Hash, Hasher},
+    io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// What every file within a resource's directory looked like on disk when we last saw it. We
+/// compare it to what's on disk when we serialize, to find out if something else changed the
+/// resource.
+///
+/// Taking a fingerprint only reads each file's metadata. The files are hashed once the resource
+/// is changed, with [`hash_unchanged`], so that we can tell which files we changed if we have to
+/// merge; resources which are only read are never hashed.
+///
+/// [`hash_unchanged`]: #method.hash_unchanged
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub(crate) struct Fingerprint {
+    directory: PathBuf,
+    files: BTreeMap<PathBuf, Stamp>,
+    hashed: bool,
+}
+
+/// A file's length and modification time, and the hash of its contents once we've taken it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Stamp {
+    len: u64,
+    modified: Option<SystemTime>,
+    hash: Option<u64>,
+}
+
+impl Stamp {
+    fn of(metadata: &fs::Metadata) -> Self {
+        Self {
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+            hash: None,
+        }
+    }
+
+    /// Returns `true` if the file's metadata hasn't changed. Without a modification time, we
+    /// can't tell, so we assume it has.
+    fn matches(&self, other: &Stamp) -> bool {
+        self.len == other.len && self.modified.is_some() && self.modified == other.modified
+    }
+}
+
+impl Fingerprint {
+    /// Reads the metadata of every file within `directory`, keyed by its path relative to
+    /// `directory`. A directory which doesn't exist has an empty fingerprint.
+    pub fn of(directory: &Path) -> io::Result<Self> {
+        let mut files = BTreeMap::new();
+        if directory.is_dir() {
+            stamp_directory(directory, Path::new(""), &mut files)?;
+        }
+
+        Ok(Self {
+            directory: directory.to_owned(),
+            files,
+            hashed: false,
+        })
+    }
+
+    /// Hashes every file which hasn't changed on disk since the fingerprint was taken. This is
+    /// done the first time the resource is changed, before anything else is likely to change it
+    /// too. Files which already changed are left unhashed, and can't be merged.
+    pub fn hash_unchanged(&mut self) -> io::Result<()> {
+        if self.hashed {
+            return Ok(());
+        }
+
+        for (relative, stamp) in self.files.iter_mut() {
+            let path = self.directory.join(relative);
+            let current = match fs::metadata(&path) {
+                Ok(metadata) => Stamp::of(&metadata),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e),
+            };
+
+            if stamp.matches(&current) {
+                stamp.hash = Some(hash_file(&path)?);
+            }
+        }
+        self.hashed = true;
+
+        Ok(())
+    }
+
+    /// Returns `true` if `directory` no longer looks like it did when the fingerprint was taken.
+    /// A file whose metadata changed is only hashed if we hashed it before, in case it was
+    /// rewritten with the same contents.
+    pub fn diverged(&self, directory: &Path) -> io::Result<bool> {
+        let disk = Self::of(directory)?;
+        if self.files.keys().ne(disk.files.keys()) {
+            return Ok(true);
+        }
+
+        for ((relative, base), current) in self.files.iter().zip(disk.files.values()) {
+            if base.matches(current) {
+                continue;
+            }
+
+            match base.hash {
+                Some(hash) if base.len == current.len => {
+                    if hash_file(&directory.join(relative))? != hash {
+                        return Ok(true);
+                    }
+                }
+                _ => return Ok(true),
+            }
+        }
+
+        Ok(false)
+    }
+}
+
+/// Merges the changes made to `directory` since it looked like `base` into `staged`, which is
+/// what we're about to write over `directory`.
+///
+/// A file which changed on disk, but which we didn't change, is taken from disk. If a file changed
+/// on both sides, and not in the same way, nothing is merged and `false` is returned. Only the
+/// files which changed on disk are hashed, and we can only tell that we didn't change one if
+/// [`Fingerprint::hash_unchanged`] hashed it first.
+///
+/// [`Fingerprint::hash_unchanged`]: struct.Fingerprint.html#method.hash_unchanged
+pub(crate) fn merge(base: &Fingerprint, directory: &Path, staged: &Path) -> io::Result<bool> {
+    let disk = Fingerprint::of(directory)?;
+
+    let files: BTreeSet<&PathBuf> = base.files.keys().chain(disk.files.keys()).collect();
+    let mut from_disk = Vec::new();
+    for file in files {
+        let (base, current) = (base.files.get(file), disk.files.get(file));
+        let unchanged = match (base, current) {
+            (Some(base), Some(current)) => base.matches(current),
+            (None, None) => true,
+            _ => false,
+        };
+        if unchanged {
+            continue;
+        }
+
+        let disk = hash_existing(&directory.join(file))?;
+        let ours = hash_existing(&staged.join(file))?;
+        if disk == ours {
+            continue;
+        }
+
+        let base = match base {
+            Some(Stamp {
+                hash: Some(hash), ..
+            }) => Some(*hash),
+            Some(_) => return Ok(false),
+            None => None,
+        };
+        if ours == base {
+            from_disk.push((file, disk.is_some()));
+        } else {
+            return Ok(false);
+        }
+    }
+
+    for (file, exists) in from_disk {
+        let destination = staged.join(file);
+        if exists {
+            fs::create_dir_all(destination.parent().unwrap())?;
+            fs::copy(directory.join(file), destination)?;
+        } else {
+            fs::remove_file(destination)?;
+        }
+    }
+
+    Ok(true)
+}
+
+fn stamp_directory(
+    path: &Path,
+    relative: &Path,
+    output: &mut BTreeMap<PathBuf, Stamp>,
+) -> io::Result<()> {
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let relative = relative.join(entry.file_name());
+
+        let metadata = entry.metadata()?;
+        if metadata.is_dir() {
+            stamp_directory(&entry.path(), &relative, output)?;
+        } else {
+            output.insert(relative, Stamp::of(&metadata));
+        }
+    }
+
+    Ok(())
+}
+
+fn hash_file(path: &Path) -> io::Result<u64> {
+    let mut hasher = DefaultHasher::new();
+    fs::read(path)?.hash(&mut hasher);
+
+    Ok(hasher.finish())
+}
+
+/// Hashes the file at `path`, if there is one.
+fn hash_existing(path: &Path) -> io::Result<Option<u64>> {
+    if path.is_file() {
+        hash_file(path).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TempDirectory;
+
+    #[test]
+    fn merge_disjoint_changes() {
+        let root = TempDirectory::new();
+        let (directory, staged) = (root.join("directory"), root.join("staged"));
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join("a.yy"), "a").unwrap();
+        fs::write(directory.join("a.gml"), "a").unwrap();
+        let mut base = Fingerprint::of(&directory).unwrap();
+        base.hash_unchanged().unwrap();
+
+        // we change the yy, while someone else changes the gml
+        fs::create_dir_all(&staged).unwrap();
+        fs::write(staged.join("a.yy"), "ours").unwrap();
+        fs::write(staged.join("a.gml"), "a").unwrap();
+        fs::write(directory.join("a.gml"), "theirs").unwrap();
+
+        assert!(merge(&base, &directory, &staged).unwrap());
+        assert_eq!(fs::read_to_string(staged.join("a.yy")).unwrap(), "ours");
+        assert_eq!(fs::read_to_string(staged.join("a.gml")).unwrap(), "theirs");
+
+        assert!(base.diverged(&directory).unwrap());
+
+        // now we both change the yy
+        let mut base = Fingerprint::of(&directory).unwrap();
+        assert!(base.diverged(&directory).unwrap() == false);
+        base.hash_unchanged().unwrap();
+        fs::write(directory.join("a.yy"), "theirs").unwrap();
+        assert!(merge(&base, &directory, &staged).unwrap() == false);
+
+        // a file we never hashed can't be merged
+        let base = Fingerprint::of(&directory).unwrap();
+        fs::write(directory.join("a.gml"), "theirs again").unwrap();
+        assert!(merge(&base, &directory, &staged).unwrap() == false);
+
+        assert!(Fingerprint::of(&root.join("missing"))
+            .unwrap()
+            .files
+            .is_empty());
+    }
+}
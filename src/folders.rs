@@ -3,10 +3,7 @@ use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
 use yy_typings::{FilesystemPath, ViewPath, ViewPathLocation, YypFolder, YypResource};
 
-use crate::{
-    dirty_handler::{DirtyDrain, DirtyHandler},
-    PathStrExt, Resource, ViewPathLocationExt, YyResource,
-};
+use crate::{dirty_handler::DirtyHandler, PathStrExt, Resource, ViewPathLocationExt, YyResource};
 
 static ROOT_FOLDER_VIEW_PATH: once_cell::sync::Lazy<ViewPathLocation> =
     once_cell::sync::Lazy::new(ViewPathLocation::root_folder);
@@ -65,16 +62,28 @@ impl Vfs {
     }
 
     pub(crate) fn load_in_file<T: YyResource>(&mut self, yy: &T) -> Result<(), FolderGraphError> {
+        self.load_in_listing(yy.name(), T::RESOURCE, &yy.parent_view_path())
+    }
+
+    /// Loads in a resource we only know the name, kind and parent of, such as one which hasn't
+    /// been read yet in a lazy project.
+    pub(crate) fn load_in_listing(
+        &mut self,
+        name: &str,
+        resource: Resource,
+        parent: &ViewPath,
+    ) -> Result<(), FolderGraphError> {
         // Add to the folder graph
-        let folder =
-            Vfs::get_folder_mut(&mut self.root, &yy.parent_view_path().path).ok_or_else(|| {
-                FolderGraphError::PathNotFound {
-                    path: yy.parent_view_path().path.inner().to_string(),
-                }
-            })?;
+        let folder = Vfs::get_folder_mut(&mut self.root, &parent.path).ok_or_else(|| {
+            FolderGraphError::PathNotFound {
+                path: parent.path.inner().to_string(),
+            }
+        })?;
 
         // add and sort
-        folder.files.load_in(yy, &mut self.resource_names);
+        folder
+            .files
+            .load_in(name, resource, &parent.path, &mut self.resource_names);
 
         Ok(())
     }
@@ -241,6 +250,27 @@ impl Vfs {
     pub fn remove_empty_folder(
         &mut self,
         folder_path: &ViewPathLocation,
+    ) -> Result<(), FolderGraphError> {
+        self.detach_empty_folder(folder_path)?;
+
+        // mark the remove as dirty...
+        self.dirty_handler.remove(folder_path);
+
+        Ok(())
+    }
+
+    /// Removes an empty folder which is already gone from the Yyp on disk, without marking it for
+    /// removal.
+    pub(crate) fn unload_empty_folder(
+        &mut self,
+        folder_path: &ViewPathLocation,
+    ) -> Result<(), FolderGraphError> {
+        self.detach_empty_folder(folder_path)
+    }
+
+    fn detach_empty_folder(
+        &mut self,
+        folder_path: &ViewPathLocation,
     ) -> Result<(), FolderGraphError> {
         let original_folder =
             Self::get_folder_mut(&mut self.root, folder_path).ok_or_else(|| {
@@ -262,9 +292,6 @@ impl Vfs {
             let pos = parent.folders.iter().position(|v| v.name == name).unwrap();
             parent.folders.remove(pos);
 
-            // mark the remove as dirty...
-            self.dirty_handler.remove(folder_path);
-
             Ok(())
         } else {
             Err(FolderGraphError::CannotEditRootFolder)
@@ -355,49 +382,6 @@ impl Vfs {
         }
     }
 
-    pub(crate) fn remove_non_empty_folder(
-        &mut self,
-        folder_path: &ViewPathLocation,
-    ) -> Result<HashMap<FilesystemPath, ResourceDescriptor>, FolderGraphError> {
-        if Self::is_root(folder_path) {
-            return Err(FolderGraphError::CannotEditRootFolder);
-        }
-
-        let original_folder =
-            Self::get_folder_mut(&mut self.root, folder_path).ok_or_else(|| {
-                FolderGraphError::PathNotFound {
-                    path: folder_path.inner().to_string(),
-                }
-            })?;
-
-        fn remove_resource(
-            fg: &mut FolderGraph,
-            rn: &mut ResourceNames,
-            dh: &mut DirtyHandler<ViewPathLocation>,
-            buffer: &mut HashMap<FilesystemPath, ResourceDescriptor>,
-        ) {
-            fg.files.drain_into(rn, buffer);
-            for mut folder in fg.folders.drain(..) {
-                remove_resource(&mut folder, rn, dh, buffer);
-
-                // mark the remove as dirty...
-                dh.remove(&folder.view_path_location());
-            }
-        }
-
-        let mut buf = Default::default();
-        remove_resource(
-            original_folder,
-            &mut self.resource_names,
-            &mut self.dirty_handler,
-            &mut buf,
-        );
-
-        self.remove_empty_folder(folder_path)?;
-
-        Ok(buf)
-    }
-
     /// Checks if a resource with a given name exists. If it does, it will return information
     /// on that resource in the form of the `CreatedResource` token, which can tell the user
     /// the type of resource.
@@ -527,23 +511,26 @@ impl Vfs {
 
         dest.files.attach(path);
 
+        // and keep the descriptor in step, so we can find it again
+        if let Some(descriptor) = self.resource_names.names.get_mut(resource_to_move) {
+            descriptor.parent_location = new_parent.clone();
+        }
+
         Ok(())
     }
 
+    /// Writes the folders and resources into the Yyp's listings. The vfs stays dirty until
+    /// [`mark_serialized`] is called.
+    ///
+    /// [`mark_serialized`]: #method.mark_serialized
     pub(crate) fn serialize(
-        &mut self,
+        &self,
         yyp_folders: &mut Vec<YypFolder>,
         yyp_resources: &mut Vec<YypResource>,
     ) {
         // refry the beans...
-        let DirtyDrain {
-            resources_to_reserialize,
-            resources_to_remove,
-            associated_values: _,
-        } = self.dirty_handler.drain_all();
-
-        for (reserialize, _) in resources_to_reserialize {
-            let folder_data = Self::get_folder_inner(&self.root, &reserialize)
+        for reserialize in self.dirty_handler.resources_to_reserialize().keys() {
+            let folder_data = Self::get_folder_inner(&self.root, reserialize)
                 .expect("always internally consistent");
 
             let output = YypFolder {
@@ -556,7 +543,7 @@ impl Vfs {
 
             if let Some(pos) = yyp_folders
                 .iter()
-                .position(|v| v.folder_path == reserialize)
+                .position(|v| v.folder_path == *reserialize)
             {
                 yyp_folders[pos] = output;
             } else {
@@ -564,18 +551,25 @@ impl Vfs {
             }
         }
 
-        // remove the excess beans...
-        for (remove_path, _) in resources_to_remove {
-            let pos = yyp_folders
+        // remove the excess beans, which gms2 might have removed already...
+        for remove_path in self.dirty_handler.resources_to_remove().keys() {
+            if let Some(pos) = yyp_folders
                 .iter()
-                .position(|v| v.folder_path == remove_path)
-                .expect("must exist to remove it");
-            yyp_folders.remove(pos);
+                .position(|v| v.folder_path == *remove_path)
+            {
+                yyp_folders.remove(pos);
+            }
         }
 
         // resource names...
         self.resource_names.serialize(yyp_resources);
     }
+
+    /// Clears the dirty folders and resources, once the Yyp has been written.
+    pub(crate) fn mark_serialized(&mut self) {
+        self.dirty_handler.clear();
+        self.resource_names.dirty_handler.clear();
+    }
 }
 
 #[derive(Debug, Clone, Eq, Serialize, Deserialize, Default)]
@@ -817,26 +811,23 @@ impl ResourceNames {
         }
     }
 
-    pub(crate) fn serialize(&mut self, yyp_resources: &mut Vec<YypResource>) {
-        let DirtyDrain {
-            resources_to_reserialize,
-            resources_to_remove,
-            associated_values: _,
-        } = self.dirty_handler.drain_all();
-
-        for (refried_bean, _) in resources_to_reserialize {
-            let desc = &self.names[&refried_bean];
+    pub(crate) fn serialize(&self, yyp_resources: &mut Vec<YypResource>) {
+        for refried_bean in self.dirty_handler.resources_to_reserialize().keys() {
+            let desc = &self.names[refried_bean];
 
-            if let Some(pos) = yyp_resources.iter().position(|v| v.id.name == refried_bean) {
-                let rsc = desc.to_yyp_resource(&refried_bean);
+            if let Some(pos) = yyp_resources
+                .iter()
+                .position(|v| v.id.name == *refried_bean)
+            {
+                let rsc = desc.to_yyp_resource(refried_bean);
                 yyp_resources[pos].id = rsc.id;
             } else {
-                yyp_resources.push(desc.to_yyp_resource(&refried_bean));
+                yyp_resources.push(desc.to_yyp_resource(refried_bean));
             }
         }
 
-        for (name, _) in resources_to_remove {
-            if let Some(pos) = yyp_resources.iter().position(|v| v.id.name == name) {
+        for name in self.dirty_handler.resources_to_remove().keys() {
+            if let Some(pos) = yyp_resources.iter().position(|v| v.id.name == *name) {
                 yyp_resources.remove(pos);
             }
         }
@@ -886,13 +877,20 @@ impl Files {
         self.0.iter().any(|f| f.name == *name)
     }
 
-    pub fn load_in<T: YyResource>(&mut self, yy: &T, rn: &mut ResourceNames) {
-        self.0.push(FilesystemPath::new(T::SUBPATH_NAME, yy.name()));
+    pub fn load_in(
+        &mut self,
+        name: &str,
+        resource: Resource,
+        parent: &ViewPathLocation,
+        rn: &mut ResourceNames,
+    ) {
+        self.0
+            .push(FilesystemPath::new(resource.subpath_name(), name));
 
         // add to resource names...
         rn.load_in_resource(
-            yy.name().to_string(),
-            ResourceDescriptor::new(T::RESOURCE, yy.parent_view_path().path),
+            name.to_string(),
+            ResourceDescriptor::new(resource, parent.clone()),
         );
     }
 
@@ -1048,6 +1046,7 @@ mod test {
         let mut dummy0 = vec![];
         let mut dummy1 = vec![];
         fgm.serialize(&mut dummy0, &mut dummy1);
+        fgm.mark_serialized();
 
         assert_eq!(
             dummy0.into_iter().collect::<HashSet<_>>(),
@@ -0,0 +1,523 @@
+use crate::{
+    typings::{AnimationCurve, Extension, Font, Path, Room, Sequence, Timeline},
+    AudioGroupManager, Resource, ResourceManipulationError, TextureGroupManager, YyResource,
+    YyResourceData, YypBoss,
+};
+use std::{any::Any, path::PathBuf};
+use yy_typings::{
+    AudioGroupId, Note, Object, Script, Shader, Sound, Sprite, TexturePath, TileSet, ViewPath,
+    ViewPathLocation, YypIncludedFile,
+};
+
+/// The undo and redo stacks of a YypBoss.
+///
+/// Each step on a stack is a list of operations which, when run in reverse order, undoes (or
+/// redoes) one mutation. Most steps have a single operation, but a recursive folder removal
+/// records every resource and folder it removed in one step.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub(crate) struct History {
+    undo: Vec<Vec<Operation>>,
+    redo: Vec<Vec<Operation>>,
+
+    /// The step being built, if we're inside `begin_step`, and where each nested step began in it.
+    open_step: Option<Vec<Operation>>,
+    open_starts: Vec<usize>,
+
+    /// While undoing or redoing, the inverses of the operations we replay land here instead.
+    replay: Option<Vec<Operation>>,
+}
+
+impl History {
+    /// Records the operation which undoes a mutation which just happened.
+    pub fn record(&mut self, inverse: Operation) {
+        if let Some(replay) = &mut self.replay {
+            replay.push(inverse);
+        } else if let Some(open_step) = &mut self.open_step {
+            open_step.push(inverse);
+        } else {
+            self.undo.push(vec![inverse]);
+            self.redo.clear();
+        }
+    }
+
+    /// Starts collecting every recorded operation into a single step, until the matching
+    /// `end_step`. Steps can nest, in which case the outermost step wins.
+    pub fn begin_step(&mut self) {
+        let open_step = self.open_step.get_or_insert_with(Vec::new);
+        self.open_starts.push(open_step.len());
+    }
+
+    pub fn end_step(&mut self) {
+        self.open_starts.pop();
+        if self.open_starts.is_empty() {
+            if let Some(step) = self.open_step.take() {
+                if step.is_empty() == false {
+                    self.undo.push(step);
+                    self.redo.clear();
+                }
+            }
+        }
+    }
+
+    /// Closes the innermost step like `end_step`, but takes out everything recorded since it
+    /// began instead of keeping it, so that it can be replayed to roll the step back.
+    pub fn abort_step(&mut self) -> Vec<Operation> {
+        let start = self.open_starts.pop().unwrap_or_default();
+        let output = match &mut self.open_step {
+            Some(open_step) => open_step.split_off(start),
+            None => vec![],
+        };
+        if self.open_starts.is_empty() {
+            self.open_step = None;
+        }
+
+        output
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.undo.is_empty() == false
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.redo.is_empty() == false
+    }
+
+    pub fn pop_undo(&mut self) -> Option<Vec<Operation>> {
+        self.undo.pop()
+    }
+
+    pub fn pop_redo(&mut self) -> Option<Vec<Operation>> {
+        self.redo.pop()
+    }
+
+    pub fn push_undo(&mut self, step: Vec<Operation>) {
+        self.undo.push(step);
+    }
+
+    pub fn push_redo(&mut self, step: Vec<Operation>) {
+        self.redo.push(step);
+    }
+
+    pub fn start_replay(&mut self) {
+        self.replay = Some(vec![]);
+    }
+
+    /// Stops replaying, returning the inverses of everything replayed, ready to be pushed as a step.
+    ///
+    /// Since steps are replayed backwards, their inverses are already in the order a step expects.
+    pub fn finish_replay(&mut self) -> Vec<Operation> {
+        self.replay.take().unwrap_or_default()
+    }
+}
+
+/// A single reversible mutation of a YypBoss.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Operation {
+    AddResource {
+        resource: ErasedResource,
+        /// Where a room was in the room order, so it's put back in the same place.
+        room_order: Option<usize>,
+    },
+    ReplaceResource(ErasedResource),
+    RemoveResource {
+        resource: Resource,
+        name: String,
+    },
+    RenameResource {
+        resource: Resource,
+        name: String,
+        new_name: String,
+    },
+    MoveResource {
+        resource: Resource,
+        name: String,
+        new_parent: ViewPath,
+    },
+    CreateFolder {
+        parent: ViewPathLocation,
+        name: String,
+    },
+    RemoveFolder {
+        folder: ViewPathLocation,
+    },
+    RenameFolder {
+        folder: ViewPathLocation,
+        new_name: String,
+    },
+    MoveFolder {
+        folder: ViewPathLocation,
+        new_parent: ViewPathLocation,
+    },
+    /// Puts the texture groups back, and each sprite and tileset back into the texture group it
+    /// was in.
+    RestoreTextureGroups {
+        texture_groups: TextureGroupManager,
+        sprites: Vec<(String, TexturePath)>,
+        tilesets: Vec<(String, TexturePath)>,
+    },
+    /// Puts the audio groups back, and each sound back into the audio group it was in.
+    RestoreAudioGroups {
+        audio_groups: AudioGroupManager,
+        sounds: Vec<(String, AudioGroupId)>,
+    },
+    AddIncludedFile {
+        position: usize,
+        file: YypIncludedFile,
+        data: Option<Vec<u8>>,
+    },
+    RemoveIncludedFile {
+        path: PathBuf,
+    },
+    MoveIncludedFile {
+        path: PathBuf,
+        new_path: PathBuf,
+    },
+}
+
+/// A resource of any type, with its associated data if it was loaded, so that it can be put
+/// back into the project.
+#[derive(Debug, Clone, PartialEq)]
+#[allow(clippy::large_enum_variant)]
+pub(crate) enum ErasedResource {
+    Sprite(YyResourceData<Sprite>),
+    Script(YyResourceData<Script>),
+    Object(YyResourceData<Object>),
+    Note(YyResourceData<Note>),
+    Shader(YyResourceData<Shader>),
+    Room(YyResourceData<Room>),
+    TileSet(YyResourceData<TileSet>),
+    Sound(YyResourceData<Sound>),
+    Font(YyResourceData<Font>),
+    Path(YyResourceData<Path>),
+    Sequence(YyResourceData<Sequence>),
+    Timeline(YyResourceData<Timeline>),
+    Extension(YyResourceData<Extension>),
+    AnimationCurve(YyResourceData<AnimationCurve>),
+}
+
+impl ErasedResource {
+    /// Erases the type of a resource. This only returns `None` if `T` isn't the type which
+    /// `T::RESOURCE` says it is, which only test resources do.
+    pub fn new<T: YyResource>(
+        yy_resource: T,
+        associated_data: Option<T::AssociatedData>,
+    ) -> Option<Self> {
+        let data: Box<dyn Any> = Box::new(YyResourceData {
+            yy_resource,
+            associated_data,
+        });
+
+        let output = match T::RESOURCE {
+            Resource::Sprite => ErasedResource::Sprite(*data.downcast().ok()?),
+            Resource::Script => ErasedResource::Script(*data.downcast().ok()?),
+            Resource::Object => ErasedResource::Object(*data.downcast().ok()?),
+            Resource::Note => ErasedResource::Note(*data.downcast().ok()?),
+            Resource::Shader => ErasedResource::Shader(*data.downcast().ok()?),
+            Resource::Room => ErasedResource::Room(*data.downcast().ok()?),
+            Resource::TileSet => ErasedResource::TileSet(*data.downcast().ok()?),
+            Resource::Sound => ErasedResource::Sound(*data.downcast().ok()?),
+            Resource::Font => ErasedResource::Font(*data.downcast().ok()?),
+            Resource::Path => ErasedResource::Path(*data.downcast().ok()?),
+            Resource::Sequence => ErasedResource::Sequence(*data.downcast().ok()?),
+            Resource::Timeline => ErasedResource::Timeline(*data.downcast().ok()?),
+            Resource::Extension => ErasedResource::Extension(*data.downcast().ok()?),
+            Resource::AnimationCurve => ErasedResource::AnimationCurve(*data.downcast().ok()?),
+        };
+
+        Some(output)
+    }
+
+    /// Adds the resource back into the project. A room is put back at `room_order` in the room
+    /// order.
+    pub fn add_to(
+        self,
+        yyp_boss: &mut YypBoss,
+        room_order: Option<usize>,
+    ) -> Result<(), ResourceManipulationError> {
+        macro_rules! add {
+            ($($variant:ident),*) => {
+                match self {
+                    $(ErasedResource::$variant(v) => {
+                        yyp_boss.insert_resource_at(v.yy_resource, v.associated_data, room_order)
+                    })*
+                }
+            };
+        }
+
+        add!(
+            Sprite,
+            Script,
+            Object,
+            Note,
+            Shader,
+            Room,
+            TileSet,
+            Sound,
+            Font,
+            Path,
+            Sequence,
+            Timeline,
+            Extension,
+            AnimationCurve
+        )
+    }
+
+    /// Replaces the resource of the same name within the project with this one.
+    pub fn replace_in(self, yyp_boss: &mut YypBoss) -> Result<(), ResourceManipulationError> {
+        macro_rules! replace {
+            ($($variant:ident),*) => {
+                match self {
+                    $(ErasedResource::$variant(v) => yyp_boss
+                        .replace_resource_inner(v.yy_resource, v.associated_data)
+                        .map(|_| ()),)*
+                }
+            };
+        }
+
+        replace!(
+            Sprite,
+            Script,
+            Object,
+            Note,
+            Shader,
+            Room,
+            TileSet,
+            Sound,
+            Font,
+            Path,
+            Sequence,
+            Timeline,
+            Extension,
+            AnimationCurve
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SpriteExt, Vfs};
+    use yy_typings::{CommonData, FrameId, SpriteSequenceId};
+
+    fn remove(name: &str) -> Operation {
+        Operation::RemoveResource {
+            resource: Resource::Script,
+            name: name.to_string(),
+        }
+    }
+
+    fn script(name: &str, parent: ViewPath) -> Script {
+        Script {
+            common_data: CommonData::new(name.to_string()),
+            parent,
+            ..Script::default()
+        }
+    }
+
+    #[test]
+    fn undo_and_redo() {
+        let mut yyp_boss = YypBoss::default();
+        let scripts = yyp_boss
+            .new_folder_end(Vfs::root_folder(), "Scripts")
+            .unwrap();
+        yyp_boss
+            .add_resource(script("scr_player", scripts.clone()), String::new())
+            .unwrap();
+
+        // an undone add never reaches the disk
+        assert!(yyp_boss.undo().unwrap());
+        assert!(yyp_boss.scripts.get("scr_player").is_none());
+        assert!(yyp_boss.vfs.resource_exists("scr_player", Resource::Script) == false);
+        let dirty_handler = yyp_boss.scripts.dirty_handler();
+        assert!(dirty_handler.resources_to_reserialize().is_empty());
+        assert!(dirty_handler.resources_to_remove().is_empty());
+
+        assert!(yyp_boss.redo().unwrap());
+        assert!(yyp_boss.can_redo() == false);
+        yyp_boss
+            .rename_resource::<Script>("scr_player", "scr_hero".to_string())
+            .unwrap();
+
+        let player = yyp_boss.new_folder_end(&scripts.path, "Player").unwrap();
+        yyp_boss
+            .move_resource::<Script>("scr_hero", player.clone())
+            .unwrap();
+        assert!(yyp_boss.undo().unwrap());
+        assert_eq!(
+            yyp_boss.scripts.get("scr_hero").unwrap().yy_resource.parent,
+            scripts
+        );
+
+        // the folder and everything within it come back in one step
+        yyp_boss.remove_folder(&scripts.path).unwrap();
+        assert!(yyp_boss.vfs.get_folder(&scripts.path).is_none());
+        assert!(yyp_boss.undo().unwrap());
+        assert!(yyp_boss.vfs.get_folder(&player.path).is_some());
+        assert_eq!(
+            yyp_boss.scripts.get("scr_hero").unwrap().associated_data,
+            Some(String::new())
+        );
+
+        while yyp_boss.undo().unwrap() {}
+        assert!(yyp_boss.scripts.resources().is_empty());
+        assert!(yyp_boss.vfs.get_folder(&scripts.path).is_none());
+    }
+
+    #[test]
+    fn failed_removals() {
+        let mut yyp_boss = YypBoss::default();
+        let scripts = yyp_boss
+            .new_folder_end(Vfs::root_folder(), "Scripts")
+            .unwrap();
+        for name in ["scr_a", "scr_b"] {
+            yyp_boss
+                .add_resource(script(name, scripts.clone()), String::new())
+                .unwrap();
+        }
+
+        // the vfs lists a resource which the handler can't give us
+        yyp_boss.scripts.unload("scr_b");
+        assert!(yyp_boss.remove_resource::<Script>("scr_b").is_err());
+        assert!(yyp_boss.vfs.resource_exists("scr_b", Resource::Script));
+
+        // so the folder stays, along with everything removed before it
+        assert!(yyp_boss.remove_folder(&scripts.path).is_err());
+        assert!(yyp_boss.vfs.get_folder(&scripts.path).is_some());
+        assert!(yyp_boss.vfs.resource_exists("scr_a", Resource::Script));
+        assert!(yyp_boss.scripts.get("scr_a").is_some());
+        assert!(yyp_boss.can_redo() == false);
+    }
+
+    #[test]
+    fn room_order() {
+        let mut yyp_boss = YypBoss::default();
+        let rooms = yyp_boss
+            .new_folder_end(Vfs::root_folder(), "Rooms")
+            .unwrap();
+        for name in ["rm_title", "rm_level"] {
+            let room = Room {
+                common_data: CommonData::new(name.to_string()),
+                parent: rooms.clone(),
+                ..Room::default()
+            };
+            yyp_boss.add_resource(room, Default::default()).unwrap();
+        }
+        let room_order = yyp_boss.yyp().room_order_nodes.clone();
+
+        yyp_boss.remove_resource::<Room>("rm_title").unwrap();
+        assert!(yyp_boss.undo().unwrap());
+        assert_eq!(yyp_boss.yyp().room_order_nodes, room_order);
+
+        // redoing the removal and undoing it again does the same
+        assert!(yyp_boss.redo().unwrap());
+        assert!(yyp_boss.undo().unwrap());
+        assert_eq!(yyp_boss.yyp().room_order_nodes, room_order);
+    }
+
+    #[test]
+    fn texture_groups() {
+        let mut yyp_boss = YypBoss::default();
+        let sprites = yyp_boss
+            .new_folder_end(Vfs::root_folder(), "Sprites")
+            .unwrap();
+        let texture_path = yyp_boss
+            .edit_texture_groups(None, |texture_groups, _, _| {
+                texture_groups.create("tg_ui", None)
+            })
+            .unwrap();
+        let sprite = Sprite::new("spr_button", texture_path.clone(), sprites);
+        yyp_boss.add_resource(sprite, Default::default()).unwrap();
+
+        yyp_boss
+            .edit_texture_groups(Some("tg_ui"), |texture_groups, sprites, tilesets| {
+                texture_groups.rename("tg_ui", "tg_menu", sprites, tilesets)
+            })
+            .unwrap();
+        let texture_group_id = |yyp_boss: &YypBoss| {
+            yyp_boss
+                .sprites
+                .get("spr_button")
+                .unwrap()
+                .yy_resource
+                .texture_group_id
+                .clone()
+        };
+        assert_eq!(texture_group_id(&yyp_boss).name, "tg_menu");
+
+        assert!(yyp_boss.undo().unwrap());
+        assert_eq!(texture_group_id(&yyp_boss), texture_path);
+        assert!(yyp_boss.texture_groups.get("tg_ui").is_some());
+
+        assert!(yyp_boss.redo().unwrap());
+        assert_eq!(texture_group_id(&yyp_boss).name, "tg_menu");
+    }
+
+    #[test]
+    fn sprite_frames() {
+        let mut yyp_boss = YypBoss::default();
+        let sprites = yyp_boss
+            .new_folder_end(Vfs::root_folder(), "Sprites")
+            .unwrap();
+        let mut sprite = Sprite::new("spr_walk", Default::default(), sprites);
+        let frame = FrameId::new();
+        sprite.set_frame(frame, SpriteSequenceId::new());
+        let frames = maplit::hashmap! { frame => Default::default() };
+        yyp_boss.add_resource(sprite, frames).unwrap();
+
+        let frame_ids = |yyp_boss: &YypBoss| -> Vec<FrameId> {
+            let sprite = &yyp_boss.sprites.get("spr_walk").unwrap().yy_resource;
+            sprite.frames.iter().map(|v| v.name).collect()
+        };
+        let copy = yyp_boss.duplicate_sprite_frame("spr_walk", frame).unwrap();
+        assert_eq!(frame_ids(&yyp_boss), [frame, copy]);
+
+        // the frame and its images go together
+        assert!(yyp_boss.undo().unwrap());
+        assert_eq!(frame_ids(&yyp_boss), [frame]);
+        let data = yyp_boss.sprites.get("spr_walk").unwrap();
+        assert!(data.associated_data.as_ref().unwrap().contains_key(&copy) == false);
+
+        assert!(yyp_boss.redo().unwrap());
+        assert_eq!(frame_ids(&yyp_boss), [frame, copy]);
+    }
+
+    #[test]
+    fn steps() {
+        let mut history = History::default();
+        history.record(remove("a"));
+
+        history.begin_step();
+        history.record(remove("b"));
+        history.begin_step();
+        history.record(remove("c"));
+        history.end_step();
+        history.end_step();
+
+        assert_eq!(history.pop_undo(), Some(vec![remove("b"), remove("c")]));
+
+        // aborting a nested step only takes out what was recorded within it
+        history.begin_step();
+        history.record(remove("b"));
+        history.begin_step();
+        history.record(remove("c"));
+        assert_eq!(history.abort_step(), vec![remove("c")]);
+        assert_eq!(history.abort_step(), vec![remove("b")]);
+        assert!(history.open_step.is_none());
+
+        // replaying collects inverses without touching the stacks
+        history.start_replay();
+        history.record(remove("c"));
+        history.record(remove("b"));
+        assert_eq!(history.finish_replay(), vec![remove("c"), remove("b")]);
+        assert!(history.can_undo());
+
+        // a new mutation clears the redo stack
+        history.push_redo(vec![remove("d")]);
+        assert!(history.can_redo());
+        history.record(remove("e"));
+        assert!(history.can_redo() == false);
+        assert_eq!(history.pop_undo(), Some(vec![remove("e")]));
+        assert_eq!(history.pop_undo(), Some(vec![remove("a")]));
+        assert!(history.can_undo() == false);
+    }
+}
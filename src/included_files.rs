@@ -0,0 +1,395 @@
+use crate::{
+    directory_manager::DirectoryManager, staging::Staging, FileChanges, ResourceManipulationError,
+};
+use anyhow::Result as AnyResult;
+use std::{
+    collections::BTreeSet,
+    fs,
+    path::{Component, Path, PathBuf},
+};
+use yy_typings::{CommonData, YypIncludedFile};
+
+/// The directory, relative to the root of the project, which holds every included file.
+pub const DATAFILES_DIRECTORY: &str = "datafiles";
+
+/// Manages the Included Files of a project, which Gms2 calls "datafiles".
+///
+/// Every path given to the handler is relative to the `datafiles` directory, so a file which
+/// sits at `datafiles/localisation/en.csv` is identified by `localisation/en.csv`.
+///
+/// Changes are only written to disk, and into the Yyp, when the YypBoss is serialized.
+#[derive(Debug, PartialEq, Eq, Default, Clone)]
+pub struct IncludedFilesHandler {
+    files: Vec<YypIncludedFile>,
+    operations: Vec<FileOperation>,
+}
+
+/// A change to the `datafiles` directory, which we hold until we serialize. Paths are relative
+/// to the root of the project.
+#[derive(Debug, PartialEq, Eq, Clone)]
+enum FileOperation {
+    Write { path: PathBuf, data: Vec<u8> },
+    Remove { path: PathBuf },
+    Move { from: PathBuf, to: PathBuf },
+}
+
+impl IncludedFilesHandler {
+    pub(crate) fn new(included_files: &[YypIncludedFile]) -> Self {
+        Self {
+            files: included_files.to_vec(),
+            operations: Vec::new(),
+        }
+    }
+
+    /// Returns every included file in the project.
+    pub fn files(&self) -> &[YypIncludedFile] {
+        &self.files
+    }
+
+    /// Returns `true` if there is an included file at the given path.
+    pub fn exists(&self, path: &Path) -> bool {
+        datafile_path(path).is_ok_and(|path| self.position(&path).is_some())
+    }
+
+    /// Adds a new included file at the given path, which will contain `data`.
+    pub fn add(&mut self, path: &Path, data: Vec<u8>) -> Result<(), ResourceManipulationError> {
+        let path = datafile_path(path)?;
+        if self.position(&path).is_some() {
+            return Err(ResourceManipulationError::NameCollision);
+        }
+
+        self.files.push(included_file(&path));
+        self.operations.push(FileOperation::Write { path, data });
+
+        Ok(())
+    }
+
+    /// Removes the included file at the given path, returning its entry in the Yyp.
+    pub fn remove(&mut self, path: &Path) -> Result<YypIncludedFile, ResourceManipulationError> {
+        let path = datafile_path(path)?;
+        let position = self
+            .position(&path)
+            .ok_or(ResourceManipulationError::BadGet)?;
+
+        self.operations.push(FileOperation::Remove { path });
+
+        Ok(self.files.remove(position))
+    }
+
+    /// Renames the included file at the given path. The file stays in the same directory.
+    pub fn rename(&mut self, path: &Path, new_name: &str) -> Result<(), ResourceManipulationError> {
+        let mut name_components = Path::new(new_name).components();
+        match (name_components.next(), name_components.next()) {
+            (Some(Component::Normal(_)), None) => {}
+            _ => return Err(ResourceManipulationError::BadName),
+        }
+
+        let path = datafile_path(path)?;
+        let new_path = path.with_file_name(new_name);
+        self.relocate(path, new_path)
+    }
+
+    /// Moves the included file at the given path into `new_directory`, which is also relative
+    /// to the `datafiles` directory. An empty path moves the file to the top of `datafiles`.
+    pub fn move_file(
+        &mut self,
+        path: &Path,
+        new_directory: &Path,
+    ) -> Result<(), ResourceManipulationError> {
+        let path = datafile_path(path)?;
+        let file_name = path.file_name().unwrap().to_owned();
+
+        let new_path = if new_directory.as_os_str().is_empty() {
+            Path::new(DATAFILES_DIRECTORY).join(file_name)
+        } else {
+            datafile_path(new_directory)?.join(file_name)
+        };
+
+        self.relocate(path, new_path)
+    }
+
+    /// Returns what the included file at the given path holds: what it was last given, if that
+    /// hasn't been written yet, or else what's on disk. Returns `None` if neither can be read.
+    pub(crate) fn data(
+        &self,
+        path: &Path,
+        directory_manager: &DirectoryManager,
+    ) -> Option<Vec<u8>> {
+        self.contents(datafile_path(path).ok()?, directory_manager)
+    }
+
+    /// Returns where the included file at the given path is in the list of files.
+    pub(crate) fn index(&self, path: &Path) -> Option<usize> {
+        self.position(&datafile_path(path).ok()?)
+    }
+
+    /// Puts back an included file which was removed, at `position` in the list of files. If we
+    /// couldn't read the file's `data` when it was removed, the file is only listed again.
+    pub(crate) fn restore(
+        &mut self,
+        position: usize,
+        file: YypIncludedFile,
+        data: Option<Vec<u8>>,
+    ) {
+        let path = file.file_path.join(&file.common_data.name);
+        self.files.insert(position.min(self.files.len()), file);
+        if let Some(data) = data {
+            self.operations.push(FileOperation::Write { path, data });
+        }
+    }
+
+    /// Moves the included file at `path` to `new_path`, both relative to the `datafiles`
+    /// directory. This is what [`rename`] and [`move_file`] both do.
+    ///
+    /// [`rename`]: #method.rename
+    /// [`move_file`]: #method.move_file
+    pub(crate) fn move_to(
+        &mut self,
+        path: &Path,
+        new_path: &Path,
+    ) -> Result<(), ResourceManipulationError> {
+        self.relocate(datafile_path(path)?, datafile_path(new_path)?)
+    }
+
+    /// Lists the files which serializing would write or delete, relative to the root of the
+    /// project. Nothing is written: what each file would hold is compared with what's on disk.
+    pub(crate) fn plan(&self, directory_manager: &DirectoryManager) -> FileChanges {
+        let mut paths = BTreeSet::new();
+        for operation in self.operations.iter() {
+            match operation {
+                FileOperation::Write { path, .. } | FileOperation::Remove { path } => {
+                    paths.insert(path);
+                }
+                FileOperation::Move { from, to } => {
+                    paths.insert(from);
+                    paths.insert(to);
+                }
+            }
+        }
+
+        let mut output = FileChanges::default();
+        for path in paths {
+            let on_disk = fs::read(directory_manager.resource_file(path)).ok();
+            match self.contents(path.clone(), directory_manager) {
+                Some(data) => {
+                    if on_disk.as_ref() != Some(&data) {
+                        output.written.push(path.clone());
+                    }
+                }
+                None => {
+                    if on_disk.is_some() {
+                        output.deleted.push(path.clone());
+                    }
+                }
+            }
+        }
+
+        output
+    }
+
+    /// Stages every change to be written, and then writes the included files into the Yyp's listing.
+    ///
+    /// The changes stay pending until [`mark_serialized`] is called.
+    ///
+    /// [`mark_serialized`]: #method.mark_serialized
+    pub(crate) fn serialize(
+        &self,
+        directory_manager: &DirectoryManager,
+        staging: &mut Staging,
+        included_files: &mut Vec<YypIncludedFile>,
+    ) -> AnyResult<()> {
+        for operation in self.operations.iter() {
+            match operation {
+                FileOperation::Write { path, data } => {
+                    let path = directory_manager.resource_file(path);
+                    fs::write(staging.file(&path)?, data)?;
+                }
+                FileOperation::Remove { path } => {
+                    staging.remove(&directory_manager.resource_file(path));
+                }
+                FileOperation::Move { from, to } => {
+                    staging.rename(
+                        &directory_manager.resource_file(from),
+                        &directory_manager.resource_file(to),
+                    )?;
+                }
+            }
+        }
+
+        *included_files = self.files.clone();
+
+        Ok(())
+    }
+
+    /// Clears the pending changes, once what [`serialize`] staged has been committed.
+    ///
+    /// [`serialize`]: #method.serialize
+    pub(crate) fn mark_serialized(&mut self) {
+        self.operations.clear();
+    }
+
+    /// What the file at `path`, relative to the root of the project, will hold once every
+    /// operation is done, or `None` if it won't exist.
+    fn contents(&self, mut path: PathBuf, directory_manager: &DirectoryManager) -> Option<Vec<u8>> {
+        // follow the file back through its moves to the last time it was written
+        for operation in self.operations.iter().rev() {
+            match operation {
+                FileOperation::Write {
+                    path: written,
+                    data,
+                } if *written == path => {
+                    return Some(data.clone());
+                }
+                FileOperation::Remove { path: removed } if *removed == path => return None,
+                FileOperation::Move { from, to } if *to == path => path = from.clone(),
+                FileOperation::Move { from, .. } if *from == path => return None,
+                _ => {}
+            }
+        }
+
+        fs::read(directory_manager.resource_file(&path)).ok()
+    }
+
+    fn relocate(
+        &mut self,
+        path: PathBuf,
+        new_path: PathBuf,
+    ) -> Result<(), ResourceManipulationError> {
+        if path == new_path {
+            return Ok(());
+        }
+
+        let position = self
+            .position(&path)
+            .ok_or(ResourceManipulationError::BadGet)?;
+        if self.position(&new_path).is_some() {
+            return Err(ResourceManipulationError::NameCollision);
+        }
+
+        let copy_to_mask = self.files[position].copy_to_mask;
+        self.files[position] = YypIncludedFile {
+            copy_to_mask,
+            ..included_file(&new_path)
+        };
+        self.operations.push(FileOperation::Move {
+            from: path,
+            to: new_path,
+        });
+
+        Ok(())
+    }
+
+    fn position(&self, path: &Path) -> Option<usize> {
+        self.files
+            .iter()
+            .position(|v| v.file_path.join(&v.common_data.name) == path)
+    }
+}
+
+/// Turns a path relative to `datafiles` into a path relative to the root of the project.
+/// The path cannot be absolute or climb out of `datafiles`.
+fn datafile_path(path: &Path) -> Result<PathBuf, ResourceManipulationError> {
+    let mut output = DATAFILES_DIRECTORY.to_string();
+
+    for component in path.components() {
+        match component {
+            Component::Normal(v) => {
+                let v = v.to_str().ok_or(ResourceManipulationError::BadName)?;
+                output.push('/');
+                output.push_str(v);
+            }
+            Component::CurDir => {}
+            _ => return Err(ResourceManipulationError::BadName),
+        }
+    }
+
+    if output == DATAFILES_DIRECTORY {
+        return Err(ResourceManipulationError::BadName);
+    }
+
+    Ok(PathBuf::from(output))
+}
+
+/// Creates the Yyp entry for a file at `path`, which is relative to the root of the project.
+fn included_file(path: &Path) -> YypIncludedFile {
+    // we write these with forward slashes, like gms2 does, even on windows.
+    let file_path = path
+        .parent()
+        .unwrap()
+        .components()
+        .map(|v| v.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+
+    YypIncludedFile {
+        common_data: CommonData::new(path.file_name().unwrap().to_string_lossy().into_owned()),
+        file_path: PathBuf::from(file_path),
+        ..YypIncludedFile::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TempDirectory;
+
+    fn serialize(
+        handler: &mut IncludedFilesHandler,
+        directory_manager: &DirectoryManager,
+        included_files: &mut Vec<YypIncludedFile>,
+    ) {
+        let mut staging = Staging::new(directory_manager.root_directory()).unwrap();
+        handler
+            .serialize(directory_manager, &mut staging, included_files)
+            .unwrap();
+        staging.commit().unwrap();
+        handler.mark_serialized();
+    }
+
+    #[test]
+    fn add_rename_move_remove() {
+        let root = TempDirectory::new();
+        let directory_manager = root.directory_manager();
+
+        let mut handler = IncludedFilesHandler::default();
+        handler
+            .add(Path::new("localisation/en.csv"), b"key,text".to_vec())
+            .unwrap();
+        handler.add(Path::new("data.json"), b"{}".to_vec()).unwrap();
+        assert_eq!(
+            handler.add(Path::new("data.json"), vec![]),
+            Err(ResourceManipulationError::NameCollision)
+        );
+        assert_eq!(
+            handler.add(Path::new("../escape.txt"), vec![]),
+            Err(ResourceManipulationError::BadName)
+        );
+
+        handler
+            .rename(Path::new("localisation/en.csv"), "en_US.csv")
+            .unwrap();
+        handler
+            .move_file(Path::new("data.json"), Path::new("config"))
+            .unwrap();
+        assert!(handler.exists(Path::new("config/data.json")));
+        assert_eq!(
+            handler.files()[0].file_path,
+            Path::new("datafiles/localisation")
+        );
+
+        let mut included_files = vec![];
+        serialize(&mut handler, &directory_manager, &mut included_files);
+        assert_eq!(included_files.len(), 2);
+        assert_eq!(
+            fs::read(root.join("datafiles/localisation/en_US.csv")).unwrap(),
+            b"key,text"
+        );
+        assert!(root.join("datafiles/config/data.json").exists());
+        assert!(root.join("datafiles/data.json").exists() == false);
+
+        handler.remove(Path::new("config/data.json")).unwrap();
+        serialize(&mut handler, &directory_manager, &mut included_files);
+        assert_eq!(included_files.len(), 1);
+        assert!(root.join("datafiles/config/data.json").exists() == false);
+    }
+}
@@ -29,7 +29,40 @@ pub use errors::*;
 mod folders;
 pub use folders::*;
 
+mod history;
+
+mod fingerprint;
+
+mod staging;
+
+mod serialize_plan;
+pub use serialize_plan::{
+    ConflictResolution, FileChanges, ResourcePlan, SerializePlan, SerializeReport, YypDiff,
+};
+
+mod watcher;
+pub use watcher::{ExternalChanges, ExternalResource, ProjectWatcher};
+
+mod audio_groups;
+pub use audio_groups::{AudioGroupManager, DEFAULT_AUDIO_GROUP};
+
+mod texture_groups;
+pub use texture_groups::{TextureGroupManager, TextureGroupOptions, DEFAULT_TEXTURE_GROUP};
+
+mod included_files;
+pub use included_files::{IncludedFilesHandler, DATAFILES_DIRECTORY};
+
 mod resources_ext;
 pub use resources_ext::*;
 
+mod aseprite;
+pub use aseprite::{
+    AsepriteDirection, AsepriteFile, AsepriteSprite, AsepriteSpriteTag, AsepriteTag, AsepriteTags,
+};
+
 pub mod cli;
+
+pub mod typings;
+
+#[cfg(test)]
+mod test_utils;
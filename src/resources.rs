@@ -1,9 +1,7 @@
 use super::YyResource;
+use crate::typings::{AnimationCurve, Extension, Font, Path, Room, Sequence, Timeline};
 use std::fmt;
-use yy_typings::{
-    AnimationCurve, Extension, Font, Note, Object, Path, Room, Script, Sequence, Shader, Sound,
-    Sprite, TileSet, Timeline,
-};
+use yy_typings::{Note, Object, Script, Shader, Sound, Sprite, TileSet};
 
 #[derive(
     Debug, PartialEq, Eq, Ord, PartialOrd, Copy, Clone, Hash, serde::Serialize, serde::Deserialize,
@@ -75,14 +73,14 @@ impl Resource {
             | Resource::Note
             | Resource::Sound
             | Resource::TileSet
-            | Resource::Shader => true,
-            Resource::AnimationCurve
             | Resource::Room
-            | Resource::Extension
             | Resource::Font
             | Resource::Path
             | Resource::Sequence
-            | Resource::Timeline => false,
+            | Resource::Timeline
+            | Resource::Extension
+            | Resource::AnimationCurve
+            | Resource::Shader => true,
         }
     }
 }
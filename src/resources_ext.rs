@@ -3,6 +3,9 @@ use super::*;
 mod sprite_ext;
 pub use sprite_ext::*;
 
+mod collision_mask;
+pub use collision_mask::CollisionMask;
+
 mod paths_ext;
 pub use paths_ext::*;
 
@@ -20,6 +23,22 @@ mod sound_ext;
 
 mod tile_set_ext;
 
-mod unidentified_resources;
+mod room_ext;
+pub use room_ext::*;
+
+mod font_ext;
+
+mod path_ext;
+pub use path_ext::*;
+
+mod sequence_ext;
+pub use sequence_ext::*;
+
+mod timeline_ext;
+
+mod extension_ext;
+
+mod animation_curve_ext;
+pub use animation_curve_ext::*;
 
 pub type SpriteImageBuffer = image::ImageBuffer<image::Rgba<u8>, Vec<u8>>;
@@ -0,0 +1,193 @@
+use crate::{
+    typings::{AnimationCurve, AnimationCurveChannel, AnimationCurvePoint, CurveInterpolation},
+    FileHolder, Resource, SerializedData, SerializedDataError, YyResource, YyResourceHandler,
+    YypBoss,
+};
+use std::path::Path;
+use yy_typings::{CommonData, TrailingCommaUtility, ViewPath};
+
+pub trait AnimationCurveExt: Sized {
+    fn with(self, edit: impl Fn(&mut Self)) -> Self;
+    fn new(name: &str, parent: ViewPath) -> Self;
+    fn parent(self, parent: ViewPath) -> Self;
+    fn function(self, function: CurveInterpolation) -> Self;
+
+    /// Adds an empty channel, returning the index of the channel.
+    fn add_channel(&mut self, name: &str) -> usize;
+    /// Removes the channel at the given index, if it exists.
+    fn remove_channel(&mut self, channel_index: usize) -> Option<AnimationCurveChannel>;
+    /// Inserts a point into the channel at `channel_index`. Points are kept ordered by `x`,
+    /// and `x` is clamped between 0 and 1.
+    ///
+    /// Returns `false` if the channel does not exist.
+    fn insert_point(&mut self, channel_index: usize, point: AnimationCurvePoint) -> bool;
+    /// Removes the point at `point_index` in the channel at `channel_index`, if it exists.
+    fn remove_point(
+        &mut self,
+        channel_index: usize,
+        point_index: usize,
+    ) -> Option<AnimationCurvePoint>;
+}
+
+impl AnimationCurveExt for AnimationCurve {
+    fn with(mut self, edit: impl Fn(&mut Self)) -> Self {
+        edit(&mut self);
+        self
+    }
+
+    fn new(name: &str, parent: ViewPath) -> Self {
+        AnimationCurve {
+            common_data: CommonData::new(name.to_owned()),
+            parent,
+            ..AnimationCurve::default()
+        }
+    }
+
+    fn parent(self, parent: ViewPath) -> Self {
+        self.with(|me| me.parent = parent.clone())
+    }
+
+    fn function(self, function: CurveInterpolation) -> Self {
+        self.with(|me| me.function = function)
+    }
+
+    fn add_channel(&mut self, name: &str) -> usize {
+        self.channels.push(AnimationCurveChannel::new(name));
+        self.channels.len() - 1
+    }
+
+    fn remove_channel(&mut self, channel_index: usize) -> Option<AnimationCurveChannel> {
+        if channel_index < self.channels.len() {
+            Some(self.channels.remove(channel_index))
+        } else {
+            None
+        }
+    }
+
+    fn insert_point(&mut self, channel_index: usize, mut point: AnimationCurvePoint) -> bool {
+        let channel = match self.channels.get_mut(channel_index) {
+            Some(v) => v,
+            None => return false,
+        };
+
+        point.x = point.x.clamp(0.0, 1.0);
+        let position = channel
+            .points
+            .iter()
+            .position(|v| v.x > point.x)
+            .unwrap_or(channel.points.len());
+        channel.points.insert(position, point);
+
+        true
+    }
+
+    fn remove_point(
+        &mut self,
+        channel_index: usize,
+        point_index: usize,
+    ) -> Option<AnimationCurvePoint> {
+        let channel = self.channels.get_mut(channel_index)?;
+
+        if point_index < channel.points.len() {
+            Some(channel.points.remove(point_index))
+        } else {
+            None
+        }
+    }
+}
+
+impl YyResource for AnimationCurve {
+    type AssociatedData = ();
+    const SUBPATH_NAME: &'static str = "animcurves";
+    const RESOURCE: Resource = Resource::AnimationCurve;
+
+    fn name(&self) -> &str {
+        &self.common_data.name
+    }
+
+    fn set_name(&mut self, name: String) {
+        self.common_data.name = name;
+    }
+
+    fn set_parent_view_path(&mut self, vp: ViewPath) {
+        self.parent = vp;
+    }
+
+    fn parent_view_path(&self) -> ViewPath {
+        self.parent.clone()
+    }
+
+    fn get_handler(yyp_boss: &YypBoss) -> &YyResourceHandler<Self> {
+        &yyp_boss.animation_curves
+    }
+
+    fn get_handler_mut(yyp_boss: &mut YypBoss) -> &mut YyResourceHandler<Self> {
+        &mut yyp_boss.animation_curves
+    }
+
+    // animation curves are entirely described by their yy file
+    fn serialize_associated_data(&self, _: &Path, _: &Self::AssociatedData) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn deserialize_associated_data(
+        &self,
+        _: &Path,
+        _: &TrailingCommaUtility,
+    ) -> Result<Self::AssociatedData, SerializedDataError> {
+        Ok(())
+    }
+
+    fn serialize_associated_data_into_data(
+        _: &Path,
+        _: &Self::AssociatedData,
+    ) -> Result<SerializedData, SerializedDataError> {
+        Ok(SerializedData::Value {
+            data: String::new(),
+        })
+    }
+
+    fn deserialize_associated_data_from_data(
+        &self,
+        _: &SerializedData,
+        _: &TrailingCommaUtility,
+    ) -> Result<Self::AssociatedData, SerializedDataError> {
+        Ok(())
+    }
+
+    fn cleanup_on_replace(&self, _: impl FileHolder) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn channels_and_points() {
+        let mut curve = AnimationCurve::new("ac_ease_out", ViewPath::default())
+            .function(CurveInterpolation::Smooth);
+
+        let channel = curve.add_channel("x");
+        assert!(curve.insert_point(channel, AnimationCurvePoint::new(1.0, 1.0)));
+        assert!(curve.insert_point(channel, AnimationCurvePoint::new(0.0, 0.0)));
+        assert!(curve.insert_point(channel, AnimationCurvePoint::new(1.5, 0.8)));
+        assert!(curve.insert_point(3, AnimationCurvePoint::default()) == false);
+
+        let points = &curve.channels[channel].points;
+        assert_eq!(points[0].x, 0.0);
+        assert_eq!(points[2], AnimationCurvePoint::new(1.0, 0.8));
+        assert_eq!(curve.remove_point(channel, 3), None);
+
+        let output = yy_typings::serialize_file(&curve);
+        let tcu = TrailingCommaUtility::new();
+        let reparsed: AnimationCurve =
+            serde_json::from_str(&tcu.clear_trailing_comma(&output)).unwrap();
+
+        assert_eq!(
+            curve.channels[channel].points,
+            reparsed.channels[channel].points
+        );
+        assert_eq!(output, yy_typings::serialize_file(&reparsed));
+        assert!(output.contains("\"function\":1"));
+    }
+}
@@ -0,0 +1,252 @@
+use crate::{composite_image, SpriteFrameLayers, SpriteImageBuffer};
+use image::{ImageResult, Rgba};
+use std::{collections::HashMap, ops::RangeInclusive, path::Path};
+use yy_typings::{CollisionKind, FrameId, Sprite};
+
+const SOLID: Rgba<u8> = Rgba([255, 255, 255, 255]);
+const PREVIEW: Rgba<u8> = Rgba([255, 0, 0, 255]);
+
+/// Which pixels of a sprite collide, from [`SpriteExt::collision_masks`]. A mask is the size of
+/// its sprite.
+///
+/// [`SpriteExt::collision_masks`]: ./trait.SpriteExt.html#tymethod.collision_masks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CollisionMask {
+    width: u32,
+    height: u32,
+    solid: Vec<bool>,
+}
+
+impl CollisionMask {
+    fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            solid: vec![false; width as usize * height as usize],
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns `true` if the pixel at `x`, `y` collides. Pixels outside of the mask never do.
+    pub fn is_solid(&self, x: u32, y: u32) -> bool {
+        x < self.width && y < self.height && self.solid[self.index(x, y)]
+    }
+
+    /// The mask as an image, with solid pixels in white and every other pixel transparent.
+    pub fn to_image(&self) -> SpriteImageBuffer {
+        SpriteImageBuffer::from_fn(self.width, self.height, |x, y| {
+            if self.is_solid(x, y) {
+                SOLID
+            } else {
+                Rgba([0, 0, 0, 0])
+            }
+        })
+    }
+
+    /// Saves the mask as an image, as in [`to_image`]. The format is taken from the extension of
+    /// `path`, which should generally be `png`.
+    ///
+    /// [`to_image`]: #method.to_image
+    pub fn save(&self, path: &Path) -> ImageResult<()> {
+        self.to_image().save(path)
+    }
+
+    /// Draws the mask in red, at half opacity, over a frame's image, so the mask can be checked
+    /// against the art.
+    pub fn preview(&self, image: &SpriteImageBuffer) -> SpriteImageBuffer {
+        let mut output = image.clone();
+        for (x, y, pixel) in output.enumerate_pixels_mut() {
+            if self.is_solid(x, y) {
+                for i in 0..3 {
+                    pixel[i] = ((pixel[i] as u16 + PREVIEW[i] as u16) / 2) as u8;
+                }
+                pixel[3] = pixel[3].max(128);
+            }
+        }
+
+        output
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        y as usize * self.width as usize + x as usize
+    }
+
+    fn set_solid(&mut self, x: u32, y: u32) {
+        let index = self.index(x, y);
+        self.solid[index] = true;
+    }
+}
+
+/// Builds the collision masks Gms2 would, as in [`SpriteExt::collision_masks`].
+///
+/// [`SpriteExt::collision_masks`]: ./trait.SpriteExt.html#tymethod.collision_masks
+pub(crate) fn collision_masks(
+    sprite: &Sprite,
+    frames: &HashMap<FrameId, SpriteFrameLayers>,
+) -> Vec<CollisionMask> {
+    let (width, height) = (sprite.width.get() as u32, sprite.height.get() as u32);
+    let (xs, ys) = bbox_ranges(sprite);
+    let images = || {
+        sprite
+            .frames
+            .iter()
+            .map(|frame| match frames.get(&frame.name) {
+                Some(frame_layers) => composite_image(sprite, frame_layers),
+                None => SpriteImageBuffer::new(width, height),
+            })
+    };
+
+    match sprite.collision_kind {
+        CollisionKind::Precise => {
+            let mut mask = CollisionMask::new(width, height);
+            for image in images() {
+                add_pixels(&mut mask, &image, sprite.collision_tolerance, &xs, &ys);
+            }
+
+            vec![mask]
+        }
+        CollisionKind::PrecisePerFrame => images()
+            .map(|image| {
+                let mut mask = CollisionMask::new(width, height);
+                add_pixels(&mut mask, &image, sprite.collision_tolerance, &xs, &ys);
+                mask
+            })
+            .collect(),
+        CollisionKind::Rectangle
+        | CollisionKind::RotatedRectangle
+        | CollisionKind::Ellipse
+        | CollisionKind::Diamond => {
+            let mut mask = CollisionMask::new(width, height);
+            if xs.is_empty() || ys.is_empty() {
+                return vec![mask];
+            }
+
+            // the shapes are inscribed within the bbox, and hold the pixels whose centers they hold
+            let radius =
+                |range: &RangeInclusive<u32>| (*range.end() - *range.start() + 1) as f32 / 2.0;
+            let (radius_x, radius_y) = (radius(&xs), radius(&ys));
+            let (center_x, center_y) =
+                (*xs.start() as f32 + radius_x, *ys.start() as f32 + radius_y);
+
+            for y in ys.clone() {
+                for x in xs.clone() {
+                    let dx = (x as f32 + 0.5 - center_x).abs() / radius_x;
+                    let dy = (y as f32 + 0.5 - center_y).abs() / radius_y;
+
+                    let solid = match sprite.collision_kind {
+                        CollisionKind::Ellipse => dx * dx + dy * dy <= 1.0,
+                        CollisionKind::Diamond => dx + dy <= 1.0,
+                        _ => true,
+                    };
+                    if solid {
+                        mask.set_solid(x, y);
+                    }
+                }
+            }
+
+            vec![mask]
+        }
+    }
+}
+
+/// The columns and rows within the bbox of `sprite`, clamped to the sprite.
+fn bbox_ranges(sprite: &Sprite) -> (RangeInclusive<u32>, RangeInclusive<u32>) {
+    let clamp = |start: i32, end: i32, size: usize| {
+        let end = end.min(size as i32 - 1);
+        if end < 0 || start > end {
+            #[allow(clippy::reversed_empty_ranges)]
+            return 1..=0;
+        }
+
+        start.max(0) as u32..=end as u32
+    };
+
+    (
+        clamp(sprite.bbox_left, sprite.bbox_right, sprite.width.get()),
+        clamp(sprite.bbox_top, sprite.bbox_bottom, sprite.height.get()),
+    )
+}
+
+fn add_pixels(
+    mask: &mut CollisionMask,
+    image: &SpriteImageBuffer,
+    tolerance: u8,
+    xs: &RangeInclusive<u32>,
+    ys: &RangeInclusive<u32>,
+) {
+    for y in ys.clone() {
+        for x in xs.clone() {
+            if x < image.width() && y < image.height() && image.get_pixel(x, y)[3] > tolerance {
+                mask.set_solid(x, y);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TempDirectory;
+    use crate::SpriteExt;
+    use std::num::NonZeroUsize;
+    use yy_typings::{SpriteSequenceId, TexturePath, ViewPath};
+
+    #[test]
+    fn precise_masks() {
+        let size = NonZeroUsize::new(4).unwrap();
+        let mut sprite = Sprite::new("spr_mask", TexturePath::default(), ViewPath::default())
+            .dimensions(size, size)
+            .collision_kind(CollisionKind::Precise);
+        let layer = sprite.layers[0].common_data.name;
+        let (first, second) = (FrameId::new(), FrameId::new());
+        sprite.set_frame(first, SpriteSequenceId::new());
+        sprite.set_frame(second, SpriteSequenceId::new());
+
+        let mut first_image = SpriteImageBuffer::new(4, 4);
+        first_image.put_pixel(1, 1, Rgba([0, 0, 0, 255]));
+        let mut second_image = SpriteImageBuffer::new(4, 4);
+        second_image.put_pixel(2, 2, Rgba([0, 0, 0, 255]));
+        let frames = maplit::hashmap! {
+            first => maplit::hashmap! { layer => first_image.clone() },
+            second => maplit::hashmap! { layer => second_image },
+        };
+        let sprite = sprite.automatic_bbox(&frames, 0);
+
+        // precise combines every frame...
+        let masks = sprite.collision_masks(&frames);
+        assert_eq!(masks.len(), 1);
+        assert!(masks[0].is_solid(1, 1) && masks[0].is_solid(2, 2));
+        assert!(masks[0].is_solid(1, 2) == false);
+
+        // ...and precise per frame doesn't
+        let masks = sprite
+            .clone()
+            .collision_kind(CollisionKind::PrecisePerFrame)
+            .collision_masks(&frames);
+        assert_eq!(masks.len(), 2);
+        assert!(masks[0].is_solid(1, 1) && masks[0].is_solid(2, 2) == false);
+        assert!(masks[1].is_solid(2, 2) && masks[1].is_solid(1, 1) == false);
+
+        let masks = sprite
+            .collision_kind(CollisionKind::Rectangle)
+            .collision_masks(&frames);
+        assert!(masks[0].is_solid(1, 2) && masks[0].is_solid(0, 0) == false);
+
+        let preview = masks[0].preview(&first_image);
+        assert_eq!(*preview.get_pixel(1, 2), Rgba([127, 0, 0, 128]));
+
+        let directory = TempDirectory::new();
+        let path = directory.join("mask.png");
+        masks[0].save(&path).unwrap();
+        let image = image::open(&path).unwrap().to_rgba8();
+        assert_eq!(*image.get_pixel(2, 1), SOLID);
+        assert_eq!(image.get_pixel(3, 3)[3], 0);
+    }
+}
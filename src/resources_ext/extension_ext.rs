@@ -0,0 +1,448 @@
+use crate::{
+    typings::{Extension, ExtensionConstant, ExtensionFunction},
+    FileHolder, FileSerializationError, Resource, SerializedData, SerializedDataError, YyResource,
+    YyResourceHandler, YyResourceHandlerError, YypBoss,
+};
+use anyhow::Context;
+use std::{collections::HashMap, path::Path};
+use yy_typings::{FilesystemPath, TrailingCommaUtility, ViewPath};
+
+impl YyResource for Extension {
+    /// The files bundled with the extension, such as its dlls and scripts, keyed by filename.
+    /// Files which are listed in the extension but missing on disk are left out.
+    type AssociatedData = HashMap<String, Vec<u8>>;
+    const SUBPATH_NAME: &'static str = "extensions";
+    const RESOURCE: Resource = Resource::Extension;
+
+    fn name(&self) -> &str {
+        &self.common_data.name
+    }
+
+    fn set_name(&mut self, name: String) {
+        // the function order points back at *this* extension's yy file.
+        let yy_path = FilesystemPath::new_path(Self::SUBPATH_NAME, &name);
+        for order in self.files.iter_mut().flat_map(|v| v.order.iter_mut()) {
+            order.path = yy_path.clone();
+        }
+
+        self.common_data.name = name;
+    }
+
+    fn set_parent_view_path(&mut self, vp: ViewPath) {
+        self.parent = vp;
+    }
+
+    fn parent_view_path(&self) -> ViewPath {
+        self.parent.clone()
+    }
+
+    fn get_handler(yyp_boss: &YypBoss) -> &YyResourceHandler<Self> {
+        &yyp_boss.extensions
+    }
+
+    fn get_handler_mut(yyp_boss: &mut YypBoss) -> &mut YyResourceHandler<Self> {
+        &mut yyp_boss.extensions
+    }
+
+    // we don't remove files here, since users keep all sorts of things next to their
+    // extensions. replaced files are cleaned up in `cleanup_on_replace`.
+    fn serialize_associated_data(
+        &self,
+        directory_path: &Path,
+        data: &Self::AssociatedData,
+    ) -> anyhow::Result<()> {
+        for filename in self.filenames() {
+            match data.get(filename) {
+                Some(bytes) => {
+                    let path = directory_path.join(filename);
+                    std::fs::write(&path, bytes).with_context(|| {
+                        format!("We couldn't write the extension file at path {:?}", path)
+                    })?;
+                }
+                None => log::warn!(
+                    "extension {} lists {}, but we don't have it. not serialized.",
+                    self.common_data.name,
+                    filename
+                ),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn deserialize_associated_data(
+        &self,
+        directory_path: &Path,
+        _: &TrailingCommaUtility,
+    ) -> Result<Self::AssociatedData, SerializedDataError> {
+        let mut files = HashMap::new();
+
+        for filename in self.filenames() {
+            let path = directory_path.join(filename);
+            if path.exists() == false {
+                continue;
+            }
+
+            let bytes = std::fs::read(&path).map_err(|e| {
+                SerializedDataError::CouldNotDeserializeFile(FileSerializationError::Io(
+                    e.to_string(),
+                ))
+            })?;
+
+            files.insert(filename.to_owned(), bytes);
+        }
+
+        Ok(files)
+    }
+
+    fn serialize_associated_data_into_data(
+        working_directory: &Path,
+        associated_data: &Self::AssociatedData,
+    ) -> Result<SerializedData, SerializedDataError> {
+        let directory = working_directory.join(uuid::Uuid::new_v4().to_string());
+
+        let write = || -> std::io::Result<()> {
+            std::fs::create_dir_all(&directory)?;
+            for (filename, bytes) in associated_data {
+                std::fs::write(directory.join(filename), bytes)?;
+            }
+
+            Ok(())
+        };
+
+        match write() {
+            Ok(()) => Ok(SerializedData::Filepath { data: directory }),
+            Err(e) => Err(SerializedDataError::InnerError(e.to_string())),
+        }
+    }
+
+    fn deserialize_associated_data_from_data(
+        &self,
+        incoming_data: &SerializedData,
+        tcu: &TrailingCommaUtility,
+    ) -> Result<Self::AssociatedData, SerializedDataError> {
+        match incoming_data {
+            SerializedData::Value { .. } => Err(SerializedDataError::CannotUseValue),
+            SerializedData::Filepath { data: p } => {
+                if p.is_dir() {
+                    self.deserialize_associated_data(p, tcu)
+                } else {
+                    Err(SerializedDataError::BadDataFile(p.to_owned()))
+                }
+            }
+            SerializedData::DefaultValue => Ok(HashMap::new()),
+        }
+    }
+
+    fn cleanup_on_replace(&self, mut files_to_delete: impl FileHolder) {
+        for filename in self.filenames() {
+            files_to_delete.push(filename.into());
+        }
+    }
+}
+
+impl YyResourceHandler<Extension> {
+    /// Adds a function to the file `filename` within an extension. The function's kind is set
+    /// to the kind of the file.
+    ///
+    /// The function's name must be a valid gml identifier, and must not already be used by
+    /// another function or constant in the extension. The extension's associated data must be loaded.
+    pub fn add_function(
+        &mut self,
+        extension_name: &str,
+        filename: &str,
+        mut function: ExtensionFunction,
+    ) -> Result<(), YyResourceHandlerError> {
+        let extension = self.editable_extension(extension_name)?;
+        check_name(extension, &function.name)?;
+
+        let file = extension
+            .file_mut(filename)
+            .ok_or(YyResourceHandlerError::ResourceNotFound)?;
+
+        function.kind = file.kind;
+        file.order.push(FilesystemPath {
+            name: function.name.clone(),
+            path: FilesystemPath::new_path(Extension::SUBPATH_NAME, extension_name),
+        });
+        file.functions.push(function);
+
+        self.mark_for_serialization(extension_name)
+    }
+
+    /// Removes a function from an extension, returning it.
+    ///
+    /// The extension's associated data must be loaded.
+    pub fn remove_function(
+        &mut self,
+        extension_name: &str,
+        function_name: &str,
+    ) -> Result<ExtensionFunction, YyResourceHandlerError> {
+        let extension = self.editable_extension(extension_name)?;
+
+        let file = extension
+            .files
+            .iter_mut()
+            .find(|file| file.functions.iter().any(|v| v.name == function_name))
+            .ok_or(YyResourceHandlerError::ResourceNotFound)?;
+
+        let position = file
+            .functions
+            .iter()
+            .position(|v| v.name == function_name)
+            .unwrap();
+        let function = file.functions.remove(position);
+        file.order.retain(|v| v.name != function_name);
+
+        self.mark_for_serialization(extension_name)?;
+
+        Ok(function)
+    }
+
+    /// Renames a function within an extension. The function's external name is left alone.
+    ///
+    /// The new name must be a valid gml identifier, and must not already be used by
+    /// another function or constant in the extension. The extension's associated data must be loaded.
+    pub fn rename_function(
+        &mut self,
+        extension_name: &str,
+        function_name: &str,
+        new_name: &str,
+    ) -> Result<(), YyResourceHandlerError> {
+        let extension = self.editable_extension(extension_name)?;
+        check_name(extension, new_name)?;
+
+        let file = extension
+            .files
+            .iter_mut()
+            .find(|file| file.functions.iter().any(|v| v.name == function_name))
+            .ok_or(YyResourceHandlerError::ResourceNotFound)?;
+
+        for function in file
+            .functions
+            .iter_mut()
+            .filter(|v| v.name == function_name)
+        {
+            function.name = new_name.to_owned();
+        }
+        for order in file.order.iter_mut().filter(|v| v.name == function_name) {
+            order.name = new_name.to_owned();
+        }
+
+        self.mark_for_serialization(extension_name)
+    }
+
+    /// Adds a constant to the file `filename` within an extension.
+    ///
+    /// The constant's name must be a valid gml identifier, and must not already be used by
+    /// another function or constant in the extension. The extension's associated data must be loaded.
+    pub fn add_constant(
+        &mut self,
+        extension_name: &str,
+        filename: &str,
+        constant: ExtensionConstant,
+    ) -> Result<(), YyResourceHandlerError> {
+        let extension = self.editable_extension(extension_name)?;
+        check_name(extension, &constant.name)?;
+
+        extension
+            .file_mut(filename)
+            .ok_or(YyResourceHandlerError::ResourceNotFound)?
+            .constants
+            .push(constant);
+
+        self.mark_for_serialization(extension_name)
+    }
+
+    /// Removes a constant from an extension, returning it.
+    ///
+    /// The extension's associated data must be loaded.
+    pub fn remove_constant(
+        &mut self,
+        extension_name: &str,
+        constant_name: &str,
+    ) -> Result<ExtensionConstant, YyResourceHandlerError> {
+        let extension = self.editable_extension(extension_name)?;
+
+        let constant = extension
+            .files
+            .iter_mut()
+            .find_map(|file| {
+                let position = file
+                    .constants
+                    .iter()
+                    .position(|v| v.name == constant_name)?;
+                Some(file.constants.remove(position))
+            })
+            .ok_or(YyResourceHandlerError::ResourceNotFound)?;
+
+        self.mark_for_serialization(extension_name)?;
+
+        Ok(constant)
+    }
+
+    /// Renames a constant within an extension.
+    ///
+    /// The new name must be a valid gml identifier, and must not already be used by
+    /// another function or constant in the extension. The extension's associated data must be loaded.
+    pub fn rename_constant(
+        &mut self,
+        extension_name: &str,
+        constant_name: &str,
+        new_name: &str,
+    ) -> Result<(), YyResourceHandlerError> {
+        let extension = self.editable_extension(extension_name)?;
+        check_name(extension, new_name)?;
+
+        let constant = extension
+            .files
+            .iter_mut()
+            .flat_map(|file| file.constants.iter_mut())
+            .find(|v| v.name == constant_name)
+            .ok_or(YyResourceHandlerError::ResourceNotFound)?;
+        constant.name = new_name.to_owned();
+
+        self.mark_for_serialization(extension_name)
+    }
+
+    /// Gets an extension which we can edit and then mark for serialization. We check
+    /// for the associated data up front so that we never edit something we can't serialize.
+    fn editable_extension(
+        &mut self,
+        extension_name: &str,
+    ) -> Result<&mut Extension, YyResourceHandlerError> {
+        let extension = unsafe { self.get_mut(extension_name) }
+            .ok_or(YyResourceHandlerError::ResourceNotFound)?;
+
+        if extension.associated_data.is_none() {
+            return Err(YyResourceHandlerError::CannotForceSerialization);
+        }
+
+        Ok(&mut extension.yy_resource)
+    }
+}
+
+/// Checks that a function or constant can be named `name` within the extension.
+fn check_name(extension: &Extension, name: &str) -> Result<(), YyResourceHandlerError> {
+    if crate::yyp_boss::RNV.is_valid(name) == false {
+        return Err(YyResourceHandlerError::BadName);
+    }
+
+    if extension.defines(name) {
+        return Err(YyResourceHandlerError::NameCollision);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::typings::{ExtensionFile, ExtensionValueType};
+
+    const EXTENSION: &str = r#"{
+  "$GMExtension":"",
+  "%Name":"ext_steam",
+  "androidactivityinject":"",
+  "author":"",
+  "classname":"",
+  "copyToTargets":-1,
+  "date":"2021-07-01T12:00:00",
+  "description":"",
+  "exportToGame":true,
+  "extensionVersion":"1.0.0",
+  "files":[
+    {"$GMExtensionFile":"","%Name":"","constants":[
+        {"$GMExtensionConstant":"","%Name":"STEAM_APP_ID","hidden":false,"name":"STEAM_APP_ID","resourceType":"GMExtensionConstant","resourceVersion":"2.0","value":"480",},
+      ],"copyToTargets":-1,"filename":"steam.dll","final":"","functions":[
+        {"$GMExtensionFunction":"","%Name":"steam_init","argCount":0,"args":[],"documentation":"","externalName":"SteamInit","help":"steam_init()","hidden":false,"kind":1,"name":"steam_init","resourceType":"GMExtensionFunction","resourceVersion":"2.0","returnType":2,},
+      ],"init":"","kind":1,"name":"","order":[
+        {"name":"steam_init","path":"extensions/ext_steam/ext_steam.yy",},
+      ],"origname":"","ProxyFiles":[
+        {"$GMProxyFile":"","%Name":"libsteam.so","name":"libsteam.so","resourceType":"GMProxyFile","resourceVersion":"2.0","TargetMask":7,},
+      ],"resourceType":"GMExtensionFile","resourceVersion":"2.0","uncompress":false,"usesRunnerInterface":false,},
+  ],
+  "helpfile":"",
+  "name":"ext_steam",
+  "options":[],
+  "parent":{"name":"Extensions","path":"folders/Extensions.yy",},
+  "resourceType":"GMExtension",
+  "resourceVersion":"2.0",
+}"#;
+
+    fn extension() -> Extension {
+        let tcu = TrailingCommaUtility::new();
+        serde_json::from_str(&tcu.clear_trailing_comma(EXTENSION)).unwrap()
+    }
+
+    #[test]
+    fn lossless_roundtrip() {
+        let extension = extension();
+        assert_eq!(
+            extension.filenames().collect::<Vec<_>>(),
+            vec!["steam.dll", "libsteam.so"]
+        );
+
+        let output = yy_typings::serialize_file(&extension);
+        let tcu = TrailingCommaUtility::new();
+        let original: serde_json::Value =
+            serde_json::from_str(&tcu.clear_trailing_comma(EXTENSION)).unwrap();
+        let reserialized: serde_json::Value =
+            serde_json::from_str(&tcu.clear_trailing_comma(&output)).unwrap();
+
+        assert_eq!(original, reserialized);
+    }
+
+    #[test]
+    fn functions_and_constants() {
+        let mut handler = YyResourceHandler::new();
+        let mut extension = extension();
+        extension.files.push(ExtensionFile::new("steam.gml"));
+        handler.set(extension, Some(HashMap::new()));
+
+        let function = ExtensionFunction::new(
+            "steam_get_name",
+            ExtensionValueType::String,
+            vec![ExtensionValueType::Real],
+        );
+        handler
+            .add_function("ext_steam", "steam.gml", function.clone())
+            .unwrap();
+        assert!(matches!(
+            handler.add_function("ext_steam", "steam.dll", function),
+            Err(YyResourceHandlerError::NameCollision)
+        ));
+
+        handler
+            .rename_function("ext_steam", "steam_init", "steam_start")
+            .unwrap();
+        handler
+            .rename_constant("ext_steam", "STEAM_APP_ID", "STEAM_ID")
+            .unwrap();
+        assert!(matches!(
+            handler.add_constant(
+                "ext_steam",
+                "steam.dll",
+                ExtensionConstant::new("1bad", "0")
+            ),
+            Err(YyResourceHandlerError::BadName)
+        ));
+
+        let extension = &handler.get("ext_steam").unwrap().yy_resource;
+        assert_eq!(extension.files[0].order[0].name, "steam_start");
+        assert_eq!(extension.files[0].functions[0].external_name, "SteamInit");
+        assert_eq!(extension.files[1].functions[0].kind, 2);
+        assert!(extension.defines("STEAM_ID"));
+
+        let removed = handler
+            .remove_function("ext_steam", "steam_get_name")
+            .unwrap();
+        assert_eq!(removed.args, vec![ExtensionValueType::Real]);
+        assert!(handler.get("ext_steam").unwrap().yy_resource.files[1]
+            .order
+            .is_empty());
+        assert!(handler
+            .remove_constant("ext_steam", "STEAM_APP_ID")
+            .is_err());
+    }
+}
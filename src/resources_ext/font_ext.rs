@@ -0,0 +1,159 @@
+use crate::{
+    typings::Font, FileHolder, Resource, SerializedData, SerializedDataError, SpriteImageBuffer,
+    YyResource, YyResourceHandler, YypBoss,
+};
+use anyhow::Context;
+use std::path::Path;
+use yy_typings::{TrailingCommaUtility, ViewPath};
+
+impl YyResource for Font {
+    /// The glyph atlas. Fonts which GameMaker has never regenerated won't have one.
+    type AssociatedData = Option<SpriteImageBuffer>;
+
+    const SUBPATH_NAME: &'static str = "fonts";
+    const RESOURCE: Resource = Resource::Font;
+
+    fn name(&self) -> &str {
+        &self.common_data.name
+    }
+
+    fn set_name(&mut self, name: String) {
+        self.common_data.name = name;
+    }
+
+    fn set_parent_view_path(&mut self, vp: ViewPath) {
+        self.parent = vp;
+    }
+
+    fn parent_view_path(&self) -> ViewPath {
+        self.parent.clone()
+    }
+
+    fn get_handler(yyp_boss: &YypBoss) -> &YyResourceHandler<Self> {
+        &yyp_boss.fonts
+    }
+
+    fn get_handler_mut(yyp_boss: &mut YypBoss) -> &mut YyResourceHandler<Self> {
+        &mut yyp_boss.fonts
+    }
+
+    fn serialize_associated_data(
+        &self,
+        directory_path: &Path,
+        data: &Self::AssociatedData,
+    ) -> anyhow::Result<()> {
+        if let Some(atlas) = data {
+            let path = directory_path.join(self.atlas_filename());
+            atlas
+                .save(&path)
+                .with_context(|| format!("We couldn't save the font atlas at path {:?}", path))?;
+        }
+
+        Ok(())
+    }
+
+    fn deserialize_associated_data(
+        &self,
+        directory_path: &Path,
+        _: &TrailingCommaUtility,
+    ) -> Result<Self::AssociatedData, SerializedDataError> {
+        let path = directory_path.join(self.atlas_filename());
+        if path.exists() == false {
+            return Ok(None);
+        }
+
+        match image::open(&path) {
+            Ok(image) => Ok(Some(image.to_rgba8())),
+            Err(e) => Err(SerializedDataError::BadData(format!(
+                "we couldn't read {:#?} -- {}",
+                path, e
+            ))),
+        }
+    }
+
+    fn serialize_associated_data_into_data(
+        working_directory: &Path,
+        associated_data: &Self::AssociatedData,
+    ) -> Result<SerializedData, SerializedDataError> {
+        match associated_data {
+            Some(atlas) => {
+                let path = working_directory.join(format!("{}.png", uuid::Uuid::new_v4()));
+                atlas
+                    .save(&path)
+                    .map_err(SerializedDataError::CouldNotWriteImage)?;
+
+                Ok(SerializedData::Filepath { data: path })
+            }
+            None => Ok(SerializedData::DefaultValue),
+        }
+    }
+
+    fn deserialize_associated_data_from_data(
+        &self,
+        incoming_data: &SerializedData,
+        tcu: &TrailingCommaUtility,
+    ) -> Result<Self::AssociatedData, SerializedDataError> {
+        match incoming_data {
+            SerializedData::Value { .. } => Err(SerializedDataError::CannotUseValue),
+            SerializedData::Filepath { data: p } => {
+                if p.is_dir() {
+                    self.deserialize_associated_data(p, tcu)
+                } else {
+                    let image = image::open(p)
+                        .map_err(|_| SerializedDataError::BadDataFile(p.to_owned()))?;
+
+                    Ok(Some(image.to_rgba8()))
+                }
+            }
+            SerializedData::DefaultValue => Ok(None),
+        }
+    }
+
+    fn cleanup_on_replace(&self, mut files_to_delete: impl FileHolder) {
+        files_to_delete.push(self.atlas_filename().into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FONT: &str = r#"{
+  "$GMFont":"",
+  "%Name":"fnt_body",
+  "AntiAlias":1,
+  "applyKerning":0,
+  "ascender":14,
+  "bold":false,
+  "fontName":"Arial",
+  "glyphs":{
+    "32":{"character":32,"h":17,"offset":0,"shift":4,"w":4,"x":2,"y":2,},
+  },
+  "kerningPairs":[],
+  "name":"fnt_body",
+  "parent":{"name":"Fonts","path":"folders/Fonts.yy",},
+  "ranges":[
+    {"lower":32,"upper":127,},
+  ],
+  "resourceType":"GMFont",
+  "resourceVersion":"2.0",
+  "sampleText":"abcdef ABCDEF",
+  "size":12.0,
+  "textureGroupId":{"name":"Default","path":"texturegroups/Default",},
+}"#;
+
+    #[test]
+    fn lossless_roundtrip() {
+        let tcu = TrailingCommaUtility::new();
+        let font: Font = serde_json::from_str(&tcu.clear_trailing_comma(FONT)).unwrap();
+        assert_eq!(font.atlas_filename(), "fnt_body.png");
+
+        let output = yy_typings::serialize_file(&font);
+        let original: serde_json::Value =
+            serde_json::from_str(&tcu.clear_trailing_comma(FONT)).unwrap();
+        let reserialized: serde_json::Value =
+            serde_json::from_str(&tcu.clear_trailing_comma(&output)).unwrap();
+
+        assert_eq!(original, reserialized);
+    }
+}
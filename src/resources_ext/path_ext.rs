@@ -0,0 +1,175 @@
+use crate::{
+    typings::{Path as YyPath, PathKind, PathPoint},
+    FileHolder, Resource, SerializedData, SerializedDataError, YyResource, YyResourceHandler,
+    YypBoss,
+};
+use std::path::Path;
+use yy_typings::{CommonData, TrailingCommaUtility, ViewPath};
+
+pub trait PathExt: Sized {
+    fn with(self, edit: impl Fn(&mut Self)) -> Self;
+    fn new(name: &str, parent: ViewPath) -> Self;
+    fn parent(self, parent: ViewPath) -> Self;
+    /// Replaces all of the points in the path with the given points.
+    fn points(self, points: &[PathPoint]) -> Self;
+    /// Sets the precision of the path. This is clamped between 1 and 8.
+    fn precision(self, precision: u8) -> Self;
+    fn closed(self, closed: bool) -> Self;
+    fn kind(self, kind: PathKind) -> Self;
+
+    /// Adds a point to the end of the path.
+    fn push_point(&mut self, point: PathPoint);
+    /// Inserts a point at the given index, shifting all points after it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`.
+    fn insert_point(&mut self, index: usize, point: PathPoint);
+    /// Removes a point at the given index, if it exists.
+    fn remove_point(&mut self, index: usize) -> Option<PathPoint>;
+    fn set_clear_all_points(&mut self);
+}
+
+impl PathExt for YyPath {
+    fn with(mut self, edit: impl Fn(&mut Self)) -> Self {
+        edit(&mut self);
+        self
+    }
+
+    fn new(name: &str, parent: ViewPath) -> Self {
+        YyPath {
+            common_data: CommonData::new(name.to_owned()),
+            parent,
+            ..YyPath::default()
+        }
+    }
+
+    fn parent(self, parent: ViewPath) -> Self {
+        self.with(|me| me.parent = parent.clone())
+    }
+
+    fn points(self, points: &[PathPoint]) -> Self {
+        self.with(|me| me.points = points.to_vec())
+    }
+
+    fn precision(self, precision: u8) -> Self {
+        self.with(|me| me.precision = precision.clamp(1, YyPath::MAX_PRECISION))
+    }
+
+    fn closed(self, closed: bool) -> Self {
+        self.with(|me| me.closed = closed)
+    }
+
+    fn kind(self, kind: PathKind) -> Self {
+        self.with(|me| me.kind = kind)
+    }
+
+    fn push_point(&mut self, point: PathPoint) {
+        self.points.push(point);
+    }
+
+    fn insert_point(&mut self, index: usize, point: PathPoint) {
+        self.points.insert(index, point);
+    }
+
+    fn remove_point(&mut self, index: usize) -> Option<PathPoint> {
+        if index < self.points.len() {
+            Some(self.points.remove(index))
+        } else {
+            None
+        }
+    }
+
+    fn set_clear_all_points(&mut self) {
+        self.points.clear();
+    }
+}
+
+impl YyResource for YyPath {
+    type AssociatedData = ();
+    const SUBPATH_NAME: &'static str = "paths";
+    const RESOURCE: Resource = Resource::Path;
+
+    fn name(&self) -> &str {
+        &self.common_data.name
+    }
+
+    fn set_name(&mut self, name: String) {
+        self.common_data.name = name;
+    }
+
+    fn set_parent_view_path(&mut self, vp: ViewPath) {
+        self.parent = vp;
+    }
+
+    fn parent_view_path(&self) -> ViewPath {
+        self.parent.clone()
+    }
+
+    fn get_handler(yyp_boss: &YypBoss) -> &YyResourceHandler<Self> {
+        &yyp_boss.paths
+    }
+
+    fn get_handler_mut(yyp_boss: &mut YypBoss) -> &mut YyResourceHandler<Self> {
+        &mut yyp_boss.paths
+    }
+
+    // paths are entirely described by their yy file
+    fn serialize_associated_data(&self, _: &Path, _: &Self::AssociatedData) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn deserialize_associated_data(
+        &self,
+        _: &Path,
+        _: &TrailingCommaUtility,
+    ) -> Result<Self::AssociatedData, SerializedDataError> {
+        Ok(())
+    }
+
+    fn serialize_associated_data_into_data(
+        _: &Path,
+        _: &Self::AssociatedData,
+    ) -> Result<SerializedData, SerializedDataError> {
+        Ok(SerializedData::Value {
+            data: String::new(),
+        })
+    }
+
+    fn deserialize_associated_data_from_data(
+        &self,
+        _: &SerializedData,
+        _: &TrailingCommaUtility,
+    ) -> Result<Self::AssociatedData, SerializedDataError> {
+        Ok(())
+    }
+
+    fn cleanup_on_replace(&self, _: impl FileHolder) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builder() {
+        let mut path = YyPath::new("pth_walk", ViewPath::default())
+            .points(&[PathPoint::new(0.0, 0.0), PathPoint::new(32.0, 0.0)])
+            .precision(12)
+            .closed(true)
+            .kind(PathKind::Smooth);
+
+        path.insert_point(1, PathPoint::new(16.0, 16.0));
+        assert_eq!(path.points[1], PathPoint::new(16.0, 16.0));
+        assert_eq!(path.remove_point(3), None);
+        assert_eq!(path.precision, YyPath::MAX_PRECISION);
+
+        let output = yy_typings::serialize_file(&path);
+        let tcu = TrailingCommaUtility::new();
+        let reparsed: YyPath = serde_json::from_str(&tcu.clear_trailing_comma(&output)).unwrap();
+
+        assert_eq!(path.points, reparsed.points);
+        assert_eq!(output, yy_typings::serialize_file(&reparsed));
+        assert!(output.contains("\"kind\":1"));
+    }
+}
@@ -0,0 +1,330 @@
+use crate::{
+    typings::Room, utils, FileHolder, FileSerializationError, Resource, SerializedData,
+    SerializedDataError, YyResource, YyResourceHandler, YyResourceHandlerError, YypBoss,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+use yy_typings::{FilesystemPath, TrailingCommaUtility, ViewPath};
+
+/// The gml attached to a room.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoomCreationCode {
+    /// The creation code of the room itself. This is only written if the room
+    /// has a `creation_code_file`.
+    pub room: String,
+
+    /// The creation code of each instance which has creation code, keyed by
+    /// the instance's name.
+    pub instances: HashMap<String, String>,
+}
+
+impl YyResource for Room {
+    type AssociatedData = RoomCreationCode;
+    const SUBPATH_NAME: &'static str = "rooms";
+    const RESOURCE: Resource = Resource::Room;
+
+    fn name(&self) -> &str {
+        &self.common_data.name
+    }
+
+    fn set_name(&mut self, name: String) {
+        if self.creation_code_file.is_empty() == false {
+            self.creation_code_file = format!(
+                "{}/{}/{}",
+                Self::SUBPATH_NAME,
+                name,
+                Room::CREATION_CODE_FILENAME
+            );
+        }
+
+        // the creation order points back at *this* room's yy file.
+        let yy_path = FilesystemPath::new_path(Self::SUBPATH_NAME, &name);
+        for instance in self.instance_creation_order.iter_mut() {
+            instance.path = yy_path.clone();
+        }
+
+        self.common_data.name = name;
+    }
+
+    fn set_parent_view_path(&mut self, vp: ViewPath) {
+        self.parent = vp;
+    }
+
+    fn parent_view_path(&self) -> ViewPath {
+        self.parent.clone()
+    }
+
+    fn get_handler(yyp_boss: &YypBoss) -> &YyResourceHandler<Self> {
+        &yyp_boss.rooms
+    }
+
+    fn get_handler_mut(yyp_boss: &mut YypBoss) -> &mut YyResourceHandler<Self> {
+        &mut yyp_boss.rooms
+    }
+
+    fn serialize_associated_data(
+        &self,
+        directory_path: &Path,
+        data: &Self::AssociatedData,
+    ) -> anyhow::Result<()> {
+        let mut allowed_files = HashSet::new();
+
+        if self.creation_code_file.is_empty() == false {
+            let path = directory_path.join(Room::CREATION_CODE_FILENAME);
+            std::fs::write(&path, &data.room)?;
+            allowed_files.insert(path);
+        }
+
+        for instance in self.instances().filter(|v| v.has_creation_code) {
+            let path = directory_path.join(Room::instance_creation_code_filename(&instance.name));
+            let gml = data.instances.get(&instance.name).map(|v| v.as_str());
+            std::fs::write(&path, gml.unwrap_or_default())?;
+            allowed_files.insert(path);
+        }
+
+        // clear out any creation code which no longer belongs to anything
+        let stale_files: Vec<PathBuf> = directory_path
+            .read_dir()?
+            .filter_map(|f| f.ok().map(|f| f.path()))
+            .filter(|path| {
+                let file_name = path
+                    .file_name()
+                    .and_then(|v| v.to_str())
+                    .unwrap_or_default();
+
+                let is_creation_code = file_name == Room::CREATION_CODE_FILENAME
+                    || (file_name.starts_with("InstanceCreationCode_")
+                        && file_name.ends_with(".gml"));
+
+                is_creation_code && allowed_files.contains(path) == false
+            })
+            .collect();
+
+        for stale_file in stale_files {
+            log::info!("removing stale creation code {}", stale_file.display());
+            std::fs::remove_file(stale_file)?;
+        }
+
+        Ok(())
+    }
+
+    fn deserialize_associated_data(
+        &self,
+        directory_path: &Path,
+        _: &TrailingCommaUtility,
+    ) -> Result<Self::AssociatedData, SerializedDataError> {
+        // gamemaker is happy to leave these files out if they're blank
+        fn read_gml(path: &Path) -> Result<String, SerializedDataError> {
+            if path.exists() == false {
+                return Ok(String::new());
+            }
+
+            std::fs::read_to_string(path).map_err(|e| {
+                SerializedDataError::CouldNotDeserializeFile(FileSerializationError::Io(
+                    e.to_string(),
+                ))
+            })
+        }
+
+        let room = if self.creation_code_file.is_empty() {
+            String::new()
+        } else {
+            read_gml(&directory_path.join(Room::CREATION_CODE_FILENAME))?
+        };
+
+        let mut instances = HashMap::new();
+        for instance in self.instances().filter(|v| v.has_creation_code) {
+            let path = directory_path.join(Room::instance_creation_code_filename(&instance.name));
+            instances.insert(instance.name.clone(), read_gml(&path)?);
+        }
+
+        Ok(RoomCreationCode { room, instances })
+    }
+
+    fn serialize_associated_data_into_data(
+        _: &Path,
+        associated_data: &Self::AssociatedData,
+    ) -> Result<SerializedData, SerializedDataError> {
+        match serde_json::to_string_pretty(associated_data) {
+            Ok(data) => Ok(SerializedData::Value { data }),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn deserialize_associated_data_from_data(
+        &self,
+        incoming_data: &SerializedData,
+        tcu: &TrailingCommaUtility,
+    ) -> Result<Self::AssociatedData, SerializedDataError> {
+        match incoming_data {
+            SerializedData::Value { data } => serde_json::from_str(data).map_err(|e| e.into()),
+            SerializedData::Filepath { data } => {
+                if data.is_dir() {
+                    self.deserialize_associated_data(data, tcu)
+                } else {
+                    utils::deserialize_json_tc(data, tcu).map_err(|e| e.into())
+                }
+            }
+            SerializedData::DefaultValue => Ok(RoomCreationCode {
+                room: String::new(),
+                instances: self
+                    .instances()
+                    .filter(|v| v.has_creation_code)
+                    .map(|v| (v.name.clone(), String::new()))
+                    .collect(),
+            }),
+        }
+    }
+
+    fn cleanup_on_replace(&self, mut files_to_delete: impl FileHolder) {
+        if self.creation_code_file.is_empty() == false {
+            files_to_delete.push(Path::new(Room::CREATION_CODE_FILENAME).to_owned());
+        }
+
+        for instance in self.instances().filter(|v| v.has_creation_code) {
+            files_to_delete.push(Room::instance_creation_code_filename(&instance.name).into());
+        }
+    }
+}
+
+impl YyResourceHandler<Room> {
+    /// Sets the creation code of a room. Passing `None` removes the room's creation code.
+    ///
+    /// The room's associated data must be loaded.
+    pub fn set_creation_code(
+        &mut self,
+        room_name: &str,
+        gml: Option<String>,
+    ) -> Result<(), YyResourceHandlerError> {
+        let room =
+            unsafe { self.get_mut(room_name) }.ok_or(YyResourceHandlerError::ResourceNotFound)?;
+        let code = room
+            .associated_data
+            .as_mut()
+            .ok_or(YyResourceHandlerError::CannotForceSerialization)?;
+
+        match gml {
+            Some(gml) => {
+                room.yy_resource.creation_code_file = format!(
+                    "{}/{}/{}",
+                    Room::SUBPATH_NAME,
+                    room.yy_resource.common_data.name,
+                    Room::CREATION_CODE_FILENAME
+                );
+                code.room = gml;
+            }
+            None => {
+                room.yy_resource.creation_code_file.clear();
+                code.room.clear();
+            }
+        }
+
+        self.mark_for_serialization(room_name)
+    }
+
+    /// Sets the creation code of an instance within a room. Passing `None` removes the
+    /// instance's creation code.
+    ///
+    /// The room's associated data must be loaded.
+    pub fn set_instance_creation_code(
+        &mut self,
+        room_name: &str,
+        instance_name: &str,
+        gml: Option<String>,
+    ) -> Result<(), YyResourceHandlerError> {
+        let room =
+            unsafe { self.get_mut(room_name) }.ok_or(YyResourceHandlerError::ResourceNotFound)?;
+        let code = room
+            .associated_data
+            .as_mut()
+            .ok_or(YyResourceHandlerError::CannotForceSerialization)?;
+        let instance = room
+            .yy_resource
+            .instance_mut(instance_name)
+            .ok_or(YyResourceHandlerError::ResourceNotFound)?;
+
+        instance.has_creation_code = gml.is_some();
+        match gml {
+            Some(gml) => {
+                code.instances.insert(instance_name.to_owned(), gml);
+            }
+            None => {
+                code.instances.remove(instance_name);
+            }
+        }
+
+        self.mark_for_serialization(room_name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROOM: &str = r#"{
+  "$GMRoom":"v1",
+  "%Name":"rm_level",
+  "creationCodeFile":"rooms/rm_level/RoomCreationCode.gml",
+  "inheritCode":false,
+  "instanceCreationOrder":[
+    {"name":"inst_1A2B3C4D","path":"rooms/rm_level/rm_level.yy",},
+  ],
+  "isDnd":false,
+  "layers":[
+    {"$GMRInstanceLayer":"","%Name":"Instances","depth":0,"effectEnabled":true,"instances":[
+        {"$GMRInstance":"v1","%Name":"inst_1A2B3C4D","colour":4294967295,"hasCreationCode":true,"name":"inst_1A2B3C4D","objectId":{"name":"obj_player","path":"objects/obj_player/obj_player.yy",},"properties":[],"resourceType":"GMRInstance","resourceVersion":"2.0","rotation":0.0,"scaleX":1.0,"scaleY":1.0,"x":32.0,"y":64.0,},
+      ],"layers":[],"name":"Instances","resourceType":"GMRInstanceLayer","resourceVersion":"2.0","visible":true,},
+    {"$GMRLayer":"","%Name":"Folder","depth":100,"layers":[
+        {"$GMRBackgroundLayer":"","%Name":"Background","colour":4278190080,"depth":200,"layers":[],"name":"Background","resourceType":"GMRBackgroundLayer","resourceVersion":"2.0","spriteId":null,"visible":true,"x":0,"y":0,},
+      ],"name":"Folder","resourceType":"GMRLayer","resourceVersion":"2.0","visible":true,},
+  ],
+  "name":"rm_level",
+  "parent":{"name":"Rooms","path":"folders/Rooms.yy",},
+  "parentRoom":null,
+  "physicsSettings":{"inheritPhysicsSettings":false,"PhysicsWorld":false,"PhysicsWorldGravityX":0.0,"PhysicsWorldGravityY":10.0,"PhysicsWorldPixToMetres":0.1,},
+  "resourceType":"GMRoom",
+  "resourceVersion":"2.0",
+  "roomSettings":{"Height":768,"inheritRoomSettings":false,"persistent":false,"Width":1366,},
+  "volume":1.0,
+}"#;
+
+    fn room() -> Room {
+        let tcu = TrailingCommaUtility::new();
+        serde_json::from_str(&tcu.clear_trailing_comma(ROOM)).unwrap()
+    }
+
+    #[test]
+    fn lossless_roundtrip() {
+        let room = room();
+        assert_eq!(room.instances().count(), 1);
+        assert_eq!(room.all_layers().count(), 3);
+
+        let output = yy_typings::serialize_file(&room);
+        let tcu = TrailingCommaUtility::new();
+        let original: serde_json::Value =
+            serde_json::from_str(&tcu.clear_trailing_comma(ROOM)).unwrap();
+        let reserialized: serde_json::Value =
+            serde_json::from_str(&tcu.clear_trailing_comma(&output)).unwrap();
+
+        assert_eq!(original, reserialized);
+    }
+
+    #[test]
+    fn rename() {
+        let mut room = room();
+        room.set_name("rm_boss".to_string());
+
+        assert_eq!(
+            room.creation_code_file,
+            "rooms/rm_boss/RoomCreationCode.gml"
+        );
+        assert_eq!(
+            room.instance_creation_order[0].path,
+            Path::new("rooms/rm_boss/rm_boss.yy")
+        );
+    }
+}
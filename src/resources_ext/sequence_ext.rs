@@ -0,0 +1,265 @@
+use crate::{
+    typings::{Keyframe, Sequence, SequenceTrack, SequenceTrackKind},
+    utils, FileHolder, FileSerializationError, Resource, SerializedData, SerializedDataError,
+    YyResource, YyResourceHandler, YypBoss,
+};
+use std::{collections::HashMap, path::Path};
+use yy_typings::{CommonData, FilesystemPath, PlaybackSpeed, TrailingCommaUtility, ViewPath};
+
+pub trait SequenceExt: Sized {
+    fn with(self, edit: impl Fn(&mut Self)) -> Self;
+    fn new(name: &str, parent: ViewPath) -> Self;
+    fn parent(self, parent: ViewPath) -> Self;
+    /// Sets the length of the sequence, in frames.
+    fn length(self, length: f64) -> Self;
+    fn playback_speed(self, pback_speed: PlaybackSpeed, speed: f64) -> Self;
+
+    /// Adds an empty track which shows the sprite `sprite_name`, returning the index of the track.
+    fn add_sprite_track(&mut self, sprite_name: &str) -> usize;
+    /// Adds an empty track which plays the sound `sound_name`, returning the index of the track.
+    fn add_audio_track(&mut self, sound_name: &str) -> usize;
+    /// Adds an empty track which creates an instance of `object_name`, returning the index of
+    /// the track.
+    fn add_instance_track(&mut self, object_name: &str) -> usize;
+
+    /// Inserts a keyframe into the track at `track_index`, starting at `key` and lasting `length`
+    /// frames. The keyframe holds the track's asset, and keyframes are kept ordered by `key`.
+    ///
+    /// Returns `false` if the track does not exist or is not a sprite, audio or instance track.
+    fn insert_keyframe(&mut self, track_index: usize, key: f64, length: f64) -> bool;
+}
+
+impl SequenceExt for Sequence {
+    fn with(mut self, edit: impl Fn(&mut Self)) -> Self {
+        edit(&mut self);
+        self
+    }
+
+    fn new(name: &str, parent: ViewPath) -> Self {
+        Sequence {
+            common_data: CommonData::new(name.to_owned()),
+            parent,
+            ..Sequence::default()
+        }
+    }
+
+    fn parent(self, parent: ViewPath) -> Self {
+        self.with(|me| me.parent = parent.clone())
+    }
+
+    fn length(self, length: f64) -> Self {
+        self.with(|me| me.length = length)
+    }
+
+    fn playback_speed(self, speed_type: PlaybackSpeed, speed: f64) -> Self {
+        self.with(|me| {
+            me.playback_speed_type = speed_type;
+            me.playback_speed = speed;
+        })
+    }
+
+    fn add_sprite_track(&mut self, sprite_name: &str) -> usize {
+        self.tracks
+            .push(SequenceTrack::new(sprite_name, SequenceTrackKind::Graphic));
+        self.tracks.len() - 1
+    }
+
+    fn add_audio_track(&mut self, sound_name: &str) -> usize {
+        self.tracks
+            .push(SequenceTrack::new(sound_name, SequenceTrackKind::Audio));
+        self.tracks.len() - 1
+    }
+
+    fn add_instance_track(&mut self, object_name: &str) -> usize {
+        self.tracks
+            .push(SequenceTrack::new(object_name, SequenceTrackKind::Instance));
+        self.tracks.len() - 1
+    }
+
+    fn insert_keyframe(&mut self, track_index: usize, key: f64, length: f64) -> bool {
+        let track = match self.tracks.get_mut(track_index) {
+            Some(v) => v,
+            None => return false,
+        };
+
+        let (keyframe_kind, subpath) = match (
+            track.resource_type.keyframe_kind(),
+            track.resource_type.asset_subpath(),
+        ) {
+            (Some(kind), Some(subpath)) => (kind, subpath),
+            _ => return false,
+        };
+
+        let asset = FilesystemPath::new(subpath, &track.name);
+        let keyframe = Keyframe::new_asset(keyframe_kind, key, length, &asset);
+
+        let keyframes = &mut track.keyframes.keyframes;
+        let position = keyframes
+            .iter()
+            .position(|v| v.key > key)
+            .unwrap_or(keyframes.len());
+        keyframes.insert(position, keyframe);
+
+        true
+    }
+}
+
+impl YyResource for Sequence {
+    /// The gml files which sit next to the sequence's yy file, such as the stubs for moments
+    /// and broadcast events, keyed by their file stem.
+    type AssociatedData = HashMap<String, String>;
+    const SUBPATH_NAME: &'static str = "sequences";
+    const RESOURCE: Resource = Resource::Sequence;
+
+    fn name(&self) -> &str {
+        &self.common_data.name
+    }
+
+    fn set_name(&mut self, name: String) {
+        self.common_data.name = name;
+    }
+
+    fn set_parent_view_path(&mut self, vp: ViewPath) {
+        self.parent = vp;
+    }
+
+    fn parent_view_path(&self) -> ViewPath {
+        self.parent.clone()
+    }
+
+    fn get_handler(yyp_boss: &YypBoss) -> &YyResourceHandler<Self> {
+        &yyp_boss.sequences
+    }
+
+    fn get_handler_mut(yyp_boss: &mut YypBoss) -> &mut YyResourceHandler<Self> {
+        &mut yyp_boss.sequences
+    }
+
+    fn serialize_associated_data(
+        &self,
+        directory_path: &Path,
+        data: &Self::AssociatedData,
+    ) -> anyhow::Result<()> {
+        for (stem, gml) in data {
+            std::fs::write(directory_path.join(format!("{}.gml", stem)), gml)?;
+        }
+
+        // and clear out any gml which has been removed
+        for path in gml_files(directory_path) {
+            let stem = path
+                .file_stem()
+                .and_then(|v| v.to_str())
+                .unwrap_or_default();
+            if data.contains_key(stem) == false {
+                std::fs::remove_file(path)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn deserialize_associated_data(
+        &self,
+        directory_path: &Path,
+        _: &TrailingCommaUtility,
+    ) -> Result<Self::AssociatedData, SerializedDataError> {
+        let mut output = HashMap::new();
+
+        for path in gml_files(directory_path) {
+            let stem = path
+                .file_stem()
+                .and_then(|v| v.to_str())
+                .unwrap_or_default()
+                .to_owned();
+
+            let gml = std::fs::read_to_string(&path).map_err(|e| {
+                SerializedDataError::CouldNotDeserializeFile(FileSerializationError::Io(
+                    e.to_string(),
+                ))
+            })?;
+
+            output.insert(stem, gml);
+        }
+
+        Ok(output)
+    }
+
+    fn serialize_associated_data_into_data(
+        _: &Path,
+        associated_data: &Self::AssociatedData,
+    ) -> Result<SerializedData, SerializedDataError> {
+        match serde_json::to_string_pretty(associated_data) {
+            Ok(data) => Ok(SerializedData::Value { data }),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn deserialize_associated_data_from_data(
+        &self,
+        incoming_data: &SerializedData,
+        tcu: &TrailingCommaUtility,
+    ) -> Result<Self::AssociatedData, SerializedDataError> {
+        match incoming_data {
+            SerializedData::Value { data } => serde_json::from_str(data).map_err(|e| e.into()),
+            SerializedData::Filepath { data } => {
+                if data.is_dir() {
+                    self.deserialize_associated_data(data, tcu)
+                } else {
+                    utils::deserialize_json_tc(data, tcu).map_err(|e| e.into())
+                }
+            }
+            SerializedData::DefaultValue => Ok(HashMap::new()),
+        }
+    }
+
+    // we don't need to clean up here -- `serialize_associated_data` removes any
+    // gml which isn't in the associated data.
+    fn cleanup_on_replace(&self, _: impl FileHolder) {}
+}
+
+fn gml_files(directory_path: &Path) -> Vec<std::path::PathBuf> {
+    directory_path
+        .read_dir()
+        .map(|d| {
+            d.filter_map(|f| f.ok().map(|f| f.path()))
+                .filter(|path| path.extension().is_some_and(|ext| ext == "gml"))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracks_and_keyframes() {
+        let mut sequence = Sequence::new("seq_intro", ViewPath::default()).length(120.0);
+
+        let sprite_track = sequence.add_sprite_track("spr_player");
+        let audio_track = sequence.add_audio_track("snd_theme");
+        assert!(sequence.insert_keyframe(sprite_track, 60.0, 60.0));
+        assert!(sequence.insert_keyframe(sprite_track, 0.0, 60.0));
+        assert!(sequence.insert_keyframe(audio_track, 0.0, 120.0));
+        assert!(sequence.insert_keyframe(5, 0.0, 1.0) == false);
+
+        let keyframes = &sequence.tracks[sprite_track].keyframes.keyframes;
+        assert_eq!(keyframes[0].key, 0.0);
+        assert_eq!(keyframes[1].key, 60.0);
+        assert_eq!(
+            keyframes[0].channels["0"]["Id"]["path"],
+            "sprites/spr_player/spr_player.yy"
+        );
+
+        let output = yy_typings::serialize_file(&sequence);
+        let tcu = TrailingCommaUtility::new();
+        let reparsed: Sequence = serde_json::from_str(&tcu.clear_trailing_comma(&output)).unwrap();
+
+        assert_eq!(reparsed.tracks.len(), 2);
+        assert_eq!(
+            reparsed.tracks[audio_track].resource_type,
+            SequenceTrackKind::Audio
+        );
+        assert_eq!(output, yy_typings::serialize_file(&reparsed));
+    }
+}
@@ -107,8 +107,11 @@ impl YyResource for Shader {
         }
     }
 
-    fn cleanup_on_replace(&self, _: impl FileHolder) {
-        todo!()
+    fn cleanup_on_replace(&self, mut files: impl FileHolder) {
+        let base_path = Path::new(&self.common_data.name);
+
+        files.push(base_path.with_extension(Self::VERT_FILE_ENDING));
+        files.push(base_path.with_extension(Self::FRAG_FILE_ENDING));
     }
 }
 
@@ -1,6 +1,6 @@
 use crate::{
-    FileHolder, Resource, SerializedData, SerializedDataError, YyResource, YyResourceHandler,
-    YypBoss,
+    CollisionMask, FileHolder, Resource, SerializedData, SerializedDataError, YyResource,
+    YyResourceHandler, YyResourceHandlerError, YypBoss,
 };
 use anyhow::Context;
 use anyhow::Result as AnyResult;
@@ -28,6 +28,12 @@ use yy_typings::ViewPath;
 
 pub type SpriteImageBuffer = ImageBuffer<Rgba<u8>, Vec<u8>>;
 
+/// The image of each layer of one frame of a sprite. A frame's composite image isn't stored, but
+/// is built from these with [`composite_image`] when the sprite is written.
+///
+/// [`composite_image`]: fn.composite_image.html
+pub type SpriteFrameLayers = HashMap<LayerId, SpriteImageBuffer>;
+
 pub trait SpriteExt: Sized {
     fn with(self, edit: impl Fn(&mut Self)) -> Self;
     fn new(name: &str, texture_group_id: TexturePath, parent: ViewPath) -> Self;
@@ -40,6 +46,23 @@ pub trait SpriteExt: Sized {
     fn parent(self, parent: ViewPath) -> Sprite;
     fn bbox_mode(self, f: impl Fn(i32, i32) -> BboxModeUtility) -> Self;
     fn collision_kind(self, collision_kind: CollisionKind) -> Self;
+
+    /// Builds the collision masks Gms2 would from the frames of the sprite, its collision kind,
+    /// its collision tolerance, and its bbox. Pixels outside of the bbox are never solid.
+    ///
+    /// [`CollisionKind::PrecisePerFrame`] gives a mask for each frame, in order. Every other kind
+    /// gives a single mask, which for [`CollisionKind::Precise`] holds the solid pixels of every
+    /// frame.
+    fn collision_masks(&self, frames: &HashMap<FrameId, SpriteFrameLayers>) -> Vec<CollisionMask>;
+
+    /// Sets the bbox to the smallest one holding every pixel, in every frame's composite image,
+    /// whose alpha is over `tolerance`. This is what Gms2's automatic mode does, so the bbox mode
+    /// is set to automatic, and the collision tolerance to `tolerance`. If no pixel is over the
+    /// tolerance, the bbox is left as it is.
+    ///
+    /// Builder version. If `frames` is missing any frame of the sprite, the sprite is left as it
+    /// is and a warning is logged.
+    fn automatic_bbox(self, frames: &HashMap<FrameId, SpriteFrameLayers>, tolerance: u8) -> Self;
     /// Clears all of the frames from the given image. Generally speaking,
     /// a sprite should have at least one frame when imported into GMS2, but this
     /// function will leave it entirely bare.
@@ -55,6 +78,37 @@ pub trait SpriteExt: Sized {
     /// function will leave it entirely bare.
     fn set_clear_all_frames(&mut self);
     fn set_frame(&mut self, frame_id: FrameId, sprite_sequence_id: SpriteSequenceId);
+
+    /// Sets the bbox to the smallest one holding every pixel, in every frame's composite image,
+    /// whose alpha is over `tolerance`. This is what Gms2's automatic mode does, so the bbox mode
+    /// is set to automatic, and the collision tolerance to `tolerance`. If no pixel is over the
+    /// tolerance, the bbox is left as it is.
+    ///
+    /// Returns `false`, leaving the sprite as it is, if `frames` is missing any frame of the
+    /// sprite.
+    fn set_automatic_bbox(
+        &mut self,
+        frames: &HashMap<FrameId, SpriteFrameLayers>,
+        tolerance: u8,
+    ) -> bool;
+
+    /// Adds a layer at `index` in `layers`, where `0` is the top layer. If `index` is past the
+    /// end, the layer is added at the bottom.
+    ///
+    /// Frames without an image for the new layer are written with a blank image for it.
+    fn add_layer(&mut self, layer: SpriteLayer, index: usize);
+
+    /// Removes a layer, returning it if it existed. Its images are no longer written, even if
+    /// they're still in the associated data.
+    fn remove_layer(&mut self, layer_id: LayerId) -> Option<SpriteLayer>;
+
+    /// Moves a layer to `index` in `layers`, where `0` is the top layer, and returns if the layer
+    /// existed. If `index` is past the end, the layer is moved to the bottom.
+    fn move_layer(&mut self, layer_id: LayerId, index: usize) -> bool;
+
+    /// Shows or hides a layer, and returns if the layer existed. Hidden layers are left out of
+    /// the composite image.
+    fn set_layer_visible(&mut self, layer_id: LayerId, visible: bool) -> bool;
 }
 
 impl SpriteExt for Sprite {
@@ -135,29 +189,54 @@ impl SpriteExt for Sprite {
         self
     }
 
+    fn automatic_bbox(
+        mut self,
+        frames: &HashMap<FrameId, SpriteFrameLayers>,
+        tolerance: u8,
+    ) -> Self {
+        if self.set_automatic_bbox(frames, tolerance) == false {
+            log::warn!(
+                "couldn't find every frame of {}, so its bbox wasn't set",
+                self.common_data.name
+            );
+        }
+        self
+    }
+
+    fn set_automatic_bbox(
+        &mut self,
+        frames: &HashMap<FrameId, SpriteFrameLayers>,
+        tolerance: u8,
+    ) -> bool {
+        let mut images = Vec::with_capacity(self.frames.len());
+        for frame in self.frames.iter() {
+            match frames.get(&frame.name) {
+                Some(frame_layers) => images.push(composite_image(self, frame_layers)),
+                None => return false,
+            }
+        }
+
+        self.bbox_mode = BBoxMode::Automatic;
+        self.collision_tolerance = tolerance;
+        if let Some(bbox) = Bbox::of_images(&images, tolerance) {
+            self.bbox_left = bbox.top_left.0;
+            self.bbox_top = bbox.top_left.1;
+            self.bbox_right = bbox.bottom_right.0;
+            self.bbox_bottom = bbox.bottom_right.1;
+        }
+
+        true
+    }
+
     fn set_frame(&mut self, frame_name: FrameId, sprite_sequence_id: SpriteSequenceId) {
-        let path_to_sprite = format!("sprites/{0}/{0}.yy", self.common_data.name);
-        let path_to_sprite = Path::new(&path_to_sprite);
         // Update the Frame
         self.frames.push(CommonData::new(frame_name));
 
         // Update the Sequence
+        let keyframe = new_keyframe(self, frame_name, sprite_sequence_id);
         let track: &mut Track = &mut self.sequence.tracks[0];
-        track.keyframes.keyframes.push(SpriteKeyframe {
-            id: sprite_sequence_id,
-            key: self.frames.len() as f32 - 1.0,
-            channels: Channels {
-                zero: SpriteZeroChannel {
-                    id: FilesystemPath {
-                        name: frame_name.inner().to_string(),
-                        path: path_to_sprite.to_owned(),
-                    },
-                    ..Default::default()
-                },
-            },
-            ..SpriteKeyframe::default()
-        });
-        self.sequence.length = self.frames.len() as f32;
+        track.keyframes.keyframes.push(keyframe);
+        update_keys(self);
     }
 
     /// Test
@@ -179,6 +258,10 @@ impl SpriteExt for Sprite {
             me.collision_kind = collision_kind;
         })
     }
+
+    fn collision_masks(&self, frames: &HashMap<FrameId, SpriteFrameLayers>) -> Vec<CollisionMask> {
+        super::collision_mask::collision_masks(self, frames)
+    }
     fn origin(self, origin: OriginUtility, locked: bool) -> Self {
         self.with(|me| {
             let w = me.width.get() as i32;
@@ -204,10 +287,133 @@ impl SpriteExt for Sprite {
             me.height = height;
         })
     }
+
+    fn add_layer(&mut self, layer: SpriteLayer, index: usize) {
+        let index = index.min(self.layers.len());
+        self.layers.insert(index, layer);
+    }
+
+    fn remove_layer(&mut self, layer_id: LayerId) -> Option<SpriteLayer> {
+        let position = self
+            .layers
+            .iter()
+            .position(|v| v.common_data.name == layer_id)?;
+
+        Some(self.layers.remove(position))
+    }
+
+    fn move_layer(&mut self, layer_id: LayerId, index: usize) -> bool {
+        match self.remove_layer(layer_id) {
+            Some(layer) => {
+                self.add_layer(layer, index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn set_layer_visible(&mut self, layer_id: LayerId, visible: bool) -> bool {
+        match self
+            .layers
+            .iter_mut()
+            .find(|v| v.common_data.name == layer_id)
+        {
+            Some(layer) => {
+                layer.visible = visible;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Builds the composite image of a frame from the images of its layers, as Gms2 does. Hidden
+/// layers are left out, and each layer's opacity is applied.
+///
+/// Only normal blending is supported: a layer with any other `blend_mode` is blended normally too,
+/// and a warning is logged. Where only one visible layer covers a pixel, that layer's pixel is
+/// copied unchanged, so a single layer sprite keeps its exact bytes.
+///
+/// The first of `sprite.layers` is the top layer, and the composite is the size of the sprite.
+pub fn composite_image(sprite: &Sprite, layers: &SpriteFrameLayers) -> SpriteImageBuffer {
+    let mut output = SpriteImageBuffer::new(sprite.width.get() as u32, sprite.height.get() as u32);
+
+    for layer in sprite.layers.iter().rev().filter(|v| v.visible) {
+        let image = match layers.get(&layer.common_data.name) {
+            Some(v) => v,
+            None => continue,
+        };
+        if layer.blend_mode != 0 {
+            log::warn!(
+                "layer {} of {} has blend mode {}, which we don't support, so it's blended normally",
+                layer.display_name,
+                sprite.common_data.name,
+                layer.blend_mode
+            );
+        }
+        let opacity = (layer.opacity / 100.0).clamp(0.0, 1.0) as f32;
+
+        for (x, y, source) in image.enumerate_pixels() {
+            if x < output.width() && y < output.height() {
+                let destination = output.get_pixel_mut(x, y);
+                *destination = blend(*source, *destination, opacity);
+            }
+        }
+    }
+
+    output
+}
+
+/// Builds the composite image of every frame in `frames`, keyed by frame, as
+/// [`composite_image`] does for one.
+///
+/// [`composite_image`]: fn.composite_image.html
+pub fn composite_frames(
+    sprite: &Sprite,
+    frames: &HashMap<FrameId, SpriteFrameLayers>,
+) -> HashMap<FrameId, SpriteImageBuffer> {
+    frames
+        .iter()
+        .map(|(frame_id, frame_layers)| (*frame_id, composite_image(sprite, frame_layers)))
+        .collect()
+}
+
+/// Blends `source` over `destination`, with straight alpha. If either one doesn't cover the pixel,
+/// the other is kept as it is.
+fn blend(source: Rgba<u8>, destination: Rgba<u8>, opacity: f32) -> Rgba<u8> {
+    let source_alpha = source[3] as f32 / 255.0 * opacity;
+    let destination_alpha = destination[3] as f32 / 255.0;
+    if destination_alpha <= 0.0 {
+        if opacity >= 1.0 {
+            return source;
+        }
+
+        let [r, g, b, a] = source.0;
+        return Rgba([r, g, b, (a as f32 * opacity).round() as u8]);
+    }
+    if source_alpha <= 0.0 {
+        return destination;
+    }
+
+    let alpha = source_alpha + destination_alpha * (1.0 - source_alpha);
+
+    let channel = |i: usize| {
+        let value = (source[i] as f32 * source_alpha
+            + destination[i] as f32 * destination_alpha * (1.0 - source_alpha))
+            / alpha;
+        value.round() as u8
+    };
+
+    Rgba([
+        channel(0),
+        channel(1),
+        channel(2),
+        (alpha * 255.0).round() as u8,
+    ])
 }
 
 impl YyResource for Sprite {
-    type AssociatedData = HashMap<FrameId, SpriteImageBuffer>;
+    type AssociatedData = HashMap<FrameId, SpriteFrameLayers>;
     const SUBPATH_NAME: &'static str = "sprites";
     const RESOURCE: Resource = Resource::Sprite;
 
@@ -250,80 +456,123 @@ impl YyResource for Sprite {
         directory_path: &Path,
         data: &Self::AssociatedData,
     ) -> AnyResult<()> {
+        if self.layers.is_empty() {
+            anyhow::bail!("All Sprites *must* have a single SpriteLayer!");
+        }
+
         let layers_path = directory_path.join("layers");
         if layers_path.exists() == false {
             std::fs::create_dir(&layers_path)?;
         }
 
-        for (frame_id, image) in data {
+        for (frame_id, frame_layers) in data {
             let inner_id_string = frame_id.inner().to_string();
-            let image: &ImageBuffer<_, _> = image;
 
             // Make the Core Image:
             let path = directory_path.join(&inner_id_string).with_extension("png");
-            image.save(&path).with_context(|| {
-                format!("We couldn't serialize the Core Image at path {:?}", path)
-            })?;
+            composite_image(self, frame_layers)
+                .save(&path)
+                .with_context(|| {
+                    format!("We couldn't serialize the Core Image at path {:?}", path)
+                })?;
 
-            // Make the folder and layer image:
+            // Make the folder, clearing out any layers we've since removed...
             let folder_path = layers_path.join(&inner_id_string);
-            if folder_path.exists() == false {
-                std::fs::create_dir(&folder_path)?;
+            if folder_path.exists() {
+                std::fs::remove_dir_all(&folder_path)?;
             }
+            std::fs::create_dir(&folder_path)?;
+
+            // ...and the layer images:
+            for layer in self.layers.iter() {
+                let image_layer_id = layer.common_data.name.inner().to_string();
+                let final_layer_path = folder_path.join(&image_layer_id).with_extension("png");
 
-            let image_layer_id = self
-                .layers
-                .first()
-                .ok_or_else(|| anyhow::anyhow!("All Sprites *must* have a single SpriteLayer!"))?
-                .common_data
-                .name
-                .inner()
-                .to_string();
-
-            let final_layer_path = folder_path.join(&image_layer_id).with_extension("png");
-            image
-                .save(&final_layer_path)
-                .with_context(|| format!("We couldn't save an Image to {:?}", final_layer_path))?;
+                let blank;
+                let image = match frame_layers.get(&layer.common_data.name) {
+                    Some(v) => v,
+                    None => {
+                        blank = SpriteImageBuffer::new(
+                            self.width.get() as u32,
+                            self.height.get() as u32,
+                        );
+                        &blank
+                    }
+                };
+
+                image.save(&final_layer_path).with_context(|| {
+                    format!("We couldn't save an Image to {:?}", final_layer_path)
+                })?;
+            }
         }
 
         Ok(())
     }
 
-    // WE DON'T HANDLE LAYERS AT ALL IN THIS CODE --
-    // WE WILL EVENTUALLY.
+    /// Reads in the image of every layer of every frame. A sprite with a single layer can be
+    /// read from its composite images alone, since they're the same image.
     fn deserialize_associated_data(
         &self,
         dir_path: &Path,
         _: &TrailingCommaUtility,
-    ) -> Result<HashMap<FrameId, SpriteImageBuffer>, SerializedDataError> {
+    ) -> Result<HashMap<FrameId, SpriteFrameLayers>, SerializedDataError> {
         let mut output = HashMap::new();
 
         for frame in self.frames.iter() {
-            let path_to_image = dir_path.join(&format!("{}.png", frame.name.inner()));
-
-            match image::open(&path_to_image) {
-                Ok(image) => output.insert(frame.name, image.to_rgba8()),
-                Err(e) => {
-                    return Err(SerializedDataError::BadData(format!(
-                        "we couldn't read {:#?} -- {}",
-                        path_to_image, e
-                    )));
+            let frame_name = frame.name.inner().to_string();
+            let mut frame_layers = HashMap::new();
+
+            for layer in self.layers.iter() {
+                let mut path_to_image = dir_path
+                    .join("layers")
+                    .join(&frame_name)
+                    .join(format!("{}.png", layer.common_data.name.inner()));
+                if path_to_image.exists() == false && self.layers.len() == 1 {
+                    path_to_image = dir_path.join(format!("{}.png", frame_name));
                 }
-            };
+
+                match image::open(&path_to_image) {
+                    Ok(image) => frame_layers.insert(layer.common_data.name, image.to_rgba8()),
+                    Err(e) => {
+                        return Err(SerializedDataError::BadData(format!(
+                            "we couldn't read {:#?} -- {}",
+                            path_to_image, e
+                        )));
+                    }
+                };
+            }
+
+            output.insert(frame.name, frame_layers);
         }
 
         Ok(output)
     }
 
+    /// Writes the image of every layer of every frame in the same layout as a sprite's own
+    /// directory, at `layers/<frame>/<layer>.png`. We don't have the sprite here to build
+    /// composite images, but a frame with a single layer also gets one at `<frame>.png`, since
+    /// it's the same image.
     fn serialize_associated_data_into_data(
         working_directory: &Path,
         associated_data: &Self::AssociatedData,
     ) -> Result<SerializedData, SerializedDataError> {
-        for (frame_id, img) in associated_data {
-            let path = working_directory.join(format!("{}.png", frame_id.inner()));
+        for (frame_id, frame_layers) in associated_data {
+            let folder_path = working_directory
+                .join("layers")
+                .join(frame_id.inner().to_string());
+            std::fs::create_dir_all(&folder_path)
+                .map_err(|e| SerializedDataError::BadData(e.to_string()))?;
+
+            for (layer_id, img) in frame_layers {
+                img.save(folder_path.join(format!("{}.png", layer_id.inner())))
+                    .map_err(SerializedDataError::CouldNotWriteImage)?;
+            }
 
-            img.save(&path)
-                .map_err(SerializedDataError::CouldNotWriteImage)?;
+            if let (Some(img), 1) = (frame_layers.values().next(), frame_layers.len()) {
+                let path = working_directory.join(format!("{}.png", frame_id.inner()));
+                img.save(&path)
+                    .map_err(SerializedDataError::CouldNotWriteImage)?;
+            }
         }
 
         Ok(SerializedData::Filepath {
@@ -343,14 +592,22 @@ impl YyResource for Sprite {
                 let output = self
                     .frames
                     .iter()
-                    .map(|name| {
-                        (
-                            name.name,
-                            SpriteImageBuffer::new(
-                                self.width.get() as u32,
-                                self.height.get() as u32,
-                            ),
-                        )
+                    .map(|frame| {
+                        let frame_layers = self
+                            .layers
+                            .iter()
+                            .map(|layer| {
+                                (
+                                    layer.common_data.name,
+                                    SpriteImageBuffer::new(
+                                        self.width.get() as u32,
+                                        self.height.get() as u32,
+                                    ),
+                                )
+                            })
+                            .collect();
+
+                        (frame.name, frame_layers)
                     })
                     .collect();
 
@@ -360,29 +617,240 @@ impl YyResource for Sprite {
     }
 
     fn cleanup_on_replace(&self, mut files: impl FileHolder) {
-        // first, clean up the layer folders...
-        let base_path = Path::new(&self.common_data.name);
-        let layers_path = base_path.join("layers");
+        // these paths are relative to the sprite's own directory
+        let layers_path = Path::new("layers");
 
-        // clean up the composite image...
         for frame in self.frames.iter() {
             let name = frame.name.inner().to_string();
             let path = Path::new(&name);
+
+            // first, clean up the layer folders...
             files.push(layers_path.join(path));
 
+            // then the composite image...
             let mut file = path.to_owned();
             file.set_extension("png");
-            files.push(base_path.join(file));
+            files.push(file);
         }
     }
 }
 
+impl YyResourceHandler<Sprite> {
+    /// Inserts a frame at `index` in the sprite's frames, with an image for each layer, and
+    /// returns its id. If `index` is past the end, the frame is added at the end.
+    pub(crate) fn insert_frame(
+        &mut self,
+        identifier: &str,
+        index: usize,
+        frame_layers: SpriteFrameLayers,
+    ) -> Result<FrameId, YyResourceHandlerError> {
+        let frame_id = FrameId::new();
+        self.edit_frames(identifier, |sprite, frames| {
+            insert_frame(sprite, frame_id, index, 1.0);
+            frames.insert(frame_id, frame_layers);
+
+            Ok(())
+        })?;
+
+        self.mark_for_serialization(identifier)?;
+        Ok(frame_id)
+    }
+
+    /// Removes a frame from the sprite, returning its images. Its image files are removed when
+    /// the sprite is serialized.
+    pub(crate) fn remove_frame(
+        &mut self,
+        identifier: &str,
+        frame_id: FrameId,
+    ) -> Result<SpriteFrameLayers, YyResourceHandlerError> {
+        let output = self.edit_frames(identifier, |sprite, frames| {
+            let index = frame_index(sprite, frame_id)?;
+            sprite.frames.remove(index);
+            sprite.sequence.tracks[0].keyframes.keyframes.remove(index);
+            update_keys(sprite);
+
+            Ok(frames.remove(&frame_id).unwrap_or_default())
+        })?;
+
+        let name = frame_id.inner().to_string();
+        self.mark_for_cleanup(
+            identifier,
+            &[
+                Path::new("layers").join(&name),
+                Path::new(&name).with_extension("png"),
+            ],
+        );
+
+        Ok(output)
+    }
+
+    /// Moves a frame to `index` in the sprite's frames. If `index` is past the end, the frame is
+    /// moved to the end.
+    pub(crate) fn move_frame(
+        &mut self,
+        identifier: &str,
+        frame_id: FrameId,
+        index: usize,
+    ) -> Result<(), YyResourceHandlerError> {
+        self.edit_frames(identifier, |sprite, _| {
+            let old_index = frame_index(sprite, frame_id)?;
+            let frame = sprite.frames.remove(old_index);
+            let keyframes = &mut sprite.sequence.tracks[0].keyframes.keyframes;
+            let keyframe = keyframes.remove(old_index);
+
+            let index = index.min(sprite.frames.len());
+            sprite.frames.insert(index, frame);
+            keyframes.insert(index, keyframe);
+            update_keys(sprite);
+
+            Ok(())
+        })?;
+
+        self.mark_for_serialization(identifier)
+    }
+
+    /// Copies a frame, and its images, into a new frame just after it, and returns the new
+    /// frame's id. The copy is shown for as long as the original.
+    pub(crate) fn duplicate_frame(
+        &mut self,
+        identifier: &str,
+        frame_id: FrameId,
+    ) -> Result<FrameId, YyResourceHandlerError> {
+        let new_frame_id = FrameId::new();
+        self.edit_frames(identifier, |sprite, frames| {
+            let index = frame_index(sprite, frame_id)?;
+            let length = sprite.sequence.tracks[0].keyframes.keyframes[index].length;
+            insert_frame(sprite, new_frame_id, index + 1, length);
+
+            let frame_layers = frames.get(&frame_id).cloned().unwrap_or_default();
+            frames.insert(new_frame_id, frame_layers);
+
+            Ok(())
+        })?;
+
+        self.mark_for_serialization(identifier)?;
+        Ok(new_frame_id)
+    }
+
+    /// Edits a sprite's frames along with its images, which must be loaded.
+    fn edit_frames<R>(
+        &mut self,
+        identifier: &str,
+        edit: impl FnOnce(
+            &mut Sprite,
+            &mut HashMap<FrameId, SpriteFrameLayers>,
+        ) -> Result<R, YyResourceHandlerError>,
+    ) -> Result<R, YyResourceHandlerError> {
+        // we keep the frames, the sequence, and the images in step, so nothing else is affected
+        let output =
+            unsafe { self.get_mut(identifier) }.ok_or(YyResourceHandlerError::ResourceNotFound)?;
+        let frames = output
+            .associated_data
+            .as_mut()
+            .ok_or(YyResourceHandlerError::CannotForceSerialization)?;
+
+        edit(&mut output.yy_resource, frames)
+    }
+}
+
+/// A keyframe showing the frame `frame_id`, for the sequence of `sprite`. Its key is set by
+/// [`update_keys`].
+///
+/// [`update_keys`]: fn.update_keys.html
+fn new_keyframe(
+    sprite: &Sprite,
+    frame_id: FrameId,
+    sprite_sequence_id: SpriteSequenceId,
+) -> SpriteKeyframe {
+    let path_to_sprite = format!("sprites/{0}/{0}.yy", sprite.common_data.name);
+
+    SpriteKeyframe {
+        id: sprite_sequence_id,
+        channels: Channels {
+            zero: SpriteZeroChannel {
+                id: FilesystemPath {
+                    name: frame_id.inner().to_string(),
+                    path: Path::new(&path_to_sprite).to_owned(),
+                },
+                ..Default::default()
+            },
+        },
+        ..SpriteKeyframe::default()
+    }
+}
+
+fn insert_frame(sprite: &mut Sprite, frame_id: FrameId, index: usize, length: f32) {
+    let index = index.min(sprite.frames.len());
+    let keyframe = SpriteKeyframe {
+        length,
+        ..new_keyframe(sprite, frame_id, SpriteSequenceId::new())
+    };
+
+    sprite.frames.insert(index, CommonData::new(frame_id));
+    sprite.sequence.tracks[0]
+        .keyframes
+        .keyframes
+        .insert(index, keyframe);
+    update_keys(sprite);
+}
+
+fn frame_index(sprite: &Sprite, frame_id: FrameId) -> Result<usize, YyResourceHandlerError> {
+    sprite
+        .frames
+        .iter()
+        .position(|v| v.name == frame_id)
+        .ok_or(YyResourceHandlerError::FrameNotFound)
+}
+
+/// Lays the keyframes of a sprite's sequence out one after another, and sets the length of the
+/// sequence to match.
+fn update_keys(sprite: &mut Sprite) {
+    let mut key = 0.0;
+    for keyframe in sprite.sequence.tracks[0].keyframes.keyframes.iter_mut() {
+        keyframe.key = key;
+        key += keyframe.length;
+    }
+
+    sprite.sequence.length = key;
+}
+
 #[derive(Debug, Default, Copy, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub struct Bbox {
     pub top_left: (i32, i32),
     pub bottom_right: (i32, i32),
 }
 
+impl Bbox {
+    /// The smallest bbox which holds every pixel, in any of `images`, whose alpha is over
+    /// `tolerance`. As in Gms2, `bottom_right` is the last pixel within the bbox, not one past it.
+    ///
+    /// Returns `None` if no pixel is over `tolerance`.
+    pub fn of_images<'a>(
+        images: impl IntoIterator<Item = &'a SpriteImageBuffer>,
+        tolerance: u8,
+    ) -> Option<Bbox> {
+        let mut output: Option<Bbox> = None;
+
+        for image in images {
+            for (x, y, pixel) in image.enumerate_pixels() {
+                if pixel[3] <= tolerance {
+                    continue;
+                }
+
+                let (x, y) = (x as i32, y as i32);
+                let bbox = output.get_or_insert(Bbox {
+                    top_left: (x, y),
+                    bottom_right: (x, y),
+                });
+                bbox.top_left = (bbox.top_left.0.min(x), bbox.top_left.1.min(y));
+                bbox.bottom_right = (bbox.bottom_right.0.max(x), bbox.bottom_right.1.max(y));
+            }
+        }
+
+        output
+    }
+}
+
 #[derive(
     Debug,
     Copy,
@@ -505,3 +973,198 @@ impl BboxModeUtility {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TempDirectory;
+
+    fn layer(display_name: &str) -> SpriteLayer {
+        SpriteLayer {
+            visible: true,
+            is_locked: false,
+            blend_mode: 0,
+            opacity: 100.0,
+            display_name: display_name.to_string(),
+            common_data: CommonData::new(LayerId::new()),
+        }
+    }
+
+    #[test]
+    fn layers_roundtrip() {
+        let size = NonZeroUsize::new(2).unwrap();
+        let mut sprite = Sprite::new("spr_layers", TexturePath::default(), ViewPath::default())
+            .dimensions(size, size);
+        let background = sprite.layers[0].common_data.name;
+        let foreground = layer("foreground");
+        let foreground_id = foreground.common_data.name;
+        sprite.add_layer(foreground, 0);
+        sprite.add_layer(layer("gone"), 10);
+        let gone = sprite.layers[2].common_data.name;
+        assert_eq!(sprite.remove_layer(gone).unwrap().display_name, "gone");
+        let frame = FrameId::new();
+        sprite.set_frame(frame, SpriteSequenceId::new());
+
+        let mut foreground_image = SpriteImageBuffer::new(2, 2);
+        foreground_image.put_pixel(0, 0, Rgba([255, 0, 0, 255]));
+        let frame_layers = maplit::hashmap! {
+            background => SpriteImageBuffer::from_pixel(2, 2, Rgba([0, 0, 255, 255])),
+            foreground_id => foreground_image,
+        };
+
+        let composite = composite_image(&sprite, &frame_layers);
+        assert_eq!(*composite.get_pixel(0, 0), Rgba([255, 0, 0, 255]));
+        assert_eq!(*composite.get_pixel(1, 1), Rgba([0, 0, 255, 255]));
+
+        assert!(sprite.move_layer(foreground_id, 1));
+        assert_eq!(
+            *composite_image(&sprite, &frame_layers).get_pixel(0, 0),
+            Rgba([0, 0, 255, 255])
+        );
+        assert!(sprite.move_layer(foreground_id, 0));
+        assert!(sprite.set_layer_visible(foreground_id, false));
+        assert_eq!(
+            *composite_image(&sprite, &frame_layers).get_pixel(0, 0),
+            Rgba([0, 0, 255, 255])
+        );
+        assert!(sprite.set_layer_visible(foreground_id, true));
+
+        // a pixel only one layer covers is copied, even where it's fully transparent
+        let mut transparent = SpriteImageBuffer::from_pixel(2, 2, Rgba([10, 20, 30, 0]));
+        transparent.put_pixel(1, 0, Rgba([10, 20, 30, 128]));
+        let single = maplit::hashmap! { background => transparent.clone() };
+        assert_eq!(composite_image(&sprite, &single), transparent);
+        let composites = composite_frames(&sprite, &maplit::hashmap! { frame => single });
+        assert_eq!(composites[&frame], transparent);
+
+        // every layer comes back as it was written
+        let directory = TempDirectory::new();
+        let data = maplit::hashmap! { frame => frame_layers };
+        sprite.serialize_associated_data(&directory, &data).unwrap();
+        assert!(directory.join(format!("{}.png", frame.inner())).exists());
+
+        let output = sprite
+            .deserialize_associated_data(&directory, &TrailingCommaUtility::new())
+            .unwrap();
+        assert_eq!(output, data);
+    }
+
+    #[test]
+    fn automatic_bbox() {
+        let size = NonZeroUsize::new(8).unwrap();
+        let mut sprite = Sprite::new("spr_bbox", TexturePath::default(), ViewPath::default())
+            .dimensions(size, size)
+            .bbox_mode(|_, _| BboxModeUtility::FullImage);
+        let layer = sprite.layers[0].common_data.name;
+        let (first, second) = (FrameId::new(), FrameId::new());
+        sprite.set_frame(first, SpriteSequenceId::new());
+        sprite.set_frame(second, SpriteSequenceId::new());
+
+        let mut first_image = SpriteImageBuffer::new(8, 8);
+        first_image.put_pixel(2, 3, Rgba([0, 0, 0, 255]));
+        first_image.put_pixel(0, 0, Rgba([0, 0, 0, 10]));
+        let mut second_image = SpriteImageBuffer::new(8, 8);
+        second_image.put_pixel(5, 6, Rgba([0, 0, 0, 128]));
+        let frames = maplit::hashmap! {
+            first => maplit::hashmap! { layer => first_image },
+            second => maplit::hashmap! { layer => second_image },
+        };
+
+        // the faint pixel in the corner is under the tolerance
+        let sprite = sprite.automatic_bbox(&frames, 10);
+        assert_eq!(sprite.bbox_mode, BBoxMode::Automatic);
+        assert_eq!(sprite.collision_tolerance, 10);
+        assert_eq!(
+            (
+                sprite.bbox_left,
+                sprite.bbox_top,
+                sprite.bbox_right,
+                sprite.bbox_bottom
+            ),
+            (2, 3, 5, 6)
+        );
+
+        let mut sprite = sprite.automatic_bbox(&frames, 0);
+        assert_eq!((sprite.bbox_left, sprite.bbox_top), (0, 0));
+        assert_eq!(Bbox::of_images(&[SpriteImageBuffer::new(1, 1)], 0), None);
+
+        // nothing over the tolerance leaves the bbox alone...
+        assert!(sprite.set_automatic_bbox(&frames, 255));
+        assert_eq!(sprite.collision_tolerance, 255);
+        assert_eq!((sprite.bbox_left, sprite.bbox_bottom), (0, 6));
+
+        // ...and a missing frame leaves the whole sprite alone
+        let mut missing = frames.clone();
+        missing.remove(&second);
+        assert!(sprite.set_automatic_bbox(&missing, 10) == false);
+        assert_eq!(sprite.collision_tolerance, 255);
+    }
+
+    #[test]
+    fn edit_frames() {
+        let root = TempDirectory::new();
+        let mut yyp_boss = root.yyp_boss();
+
+        let folder = yyp_boss
+            .new_folder_end(crate::Vfs::root_folder(), "Sprites")
+            .unwrap();
+        let mut sprite = Sprite::new("spr_frames", TexturePath::default(), folder);
+        let layer = sprite.layers[0].common_data.name;
+        let (first, second) = (FrameId::new(), FrameId::new());
+        sprite.set_frame(first, SpriteSequenceId::new());
+        sprite.set_frame(second, SpriteSequenceId::new());
+        let image = |value: u8| maplit::hashmap! { layer => SpriteImageBuffer::from_pixel(1, 1, Rgba([value, 0, 0, 255])) };
+        let frames = maplit::hashmap! { first => image(1), second => image(2) };
+        yyp_boss.add_resource(sprite, frames).unwrap();
+        yyp_boss.serialize().unwrap();
+
+        let removed = yyp_boss.remove_sprite_frame("spr_frames", first).unwrap();
+        assert_eq!(removed, image(1));
+        let copy = yyp_boss
+            .duplicate_sprite_frame("spr_frames", second)
+            .unwrap();
+        let inserted = yyp_boss
+            .insert_sprite_frame("spr_frames", 0, image(3))
+            .unwrap();
+        yyp_boss
+            .move_sprite_frame("spr_frames", inserted, 10)
+            .unwrap();
+        assert!(matches!(
+            yyp_boss.remove_sprite_frame("spr_frames", first),
+            Err(YyResourceHandlerError::FrameNotFound)
+        ));
+
+        // the frames, the keyframes, and the images all agree
+        let data = yyp_boss.sprites.get("spr_frames").unwrap();
+        let sprite = &data.yy_resource;
+        let order: Vec<FrameId> = sprite.frames.iter().map(|v| v.name).collect();
+        assert_eq!(order, [second, copy, inserted]);
+        let keyframes: Vec<(String, f32)> = sprite.sequence.tracks[0]
+            .keyframes
+            .keyframes
+            .iter()
+            .map(|v| (v.channels.zero.id.name.clone(), v.key))
+            .collect();
+        let expected: Vec<(String, f32)> = order
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (v.inner().to_string(), i as f32))
+            .collect();
+        assert_eq!(keyframes, expected);
+        assert_eq!(sprite.sequence.length, 3.0);
+        assert_eq!(data.associated_data.as_ref().unwrap()[&copy], image(2));
+
+        // and the removed frame's files are cleaned up
+        yyp_boss.serialize().unwrap();
+        let directory = root.join("sprites/spr_frames");
+        assert!(directory.join(format!("{}.png", first.inner())).exists() == false);
+        assert!(
+            directory
+                .join("layers")
+                .join(first.inner().to_string())
+                .exists()
+                == false
+        );
+        assert!(directory.join(format!("{}.png", copy.inner())).exists());
+    }
+}
@@ -0,0 +1,291 @@
+use crate::{
+    typings::{Timeline, TimelineMoment},
+    utils, FileHolder, FileSerializationError, Resource, SerializedData, SerializedDataError,
+    YyResource, YyResourceHandler, YypBoss,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+use yy_typings::{TrailingCommaUtility, ViewPath};
+
+impl YyResource for Timeline {
+    /// The gml of each moment, keyed by the moment's step.
+    type AssociatedData = HashMap<usize, String>;
+    const SUBPATH_NAME: &'static str = "timelines";
+    const RESOURCE: Resource = Resource::Timeline;
+
+    fn name(&self) -> &str {
+        &self.common_data.name
+    }
+
+    fn set_name(&mut self, name: String) {
+        self.common_data.name = name;
+    }
+
+    fn set_parent_view_path(&mut self, vp: ViewPath) {
+        self.parent = vp;
+    }
+
+    fn parent_view_path(&self) -> ViewPath {
+        self.parent.clone()
+    }
+
+    fn get_handler(yyp_boss: &YypBoss) -> &YyResourceHandler<Self> {
+        &yyp_boss.timelines
+    }
+
+    fn get_handler_mut(yyp_boss: &mut YypBoss) -> &mut YyResourceHandler<Self> {
+        &mut yyp_boss.timelines
+    }
+
+    fn serialize_associated_data(
+        &self,
+        directory_path: &Path,
+        data: &Self::AssociatedData,
+    ) -> anyhow::Result<()> {
+        let mut allowed_files = HashSet::with_capacity(self.moment_list.len());
+
+        for moment in self.moment_list.iter().map(|v| v.moment) {
+            let path = directory_path.join(Timeline::moment_filename(moment));
+            match data.get(&moment) {
+                Some(gml) => std::fs::write(&path, gml)?,
+                None => log::error!(
+                    "we couldn't find moment {} in our associated data, even though it should have been there. not serialized.",
+                    moment
+                ),
+            }
+
+            allowed_files.insert(path);
+        }
+
+        // clear out any moments which have been removed
+        let stale_files: Vec<PathBuf> = directory_path
+            .read_dir()?
+            .filter_map(|f| f.ok().map(|f| f.path()))
+            .filter(|path| {
+                let file_name = path
+                    .file_name()
+                    .and_then(|v| v.to_str())
+                    .unwrap_or_default();
+
+                parse_moment_filename(file_name).is_some() && allowed_files.contains(path) == false
+            })
+            .collect();
+
+        for stale_file in stale_files {
+            log::info!("removing stale moment {}", stale_file.display());
+            std::fs::remove_file(stale_file)?;
+        }
+
+        Ok(())
+    }
+
+    fn deserialize_associated_data(
+        &self,
+        directory_path: &Path,
+        _: &TrailingCommaUtility,
+    ) -> Result<Self::AssociatedData, SerializedDataError> {
+        let mut associated_data = HashMap::new();
+
+        for moment in self.moment_list.iter().map(|v| v.moment) {
+            let path = directory_path.join(Timeline::moment_filename(moment));
+            // like objects, gamemaker won't write out blank moments
+            let gml = if path.exists() == false {
+                String::new()
+            } else {
+                std::fs::read_to_string(&path).map_err(|e| {
+                    SerializedDataError::CouldNotDeserializeFile(FileSerializationError::Io(
+                        e.to_string(),
+                    ))
+                })?
+            };
+
+            associated_data.insert(moment, gml);
+        }
+
+        Ok(associated_data)
+    }
+
+    fn serialize_associated_data_into_data(
+        _: &Path,
+        associated_data: &Self::AssociatedData,
+    ) -> Result<SerializedData, SerializedDataError> {
+        match serde_json::to_string_pretty(associated_data) {
+            Ok(data) => Ok(SerializedData::Value { data }),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn deserialize_associated_data_from_data(
+        &self,
+        incoming_data: &SerializedData,
+        tcu: &TrailingCommaUtility,
+    ) -> Result<Self::AssociatedData, SerializedDataError> {
+        let mut moments: HashMap<usize, String> = match incoming_data {
+            SerializedData::Value { data } => serde_json::from_str(data)?,
+            SerializedData::Filepath { data } => {
+                if data.is_dir() {
+                    self.deserialize_associated_data(data, tcu)?
+                } else {
+                    utils::deserialize_json_tc(data, tcu)?
+                }
+            }
+            SerializedData::DefaultValue => self
+                .moment_list
+                .iter()
+                .map(|v| (v.moment, String::new()))
+                .collect(),
+        };
+
+        // sheer off any moments we don't have...
+        moments.retain(|key, _| self.moment_list.iter().any(|v| v.moment == *key));
+
+        // and make sure we have every moment we do have.
+        for moment in self.moment_list.iter().map(|v| v.moment) {
+            if moments.contains_key(&moment) == false {
+                return Err(SerializedDataError::BadData(format!(
+                    "missing moment {}",
+                    moment
+                )));
+            }
+        }
+
+        Ok(moments)
+    }
+
+    fn cleanup_on_replace(&self, mut files_to_delete: impl FileHolder) {
+        for moment in self.moment_list.iter() {
+            files_to_delete.push(Timeline::moment_filename(moment.moment).into());
+        }
+    }
+}
+
+impl YyResourceHandler<Timeline> {
+    /// Adds a blank moment at the given step. Returns `false` if there is no such timeline,
+    /// or if the timeline already has a moment at that step.
+    ///
+    /// The timeline's associated data must be loaded.
+    pub fn add_moment(&mut self, identifier: &str, moment: usize) -> bool {
+        let output = match unsafe { self.get_mut(identifier) } {
+            Some(v) => v,
+            None => return false,
+        };
+        let moments: &mut HashMap<usize, String> = output.associated_data.as_mut().unwrap();
+        let moment_list = &mut output.yy_resource.moment_list;
+
+        if moment_list.iter().any(|v| v.moment == moment) == false {
+            moments.insert(moment, String::new());
+
+            // keep the list ordered by step, like gamemaker does
+            let position = moment_list
+                .iter()
+                .position(|v| v.moment > moment)
+                .unwrap_or(moment_list.len());
+            moment_list.insert(position, TimelineMoment::new(moment));
+
+            // mark it an serialize...we know this is infallible
+            self.mark_for_serialization(identifier).unwrap();
+
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Removes the moment at the given step. Returns `false` if there is no such timeline,
+    /// or if the timeline has no moment at that step.
+    ///
+    /// The timeline's associated data must be loaded.
+    pub fn remove_moment(&mut self, identifier: &str, moment: usize) -> bool {
+        let output = match unsafe { self.get_mut(identifier) } {
+            Some(v) => v,
+            None => return false,
+        };
+
+        if let Some(v) = output
+            .yy_resource
+            .moment_list
+            .iter()
+            .position(|v| v.moment == moment)
+        {
+            output.yy_resource.moment_list.remove(v);
+            output.associated_data.as_mut().unwrap().remove(&moment);
+
+            // mark it an serialize...we know this is infallible
+            self.mark_for_serialization(identifier).unwrap();
+
+            true
+        } else {
+            false
+        }
+    }
+}
+
+fn parse_moment_filename(file_name: &str) -> Option<usize> {
+    file_name
+        .strip_prefix("moment_")?
+        .strip_suffix(".gml")?
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMELINE: &str = r#"{
+  "$GMTimeline":"",
+  "%Name":"tl_intro",
+  "momentList":[
+    {"$Moment":"","%Name":"","evnt":{"$GMEvent":"v1","%Name":"","collisionObjectId":null,"eventNum":0,"eventType":0,"isDnD":false,"name":"","resourceType":"GMEvent","resourceVersion":"2.0",},"moment":0,"name":"","resourceType":"Moment","resourceVersion":"2.0",},
+    {"$Moment":"","%Name":"","evnt":{"$GMEvent":"v1","%Name":"","collisionObjectId":null,"eventNum":0,"eventType":0,"isDnD":false,"name":"","resourceType":"GMEvent","resourceVersion":"2.0",},"moment":30,"name":"","resourceType":"Moment","resourceVersion":"2.0",},
+  ],
+  "name":"tl_intro",
+  "parent":{"name":"Timelines","path":"folders/Timelines.yy",},
+  "resourceType":"GMTimeline",
+  "resourceVersion":"2.0",
+}"#;
+
+    #[test]
+    fn moments() {
+        let tcu = TrailingCommaUtility::new();
+        let mut timeline: Timeline =
+            serde_json::from_str(&tcu.clear_trailing_comma(TIMELINE)).unwrap();
+        assert_eq!(parse_moment_filename("moment_30.gml"), Some(30));
+        assert_eq!(parse_moment_filename("moment_.gml"), None);
+
+        let output = yy_typings::serialize_file(&timeline);
+        let original: serde_json::Value =
+            serde_json::from_str(&tcu.clear_trailing_comma(TIMELINE)).unwrap();
+        let reserialized: serde_json::Value =
+            serde_json::from_str(&tcu.clear_trailing_comma(&output)).unwrap();
+        assert_eq!(original, reserialized);
+
+        let data: HashMap<usize, String> = timeline
+            .deserialize_associated_data_from_data(&SerializedData::DefaultValue, &tcu)
+            .unwrap();
+        assert_eq!(data.len(), 2);
+
+        let value = Timeline::serialize_associated_data_into_data(Path::new(""), &data).unwrap();
+        timeline.moment_list.pop();
+        let data = timeline
+            .deserialize_associated_data_from_data(&value, &tcu)
+            .unwrap();
+        assert_eq!(data.keys().collect::<Vec<_>>(), vec![&0]);
+
+        // a new moment is just what gamemaker writes
+        let written: Timeline = serde_json::from_str(&tcu.clear_trailing_comma(TIMELINE)).unwrap();
+        assert_eq!(TimelineMoment::new(30), written.moment_list[1]);
+        timeline.moment_list.push(TimelineMoment::new(30));
+        let output = yy_typings::serialize_file(&timeline);
+        let reserialized: serde_json::Value =
+            serde_json::from_str(&tcu.clear_trailing_comma(&output)).unwrap();
+        assert_eq!(original, reserialized);
+
+        // there's nothing to add a moment to without a timeline
+        let mut handler = YyResourceHandler::<Timeline>::new();
+        assert!(handler.add_moment("tl_missing", 0) == false);
+        assert!(handler.remove_moment("tl_missing", 0) == false);
+    }
+}
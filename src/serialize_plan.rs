@@ -0,0 +1,180 @@
+use crate::{ExternalResource, Resource};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use yy_typings::ViewPathLocation;
+
+/// What [`YypBoss::serialize`] would do if it was called now, from [`YypBoss::serialize_plan`].
+///
+/// Every path is relative to the root of the project.
+///
+/// [`YypBoss::serialize`]: ../struct.YypBoss.html#method.serialize
+/// [`YypBoss::serialize_plan`]: ../struct.YypBoss.html#method.serialize_plan
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializePlan {
+    /// The changes each kind of resource would make. Kinds of resources without any changes
+    /// aren't listed.
+    pub resources: Vec<ResourcePlan>,
+
+    /// The changes to the `datafiles` directory.
+    pub included_files: FileChanges,
+
+    /// The changes to the Yyp.
+    pub yyp: YypDiff,
+
+    /// Resources which changed on disk since we loaded or last wrote them, and which serializing
+    /// would write or remove. How these are handled depends on the [`ConflictResolution`].
+    ///
+    /// [`ConflictResolution`]: ./enum.ConflictResolution.html
+    pub conflicts: Vec<ExternalResource>,
+}
+
+impl SerializePlan {
+    /// Returns `true` if serializing wouldn't change anything on disk.
+    pub fn is_empty(&self) -> bool {
+        self.resources.is_empty() && self.included_files.is_empty() && self.yyp.changed == false
+    }
+}
+
+/// What [`YypBoss::serialize_with`] does with a resource which changed on disk since we loaded or
+/// last wrote it, if it would write or remove that resource.
+///
+/// [`YypBoss::serialize_with`]: ../struct.YypBoss.html#method.serialize_with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ConflictResolution {
+    /// Nothing is written, and serializing fails with a [`ConflictError`].
+    ///
+    /// [`ConflictError`]: ./struct.ConflictError.html
+    #[default]
+    Refuse,
+
+    /// Our changes are written over what's on disk.
+    Overwrite,
+
+    /// The resource is left alone on disk, and stays marked for serialization.
+    Skip,
+
+    /// Files which only changed on disk are kept, and files which only we changed are written.
+    /// The resource is then reloaded from disk. If a file changed on both sides, the resource is
+    /// skipped instead.
+    Merge,
+}
+
+/// What [`YypBoss::serialize_with`] did with resources which changed on disk.
+///
+/// [`YypBoss::serialize_with`]: ../struct.YypBoss.html#method.serialize_with
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializeReport {
+    pub overwritten: Vec<ExternalResource>,
+    pub skipped: Vec<ExternalResource>,
+    pub merged: Vec<ExternalResource>,
+}
+
+/// The changes one kind of resource would make.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourcePlan {
+    pub resource: Resource,
+
+    /// The resources which are marked to be written.
+    pub reserialized: Vec<String>,
+
+    /// The resources which are marked to be removed.
+    pub removed: Vec<String>,
+
+    /// The files which would be written or deleted.
+    pub files: FileChanges,
+}
+
+/// Files which would be written or deleted. A file is only written if it's new or its
+/// contents change.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileChanges {
+    pub written: Vec<PathBuf>,
+    pub deleted: Vec<PathBuf>,
+}
+
+impl FileChanges {
+    pub fn is_empty(&self) -> bool {
+        self.written.is_empty() && self.deleted.is_empty()
+    }
+
+    pub(crate) fn extend(&mut self, other: FileChanges) {
+        self.written.extend(other.written);
+        self.deleted.extend(other.deleted);
+    }
+}
+
+/// The changes to the listings of the Yyp.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct YypDiff {
+    /// Whether the Yyp would be written at all. This can be `true` even if every listing below is
+    /// empty, such as when a texture group changes.
+    pub changed: bool,
+
+    pub added_resources: Vec<String>,
+    pub removed_resources: Vec<String>,
+
+    pub added_folders: Vec<ViewPathLocation>,
+    pub removed_folders: Vec<ViewPathLocation>,
+
+    pub added_included_files: Vec<PathBuf>,
+    pub removed_included_files: Vec<PathBuf>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Resource, Vfs, YypBoss};
+    use std::path::{Path, PathBuf};
+    use yy_typings::{CommonData, Script};
+
+    #[test]
+    fn plan_drains_nothing() {
+        let mut yyp_boss = YypBoss::default();
+        let scripts = yyp_boss
+            .new_folder_end(Vfs::root_folder(), "Scripts")
+            .unwrap();
+        let script = Script {
+            common_data: CommonData::new("scr_plan".to_string()),
+            parent: scripts.clone(),
+            ..Script::default()
+        };
+        yyp_boss
+            .add_resource(script, "show_debug_message(0);".to_string())
+            .unwrap();
+        yyp_boss
+            .included_files
+            .add(Path::new("plan.txt"), vec![])
+            .unwrap();
+
+        let plan = yyp_boss.serialize_plan().unwrap();
+        assert_eq!(plan.resources.len(), 1);
+        assert_eq!(plan.resources[0].resource, Resource::Script);
+        assert_eq!(plan.resources[0].reserialized, vec!["scr_plan".to_string()]);
+        assert_eq!(
+            plan.resources[0].files.written,
+            vec![
+                PathBuf::from("scripts/scr_plan/scr_plan.gml"),
+                PathBuf::from("scripts/scr_plan/scr_plan.yy"),
+            ]
+        );
+        assert_eq!(
+            plan.included_files.written,
+            vec![PathBuf::from("datafiles/plan.txt")]
+        );
+        assert_eq!(plan.yyp.added_resources, vec!["scr_plan".to_string()]);
+        assert_eq!(plan.yyp.added_folders, vec![scripts.path]);
+        assert_eq!(
+            plan.yyp.added_included_files,
+            vec![PathBuf::from("datafiles/plan.txt")]
+        );
+        assert!(plan.yyp.changed);
+
+        // nothing was written or forgotten
+        assert!(Path::new("scripts").exists() == false);
+        assert_eq!(yyp_boss.serialize_plan().unwrap(), plan);
+    }
+}
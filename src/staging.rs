@@ -0,0 +1,557 @@
+use crate::FileChanges;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+};
+
+const PREFIX: &str = ".yy-boss-staging-";
+
+/// Stages every write and removal of a serialization in a directory next to the project, so that
+/// nothing in the project changes until we [`commit`].
+///
+/// Committing moves the staged files into place with renames, moving whatever they replace into
+/// a backup directory first. If any rename fails, everything which was moved is put back. Writes
+/// are committed in the order they were staged, and removals after every write, so the Yyp, which
+/// is staged last, never points at a resource which wasn't written.
+///
+/// Each step is written to a journal in the staging directory before we take it, and a marker is
+/// written once every step is done, so if we die partway through a commit, [`recover`] can put
+/// the project back the next time it's loaded.
+///
+/// [`commit`]: #method.commit
+/// [`recover`]: fn.recover.html
+#[derive(Debug)]
+pub(crate) struct Staging {
+    /// The directory we stage within, which sits inside the project so that renames never cross
+    /// a filesystem.
+    directory: PathBuf,
+    pending: Vec<(PathBuf, Pending)>,
+    positions: HashMap<PathBuf, usize>,
+    staged_count: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Pending {
+    /// The path will be replaced by the staged file or directory.
+    Write(PathBuf),
+    Remove,
+}
+
+/// Something we did to the project while committing, which we undo if a later step fails.
+#[derive(Debug, Serialize, Deserialize)]
+enum Committed {
+    Displaced { path: PathBuf, backup: PathBuf },
+    Placed { path: PathBuf },
+    CreatedDirectory { path: PathBuf },
+}
+
+impl Staging {
+    /// Creates a new staging directory within `directory`. Renames can't cross a filesystem, so
+    /// to commit, this should be the root directory of the project.
+    pub fn new(directory: &Path) -> io::Result<Self> {
+        let directory = directory.join(format!("{}{}", PREFIX, uuid::Uuid::new_v4()));
+        fs::create_dir_all(directory.join("staged"))?;
+        fs::create_dir_all(directory.join("backup"))?;
+
+        Ok(Self {
+            directory,
+            pending: Vec::new(),
+            positions: HashMap::new(),
+            staged_count: 0,
+        })
+    }
+
+    /// Returns a staged copy of the directory at `path`, which will replace it when we commit.
+    /// The copy holds whatever the directory would hold if we committed now.
+    pub fn directory(&mut self, path: &Path) -> io::Result<PathBuf> {
+        if let Some(Pending::Write(staged)) = self.get(path) {
+            return Ok(staged.clone());
+        }
+
+        let staged = self.next_staged_path();
+        fs::create_dir_all(&staged)?;
+        if let Some(current) = self.current(path) {
+            if current.is_dir() {
+                copy_directory(&current, &staged)?;
+            }
+        }
+
+        self.set(path, Pending::Write(staged.clone()));
+        Ok(staged)
+    }
+
+    /// Returns a path to write a file to, which will replace the file at `path` when we commit.
+    pub fn file(&mut self, path: &Path) -> io::Result<PathBuf> {
+        if let Some(Pending::Write(staged)) = self.get(path) {
+            return Ok(staged.clone());
+        }
+
+        let staged = self.next_staged_path();
+        self.set(path, Pending::Write(staged.clone()));
+        Ok(staged)
+    }
+
+    /// Removes the file or directory at `path` when we commit.
+    pub fn remove(&mut self, path: &Path) {
+        self.set(path, Pending::Remove);
+    }
+
+    /// Forgets whatever was staged for `path`, so committing leaves it as it is.
+    pub fn unstage(&mut self, path: &Path) -> io::Result<()> {
+        let index = match self.positions.remove(path) {
+            Some(v) => v,
+            None => return Ok(()),
+        };
+
+        if let (_, Pending::Write(staged)) = self.pending.remove(index) {
+            if staged.is_dir() {
+                fs::remove_dir_all(staged)?;
+            } else if staged.exists() {
+                fs::remove_file(staged)?;
+            }
+        }
+
+        for position in self.positions.values_mut() {
+            if *position > index {
+                *position -= 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Moves the file at `from` to `to` when we commit.
+    pub fn rename(&mut self, from: &Path, to: &Path) -> io::Result<()> {
+        let current = self.current(from).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{:?} doesn't exist, so we can't move it", from),
+            )
+        })?;
+
+        let staged = self.file(to)?;
+        fs::copy(current, staged)?;
+        self.remove(from);
+
+        Ok(())
+    }
+
+    /// Moves everything into place. If anything fails, the project is put back as it was.
+    ///
+    /// The staging directory is removed either way.
+    pub fn commit(self) -> io::Result<()> {
+        let mut committed = Vec::new();
+        let output = self.commit_inner(&mut committed);
+
+        if let Err(e) = &output {
+            log::error!("couldn't commit serialization, rolling back...{}", e);
+            for step in committed.into_iter().rev() {
+                if let Err(e) = rollback(&step) {
+                    log::error!("couldn't roll back {:?}...{}", step, e);
+                }
+            }
+        } else if let Err(e) =
+            fs::File::create(self.directory.join("committed")).and_then(|v| v.sync_all())
+        {
+            log::error!("couldn't mark the serialization as committed...{}", e);
+        }
+
+        self.discard();
+
+        output
+    }
+
+    /// Removes the staging directory without committing anything.
+    pub fn discard(self) {
+        if let Err(e) = fs::remove_dir_all(&self.directory) {
+            log::error!(
+                "couldn't remove the staging directory {:?}...{}",
+                self.directory,
+                e
+            );
+        }
+    }
+
+    fn commit_inner(&self, committed: &mut Vec<Committed>) -> io::Result<()> {
+        let mut journal = Journal::create(&self.directory.join("journal"))?;
+
+        let writes = self
+            .pending
+            .iter()
+            .filter_map(|(path, pending)| match pending {
+                Pending::Write(staged) => Some((path, staged)),
+                Pending::Remove => None,
+            });
+        for (path, staged) in writes {
+            self.displace(path, &mut journal, committed)?;
+
+            create_parent(path, &mut journal, committed)?;
+            journal.take(Committed::Placed { path: path.clone() }, committed, || {
+                fs::rename(staged, path)
+            })?;
+        }
+
+        let removals = self
+            .pending
+            .iter()
+            .filter(|(_, pending)| *pending == Pending::Remove);
+        for (path, _) in removals {
+            self.displace(path, &mut journal, committed)?;
+        }
+
+        Ok(())
+    }
+
+    /// Moves whatever is at `path` into the backup directory.
+    fn displace(
+        &self,
+        path: &Path,
+        journal: &mut Journal,
+        committed: &mut Vec<Committed>,
+    ) -> io::Result<()> {
+        if path.exists() {
+            let backup = self
+                .directory
+                .join("backup")
+                .join(committed.len().to_string());
+
+            let step = Committed::Displaced {
+                path: path.to_owned(),
+                backup: backup.clone(),
+            };
+            journal.take(step, committed, || fs::rename(path, backup))?;
+        }
+
+        Ok(())
+    }
+
+    /// Lists the files at or within `path` which committing would write or delete, relative to
+    /// `root_directory`, without committing anything.
+    pub fn changes_at(&self, path: &Path, root_directory: &Path) -> io::Result<FileChanges> {
+        let mut output = FileChanges::default();
+
+        let mut new_files = HashMap::new();
+        if let Some(Pending::Write(staged)) = self.get(path) {
+            list_files(staged, Path::new(""), &mut new_files)?;
+        }
+        let mut old_files = HashMap::new();
+        if self.get(path).is_some() {
+            list_files(path, Path::new(""), &mut old_files)?;
+        }
+
+        for (relative, staged) in new_files.iter() {
+            let written = match old_files.get(relative) {
+                Some(old) => fs::read(old)? != fs::read(staged)?,
+                None => true,
+            };
+
+            if written {
+                output
+                    .written
+                    .push(join_relative(path, relative, root_directory));
+            }
+        }
+
+        for relative in old_files.keys() {
+            if new_files.contains_key(relative) == false {
+                output
+                    .deleted
+                    .push(join_relative(path, relative, root_directory));
+            }
+        }
+
+        output.written.sort();
+        output.deleted.sort();
+
+        Ok(output)
+    }
+
+    /// Where the contents of `path` would be if we committed now.
+    fn current(&self, path: &Path) -> Option<PathBuf> {
+        match self.get(path) {
+            Some(Pending::Write(staged)) => Some(staged.clone()),
+            Some(Pending::Remove) => None,
+            None => Some(path.to_owned()).filter(|v| v.exists()),
+        }
+    }
+
+    fn get(&self, path: &Path) -> Option<&Pending> {
+        self.positions.get(path).map(|&i| &self.pending[i].1)
+    }
+
+    fn set(&mut self, path: &Path, pending: Pending) {
+        match self.positions.get(path) {
+            Some(&i) => self.pending[i].1 = pending,
+            None => {
+                self.positions.insert(path.to_owned(), self.pending.len());
+                self.pending.push((path.to_owned(), pending));
+            }
+        }
+    }
+
+    fn next_staged_path(&mut self) -> PathBuf {
+        self.staged_count += 1;
+        self.directory
+            .join("staged")
+            .join(self.staged_count.to_string())
+    }
+}
+
+/// Creates the parent directory of `path`, remembering the outermost directory we created.
+fn create_parent(
+    path: &Path,
+    journal: &mut Journal,
+    committed: &mut Vec<Committed>,
+) -> io::Result<()> {
+    let parent = path.parent().unwrap();
+    let outermost = parent
+        .ancestors()
+        .take_while(|v| v.exists() == false)
+        .last()
+        .map(|v| v.to_owned());
+
+    if let Some(outermost) = outermost {
+        let step = Committed::CreatedDirectory { path: outermost };
+        journal.take(step, committed, || fs::create_dir_all(parent))?;
+    }
+
+    Ok(())
+}
+
+/// The steps of a commit, written before we take each one.
+struct Journal(fs::File);
+
+impl Journal {
+    fn create(path: &Path) -> io::Result<Self> {
+        fs::File::create(path).map(Self)
+    }
+
+    /// Writes `step` to the journal, then takes it with `action`, and remembers it in
+    /// `committed` if it worked.
+    fn take(
+        &mut self,
+        step: Committed,
+        committed: &mut Vec<Committed>,
+        action: impl FnOnce() -> io::Result<()>,
+    ) -> io::Result<()> {
+        let line = serde_json::to_string(&step).map_err(io::Error::from)?;
+        writeln!(self.0, "{}", line)?;
+        self.0.sync_data()?;
+
+        action()?;
+        committed.push(step);
+
+        Ok(())
+    }
+}
+
+/// Cleans up every staging directory left in `root_directory` by a commit we never finished. A
+/// commit which got to its marker is left as it is, and any other is rolled back with its journal,
+/// so the project is either entirely as it was or entirely as it was committed.
+pub(crate) fn recover(root_directory: &Path) -> io::Result<()> {
+    let root_directory = if root_directory.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        root_directory
+    };
+    if root_directory.is_dir() == false {
+        return Ok(());
+    }
+
+    for entry in fs::read_dir(root_directory)? {
+        let directory = entry?.path();
+        let is_staging = directory
+            .file_name()
+            .and_then(|v| v.to_str())
+            .is_some_and(|v| v.starts_with(PREFIX));
+        if is_staging == false || directory.is_dir() == false {
+            continue;
+        }
+
+        let journal = directory.join("journal");
+        if directory.join("committed").exists() == false && journal.exists() {
+            log::warn!(
+                "found an interrupted serialization in {:?}, rolling back...",
+                directory
+            );
+
+            // the last line may have been cut off if we died writing it, in which case we never
+            // took that step
+            let mut steps = Vec::new();
+            for line in io::BufReader::new(fs::File::open(&journal)?).lines() {
+                match serde_json::from_str::<Committed>(&line?) {
+                    Ok(step) => steps.push(step),
+                    Err(_) => break,
+                }
+            }
+
+            for step in steps.into_iter().rev() {
+                rollback(&step)?;
+            }
+        }
+
+        fs::remove_dir_all(&directory)?;
+    }
+
+    Ok(())
+}
+
+/// Undoes `step`. Since a step is journaled before it's taken, this does nothing for any part of
+/// it which never happened.
+fn rollback(step: &Committed) -> io::Result<()> {
+    match step {
+        Committed::Displaced { path, backup } => {
+            if backup.exists() {
+                fs::rename(backup, path)?;
+            }
+        }
+        Committed::CreatedDirectory { path } => {
+            if path.exists() {
+                fs::remove_dir_all(path)?;
+            }
+        }
+        Committed::Placed { path } => {
+            if path.is_dir() {
+                fs::remove_dir_all(path)?;
+            } else if path.exists() {
+                fs::remove_file(path)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Lists every file at or within `path`, keyed by their path relative to `path`.
+fn list_files(
+    path: &Path,
+    relative: &Path,
+    output: &mut HashMap<PathBuf, PathBuf>,
+) -> io::Result<()> {
+    if path.is_dir() {
+        for entry in fs::read_dir(path)? {
+            let entry = entry?;
+            list_files(&entry.path(), &relative.join(entry.file_name()), output)?;
+        }
+    } else if path.exists() {
+        output.insert(relative.to_owned(), path.to_owned());
+    }
+
+    Ok(())
+}
+
+fn join_relative(path: &Path, relative: &Path, root_directory: &Path) -> PathBuf {
+    let path = if relative.as_os_str().is_empty() {
+        path.to_owned()
+    } else {
+        path.join(relative)
+    };
+
+    path.strip_prefix(root_directory)
+        .map(|v| v.to_owned())
+        .unwrap_or(path)
+}
+
+fn copy_directory(from: &Path, to: &Path) -> io::Result<()> {
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let destination = to.join(entry.file_name());
+
+        if entry.file_type()?.is_dir() {
+            fs::create_dir_all(&destination)?;
+            copy_directory(&entry.path(), &destination)?;
+        } else {
+            fs::copy(entry.path(), destination)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TempDirectory;
+
+    #[test]
+    fn commit_and_rollback() {
+        let root = TempDirectory::new();
+        fs::create_dir_all(root.join("scripts/scr_a")).unwrap();
+        fs::write(root.join("scripts/scr_a/scr_a.gml"), "old").unwrap();
+        fs::write(root.join("project.yyp"), "old").unwrap();
+
+        // the staged copy sees the old files, but the project doesn't change yet
+        let mut staging = Staging::new(&root).unwrap();
+        let scr_a = staging.directory(&root.join("scripts/scr_a")).unwrap();
+        assert_eq!(fs::read_to_string(scr_a.join("scr_a.gml")).unwrap(), "old");
+        fs::write(scr_a.join("scr_a.gml"), "new").unwrap();
+        staging.remove(&root.join("scripts/scr_gone"));
+        fs::write(staging.file(&root.join("project.yyp")).unwrap(), "new").unwrap();
+        assert_eq!(
+            fs::read_to_string(root.join("scripts/scr_a/scr_a.gml")).unwrap(),
+            "old"
+        );
+
+        staging.commit().unwrap();
+        assert_eq!(
+            fs::read_to_string(root.join("scripts/scr_a/scr_a.gml")).unwrap(),
+            "new"
+        );
+        assert_eq!(fs::read_to_string(root.join("project.yyp")).unwrap(), "new");
+
+        // a write with nothing staged for it fails, which puts everything back
+        let mut staging = Staging::new(&root).unwrap();
+        fs::write(staging.file(&root.join("project.yyp")).unwrap(), "newer").unwrap();
+        staging.file(&root.join("datafiles/missing.txt")).unwrap();
+        staging.remove(&root.join("scripts/scr_a"));
+        assert!(staging.commit().is_err());
+
+        assert_eq!(fs::read_to_string(root.join("project.yyp")).unwrap(), "new");
+        assert!(root.join("scripts/scr_a/scr_a.gml").exists());
+        assert_eq!(fs::read_dir(&root).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn recover_interrupted() {
+        let root = TempDirectory::new();
+        fs::create_dir_all(root.join("scripts/scr_a")).unwrap();
+        fs::write(root.join("scripts/scr_a/scr_a.gml"), "old").unwrap();
+        fs::write(root.join("project.yyp"), "old").unwrap();
+
+        // a finished commit is left alone
+        let mut staging = Staging::new(&root).unwrap();
+        fs::write(staging.file(&root.join("project.yyp")).unwrap(), "new").unwrap();
+        staging.commit_inner(&mut Vec::new()).unwrap();
+        fs::File::create(staging.directory.join("committed")).unwrap();
+
+        recover(&root).unwrap();
+        assert_eq!(fs::read_to_string(root.join("project.yyp")).unwrap(), "new");
+        assert!(staging.directory.exists() == false);
+
+        // we die partway through, without rolling back, and halfway through a journal line
+        let mut staging = Staging::new(&root).unwrap();
+        fs::write(staging.file(&root.join("project.yyp")).unwrap(), "newer").unwrap();
+        staging.file(&root.join("datafiles/missing.txt")).unwrap();
+        staging.remove(&root.join("scripts/scr_a"));
+        assert!(staging.commit_inner(&mut Vec::new()).is_err());
+        assert_eq!(
+            fs::read_to_string(root.join("project.yyp")).unwrap(),
+            "newer"
+        );
+        assert!(root.join("datafiles").exists());
+
+        let mut journal = fs::OpenOptions::new()
+            .append(true)
+            .open(staging.directory.join("journal"))
+            .unwrap();
+        write!(journal, "{{\"Displaced\":{{\"pa").unwrap();
+
+        recover(&root).unwrap();
+        assert_eq!(fs::read_to_string(root.join("project.yyp")).unwrap(), "new");
+        assert!(root.join("scripts/scr_a/scr_a.gml").exists());
+        assert_eq!(fs::read_dir(&root).unwrap().count(), 2);
+    }
+}
@@ -0,0 +1,54 @@
+use crate::{directory_manager::DirectoryManager, YypBoss};
+use std::{
+    fs,
+    ops::Deref,
+    path::{Path, PathBuf},
+};
+
+/// A fresh directory in the system's temporary directory, which is removed when it's dropped, so
+/// a test which fails partway through doesn't leave it behind.
+#[derive(Debug)]
+pub(crate) struct TempDirectory(PathBuf);
+
+impl TempDirectory {
+    pub fn new() -> Self {
+        let path = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        fs::create_dir_all(&path).unwrap();
+
+        Self(path)
+    }
+
+    /// A `DirectoryManager` for a `project.yyp` at the root of this directory.
+    pub fn directory_manager(&self) -> DirectoryManager {
+        DirectoryManager::new(&self.0.join("project.yyp")).unwrap()
+    }
+
+    /// An empty project, which serializes to a `project.yyp` at the root of this directory.
+    pub fn yyp_boss(&self) -> YypBoss {
+        let mut yyp_boss = YypBoss::default();
+        yyp_boss.directory_manager = self.directory_manager();
+        yyp_boss
+    }
+}
+
+impl Deref for TempDirectory {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TempDirectory {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDirectory {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_dir_all(&self.0) {
+            log::error!("couldn't remove the test directory {:?}...{}", self.0, e);
+        }
+    }
+}
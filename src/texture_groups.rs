@@ -0,0 +1,406 @@
+use crate::{GroupError, YyResourceHandler};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use yy_typings::{CommonData, Sprite, TextureGroup, TexturePath, TileSet};
+
+/// The name of the texture group which every project has, and which new sprites use.
+pub const DEFAULT_TEXTURE_GROUP: &str = "Default";
+
+/// Manages the Texture Groups of a project.
+///
+/// Changes are only written into the Yyp when the YypBoss is serialized. Renaming or removing
+/// a group also edits every Sprite and TileSet which used it.
+#[derive(Debug, PartialEq, Eq, Default, Clone)]
+pub struct TextureGroupManager {
+    texture_groups: Vec<TextureGroup>,
+}
+
+/// Options to change on a Texture Group. Any option which is `None` is left alone.
+#[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextureGroupOptions {
+    pub autocrop: Option<bool>,
+    pub border: Option<usize>,
+    pub compress_format: Option<String>,
+    pub custom_options: Option<String>,
+    pub directory: Option<String>,
+    pub is_scaled: Option<bool>,
+    pub load_type: Option<String>,
+    pub mips_to_generate: Option<i8>,
+
+    /// The platforms which this group is built for, as a bitmask. `-1` is every platform.
+    pub targets: Option<isize>,
+
+    /// Overrides of the options per config, keyed by config name. Each value is a map of
+    /// option names to their value in that config.
+    pub config_values: Option<BTreeMap<String, serde_json::Value>>,
+}
+
+impl TextureGroupManager {
+    pub(crate) fn new(texture_groups: &[TextureGroup]) -> Self {
+        Self {
+            texture_groups: texture_groups.to_vec(),
+        }
+    }
+
+    /// Returns every texture group in the project.
+    pub fn texture_groups(&self) -> &[TextureGroup] {
+        &self.texture_groups
+    }
+
+    /// Gets a texture group by name.
+    pub fn get(&self, name: &str) -> Option<&TextureGroup> {
+        self.texture_groups
+            .iter()
+            .find(|v| v.common_data.name == name)
+    }
+
+    /// Gets the path to a texture group, which sprites and tilesets use to refer to it.
+    pub fn texture_path(&self, name: &str) -> Option<TexturePath> {
+        self.get(name).map(|v| v.into())
+    }
+
+    /// Gets the default texture path, if it exists. The "Default" group simply
+    /// has the name `"Default"`.
+    pub fn default_texture_path(&self) -> Option<TexturePath> {
+        self.texture_path(DEFAULT_TEXTURE_GROUP)
+    }
+
+    /// Creates a new texture group, returning its path.
+    pub fn create(&mut self, name: &str, parent: Option<&str>) -> Result<TexturePath, GroupError> {
+        self.can_use_name(name)?;
+        let group_parent = parent
+            .map(|v| self.texture_path(v).ok_or(GroupError::NotFound))
+            .transpose()?;
+
+        let texture_group = TextureGroup {
+            common_data: CommonData::new(name.to_owned()),
+            compress_format: "bz2".to_string(),
+            group_parent,
+            load_type: "default".to_string(),
+            ..TextureGroup::default()
+        };
+        let output = (&texture_group).into();
+        self.texture_groups.push(texture_group);
+
+        Ok(output)
+    }
+
+    /// Sets the parent of a texture group. Passing `None` removes the parent.
+    pub fn set_parent(&mut self, name: &str, parent: Option<&str>) -> Result<(), GroupError> {
+        let position = self.position(name)?;
+
+        let group_parent = match parent {
+            Some(parent) => {
+                // walk up from the new parent to make sure we don't make a loop
+                let mut current = Some(parent);
+                while let Some(ancestor) = current {
+                    if ancestor == name {
+                        return Err(GroupError::ParentCycle);
+                    }
+
+                    current = self
+                        .get(ancestor)
+                        .ok_or(GroupError::NotFound)?
+                        .group_parent
+                        .as_ref()
+                        .map(|v| v.name.as_str());
+                }
+
+                self.texture_path(parent)
+            }
+            None => None,
+        };
+
+        self.texture_groups[position].group_parent = group_parent;
+
+        Ok(())
+    }
+
+    /// Sets the options of a texture group.
+    pub fn set_options(
+        &mut self,
+        name: &str,
+        options: TextureGroupOptions,
+    ) -> Result<(), GroupError> {
+        let position = self.position(name)?;
+        let texture_group = &mut self.texture_groups[position];
+
+        let TextureGroupOptions {
+            autocrop,
+            border,
+            compress_format,
+            custom_options,
+            directory,
+            is_scaled,
+            load_type,
+            mips_to_generate,
+            targets,
+            config_values,
+        } = options;
+
+        if let Some(autocrop) = autocrop {
+            texture_group.autocrop = autocrop;
+        }
+        if let Some(border) = border {
+            texture_group.border = border;
+        }
+        if let Some(compress_format) = compress_format {
+            texture_group.compress_format = compress_format;
+        }
+        if let Some(custom_options) = custom_options {
+            texture_group.custom_options = custom_options;
+        }
+        if let Some(directory) = directory {
+            texture_group.directory = directory;
+        }
+        if let Some(is_scaled) = is_scaled {
+            texture_group.is_scaled = is_scaled;
+        }
+        if let Some(load_type) = load_type {
+            texture_group.load_type = load_type;
+        }
+        if let Some(mips_to_generate) = mips_to_generate {
+            texture_group.mips_to_generate = mips_to_generate;
+        }
+        if let Some(targets) = targets {
+            texture_group.targets = targets;
+        }
+        if let Some(config_values) = config_values {
+            texture_group.config_values = Some(config_values);
+        }
+
+        Ok(())
+    }
+
+    /// Renames a texture group. Every sprite, tileset and child group which used the
+    /// old name is moved to the new name.
+    pub fn rename(
+        &mut self,
+        name: &str,
+        new_name: &str,
+        sprites: &mut YyResourceHandler<Sprite>,
+        tilesets: &mut YyResourceHandler<TileSet>,
+    ) -> Result<(), GroupError> {
+        if name == DEFAULT_TEXTURE_GROUP {
+            return Err(GroupError::DefaultGroup);
+        }
+        let position = self.position(name)?;
+        self.can_use_name(new_name)?;
+
+        self.texture_groups[position].common_data.name = new_name.to_owned();
+        let new_path = self.texture_path(new_name);
+
+        self.reassign(name, new_path, sprites, tilesets)
+    }
+
+    /// Removes a texture group. Every sprite, tileset and child group which used it
+    /// is moved to the removed group's parent, or to the Default group if it had no parent.
+    ///
+    /// If the group has no parent and the project has no Default group, nothing is changed and
+    /// [`GroupError::NoDefaultGroup`] is returned, since its assets would have nowhere to go.
+    ///
+    /// [`GroupError::NoDefaultGroup`]: ./enum.GroupError.html#variant.NoDefaultGroup
+    pub fn remove(
+        &mut self,
+        name: &str,
+        sprites: &mut YyResourceHandler<Sprite>,
+        tilesets: &mut YyResourceHandler<TileSet>,
+    ) -> Result<TextureGroup, GroupError> {
+        if name == DEFAULT_TEXTURE_GROUP {
+            return Err(GroupError::DefaultGroup);
+        }
+        let position = self.position(name)?;
+
+        // assets go to the removed group's parent, or to the default group
+        let replacement = self.texture_groups[position].group_parent.clone();
+        let asset_replacement = replacement
+            .clone()
+            .or_else(|| self.default_texture_path())
+            .ok_or(GroupError::NoDefaultGroup)?;
+
+        let texture_group = self.texture_groups.remove(position);
+
+        // children keep the removed group's parent...
+        for child in self
+            .texture_groups
+            .iter_mut()
+            .filter(|v| v.group_parent.as_ref().is_some_and(|v| v.name == name))
+        {
+            child.group_parent = replacement.clone();
+        }
+
+        self.reassign_assets(name, asset_replacement, sprites, tilesets)?;
+
+        Ok(texture_group)
+    }
+
+    /// Writes the texture groups into the Yyp's listing.
+    pub(crate) fn serialize(&self, texture_groups: &mut Vec<TextureGroup>) {
+        *texture_groups = self.texture_groups.clone();
+    }
+
+    fn reassign(
+        &mut self,
+        name: &str,
+        new_path: Option<TexturePath>,
+        sprites: &mut YyResourceHandler<Sprite>,
+        tilesets: &mut YyResourceHandler<TileSet>,
+    ) -> Result<(), GroupError> {
+        for child in self
+            .texture_groups
+            .iter_mut()
+            .filter(|v| v.group_parent.as_ref().is_some_and(|v| v.name == name))
+        {
+            child.group_parent = new_path.clone();
+        }
+
+        if let Some(new_path) = new_path {
+            self.reassign_assets(name, new_path, sprites, tilesets)?;
+        }
+
+        Ok(())
+    }
+
+    fn reassign_assets(
+        &self,
+        name: &str,
+        new_path: TexturePath,
+        sprites: &mut YyResourceHandler<Sprite>,
+        tilesets: &mut YyResourceHandler<TileSet>,
+    ) -> Result<(), GroupError> {
+        sprites.load_all();
+        tilesets.load_all();
+
+        let sprite_names: Vec<String> = sprites
+            .resources()
+            .iter()
+            .filter(|(_, v)| v.yy_resource.texture_group_id.name == name)
+            .map(|(k, _)| k.clone())
+            .collect();
+        for sprite_name in sprite_names {
+            sprites
+                .edit_yy(&sprite_name, |v| v.texture_group_id = new_path.clone())
+                .map_err(|e| GroupError::Reassign(sprite_name.clone(), e.to_string()))?;
+        }
+
+        let tileset_names: Vec<String> = tilesets
+            .resources()
+            .iter()
+            .filter(|(_, v)| v.yy_resource.texture_group_id.name == name)
+            .map(|(k, _)| k.clone())
+            .collect();
+        for tileset_name in tileset_names {
+            tilesets
+                .edit_yy(&tileset_name, |v| v.texture_group_id = new_path.clone())
+                .map_err(|e| GroupError::Reassign(tileset_name.clone(), e.to_string()))?;
+        }
+
+        Ok(())
+    }
+
+    fn can_use_name(&self, name: &str) -> Result<(), GroupError> {
+        if crate::yyp_boss::RNV.is_valid(name) == false {
+            return Err(GroupError::BadName);
+        }
+
+        if self.get(name).is_some() {
+            return Err(GroupError::NameCollision);
+        }
+
+        Ok(())
+    }
+
+    fn position(&self, name: &str) -> Result<usize, GroupError> {
+        self.texture_groups
+            .iter()
+            .position(|v| v.common_data.name == name)
+            .ok_or(GroupError::NotFound)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SpriteExt;
+    use yy_typings::ViewPath;
+
+    #[test]
+    fn rename_and_remove() {
+        let mut manager = TextureGroupManager::default();
+        let default = manager.create(DEFAULT_TEXTURE_GROUP, None).unwrap();
+        let crops = manager.create("Crops", None).unwrap();
+        manager.create("Trees", Some("Crops")).unwrap();
+        assert_eq!(
+            manager.create("Crops", None),
+            Err(GroupError::NameCollision)
+        );
+        assert_eq!(
+            manager.set_parent("Crops", Some("Trees")),
+            Err(GroupError::ParentCycle)
+        );
+
+        let mut sprites = YyResourceHandler::new();
+        let mut tilesets = YyResourceHandler::new();
+        sprites.set(
+            Sprite::new("spr_wheat", crops, ViewPath::default()),
+            Some(Default::default()),
+        );
+        sprites.set(
+            Sprite::new("spr_player", default.clone(), ViewPath::default()),
+            Some(Default::default()),
+        );
+
+        manager
+            .rename("Crops", "Farm", &mut sprites, &mut tilesets)
+            .unwrap();
+        assert_eq!(
+            sprites
+                .get("spr_wheat")
+                .unwrap()
+                .yy_resource
+                .texture_group_id,
+            manager.texture_path("Farm").unwrap()
+        );
+        assert_eq!(
+            manager.get("Trees").unwrap().group_parent,
+            manager.texture_path("Farm")
+        );
+
+        manager.remove("Farm", &mut sprites, &mut tilesets).unwrap();
+        assert_eq!(
+            sprites
+                .get("spr_wheat")
+                .unwrap()
+                .yy_resource
+                .texture_group_id,
+            default
+        );
+        assert_eq!(manager.get("Trees").unwrap().group_parent, None);
+        assert_eq!(
+            manager.remove(DEFAULT_TEXTURE_GROUP, &mut sprites, &mut tilesets),
+            Err(GroupError::DefaultGroup)
+        );
+
+        // without a default group, a group without a parent can't be removed
+        let mut manager = TextureGroupManager::default();
+        let crops = manager.create("Crops", None).unwrap();
+        sprites.set(
+            Sprite::new("spr_corn", crops.clone(), ViewPath::default()),
+            Some(Default::default()),
+        );
+        assert_eq!(
+            manager.remove("Crops", &mut sprites, &mut tilesets),
+            Err(GroupError::NoDefaultGroup)
+        );
+        assert!(manager.get("Crops").is_some());
+        assert_eq!(
+            sprites
+                .get("spr_corn")
+                .unwrap()
+                .yy_resource
+                .texture_group_id,
+            crops
+        );
+    }
+}
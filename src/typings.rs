@@ -0,0 +1,102 @@
+//! Typings for the Gms2 resources which `yy_typings` only provides stubs for.
+//!
+//! Each typing here names the fields the YypBoss actually manipulates, and collects every other
+//! field into an `extra` map. This keeps us lossless: reserializing a resource writes back
+//! everything GameMaker gave us, even the fields we don't understand yet.
+
+use serde::{Deserialize, Serialize};
+
+/// Creates unit enums which serialize into a constant `resourceType` string.
+macro_rules! gm_const {
+    ($($name:ident -> $serde_name:literal),+ $(,)?) => {
+        $(
+            #[derive(
+                Debug,
+                Copy,
+                Clone,
+                PartialEq,
+                Eq,
+                Hash,
+                PartialOrd,
+                Ord,
+                Default,
+                serde::Serialize,
+                serde::Deserialize,
+            )]
+            pub enum $name {
+                #[default]
+                #[serde(rename = $serde_name)]
+                Const,
+            }
+        )+
+    };
+}
+
+/// The catch-all map of fields we do not have typings for.
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+#[serde(transparent)]
+pub struct ExtraFields(pub serde_json::Map<String, serde_json::Value>);
+
+impl<'de> Deserialize<'de> for ExtraFields {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let mut map = serde_json::Map::deserialize(deserializer)?;
+
+        // the formatter writes this out for us from the `name` field, so
+        // keeping it around would write a stale name after a rename.
+        map.remove("%Name");
+
+        Ok(Self(map))
+    }
+}
+
+impl ExtraFields {
+    /// Creates the extra fields out of a `json!` object.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` is not an object.
+    pub(crate) fn from_json(value: serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::Object(map) => Self(map),
+            _ => panic!("extra fields must be an object"),
+        }
+    }
+}
+
+impl std::ops::Deref for ExtraFields {
+    type Target = serde_json::Map<String, serde_json::Value>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl std::ops::DerefMut for ExtraFields {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+mod animation_curve;
+pub use animation_curve::*;
+
+mod extension;
+pub use extension::*;
+
+mod font;
+pub use font::*;
+
+mod path;
+pub use path::*;
+
+mod room;
+pub use room::*;
+
+mod sequence;
+pub use sequence::*;
+
+mod timeline;
+pub use timeline::*;
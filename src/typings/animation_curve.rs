@@ -0,0 +1,157 @@
+use super::ExtraFields;
+use serde::{Deserialize, Serialize};
+use yy_typings::{CommonData, ResourceVersion, ViewPath};
+
+gm_const!(
+    ConstGmAnimCurve -> "GMAnimCurve",
+    ConstGmAnimCurveChannel -> "GMAnimCurveChannel",
+);
+
+/// A Gms2 Animation Curve.
+#[derive(Debug, Serialize, Deserialize, Default, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AnimationCurve {
+    /// Common data
+    #[serde(flatten)]
+    pub common_data: CommonData<ConstGmAnimCurve>,
+
+    /// The channels of the curve, each of which is a separate curve over the same range.
+    #[serde(default)]
+    pub channels: Vec<AnimationCurveChannel>,
+
+    /// How the points of every channel are connected.
+    pub function: CurveInterpolation,
+
+    /// The parent in the Gms2 virtual file system, ie. the parent which
+    /// a user would see in the Navigation Pane in Gms2. This has no
+    /// relationship to the actual operating system's filesystem.
+    pub parent: ViewPath,
+
+    /// Every other field, which we pass through untouched.
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+/// A single channel of an Animation Curve.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AnimationCurveChannel {
+    pub resource_type: ConstGmAnimCurveChannel,
+
+    /// The resource version of this channel.
+    pub resource_version: ResourceVersion,
+
+    /// The name of the channel, which is used to find it in gml.
+    pub name: String,
+
+    /// The colour of the channel in the curve editor, as an ABGR integer.
+    pub colour: u32,
+
+    /// The points of the channel, ordered by their `x`.
+    #[serde(default)]
+    pub points: Vec<AnimationCurvePoint>,
+
+    /// Whether the channel is shown in the curve editor.
+    pub visible: bool,
+
+    /// Every other field, which we pass through untouched.
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+impl AnimationCurveChannel {
+    /// The colour Gms2 gives the first channel of a new curve.
+    pub const DEFAULT_COLOUR: u32 = 4290799884;
+
+    /// Creates a new channel with no points.
+    pub fn new(name: &str) -> Self {
+        Self {
+            resource_type: ConstGmAnimCurveChannel::Const,
+            resource_version: ResourceVersion::new(2, 0),
+            name: name.to_owned(),
+            colour: Self::DEFAULT_COLOUR,
+            points: Vec::new(),
+            visible: true,
+            extra: ExtraFields::default(),
+        }
+    }
+}
+
+impl Default for AnimationCurveChannel {
+    fn default() -> Self {
+        Self::new("")
+    }
+}
+
+/// A single point on an Animation Curve channel.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
+pub struct AnimationCurvePoint {
+    /// The horizontal offset of the incoming bezier handle.
+    pub th0: f64,
+    /// The horizontal offset of the outgoing bezier handle.
+    pub th1: f64,
+    /// The vertical offset of the incoming bezier handle.
+    pub tv0: f64,
+    /// The vertical offset of the outgoing bezier handle.
+    pub tv1: f64,
+    /// The position of the point along the curve, between 0 and 1.
+    pub x: f64,
+    /// The value of the curve at this point.
+    pub y: f64,
+}
+
+impl AnimationCurvePoint {
+    /// Creates a new point with the default bezier handles.
+    pub fn new(x: f64, y: f64) -> Self {
+        Self {
+            th0: -1.0,
+            th1: 1.0,
+            tv0: 0.0,
+            tv1: 0.0,
+            x,
+            y,
+        }
+    }
+}
+
+impl Default for AnimationCurvePoint {
+    fn default() -> Self {
+        Self::new(0.0, 0.0)
+    }
+}
+
+/// The connection between the points of an Animation Curve.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Default)]
+#[serde(into = "u8", try_from = "u8")]
+pub enum CurveInterpolation {
+    /// Each point is connected with a straight line.
+    #[default]
+    Linear,
+    /// The points are connected by a smooth curve through each point.
+    Smooth,
+    /// The points are connected by bezier curves, shaped by each point's handles.
+    Bezier,
+}
+
+impl From<CurveInterpolation> for u8 {
+    fn from(o: CurveInterpolation) -> Self {
+        match o {
+            CurveInterpolation::Linear => 0,
+            CurveInterpolation::Smooth => 1,
+            CurveInterpolation::Bezier => 2,
+        }
+    }
+}
+
+impl TryFrom<u8> for CurveInterpolation {
+    type Error = String;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(CurveInterpolation::Linear),
+            1 => Ok(CurveInterpolation::Smooth),
+            2 => Ok(CurveInterpolation::Bezier),
+            v => Err(format!("{} is not a valid curve interpolation", v)),
+        }
+    }
+}
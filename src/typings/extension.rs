@@ -0,0 +1,276 @@
+use super::ExtraFields;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use yy_typings::{CommonData, FilesystemPath, ResourceVersion, ViewPath};
+
+gm_const!(
+    ConstGmExtension -> "GMExtension",
+    ConstGmExtensionFile -> "GMExtensionFile",
+    ConstGmExtensionFunction -> "GMExtensionFunction",
+    ConstGmExtensionConstant -> "GMExtensionConstant",
+    ConstGmProxyFile -> "GMProxyFile",
+);
+
+/// A Gms2 Extension.
+#[derive(Debug, Serialize, Deserialize, Default, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Extension {
+    /// Common data
+    #[serde(flatten)]
+    pub common_data: CommonData<ConstGmExtension>,
+
+    /// The files bundled with the extension, which hold its functions and constants.
+    #[serde(default)]
+    pub files: Vec<ExtensionFile>,
+
+    /// The parent in the Gms2 virtual file system, ie. the parent which
+    /// a user would see in the Navigation Pane in Gms2. This has no
+    /// relationship to the actual operating system's filesystem.
+    pub parent: ViewPath,
+
+    /// Every other field, such as the platform options, which we pass through untouched.
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+impl Extension {
+    /// The filenames of every file which sits next to the extension's yy file,
+    /// including proxy files.
+    pub fn filenames(&self) -> impl Iterator<Item = &str> {
+        self.files.iter().flat_map(|file| {
+            std::iter::once(file.filename.as_str())
+                .chain(file.proxy_files.iter().map(|v| v.name.as_str()))
+        })
+    }
+
+    /// Finds a file by its filename.
+    pub fn file_mut(&mut self, filename: &str) -> Option<&mut ExtensionFile> {
+        self.files.iter_mut().find(|v| v.filename == filename)
+    }
+
+    /// Returns `true` if a function or a constant in this extension has the given name.
+    pub fn defines(&self, name: &str) -> bool {
+        self.files.iter().any(|file| {
+            file.functions.iter().any(|v| v.name == name)
+                || file.constants.iter().any(|v| v.name == name)
+        })
+    }
+}
+
+/// A file bundled with an extension, such as a dll or a gml script.
+#[derive(Debug, Serialize, Deserialize, Default, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtensionFile {
+    pub resource_type: ConstGmExtensionFile,
+
+    /// The resource version of this file.
+    pub resource_version: ResourceVersion,
+
+    /// Appears to always be empty.
+    #[serde(default)]
+    pub name: String,
+
+    /// The name of the file on disk, such as `steam_api.dll`.
+    pub filename: String,
+
+    /// The kind of the file. `1` is a native library, `2` is gml, `4` is anything else, and
+    /// `5` is javascript.
+    pub kind: u8,
+
+    /// The constants which this file defines.
+    #[serde(default)]
+    pub constants: Vec<ExtensionConstant>,
+
+    /// The functions which this file defines.
+    #[serde(default)]
+    pub functions: Vec<ExtensionFunction>,
+
+    /// The order of the functions, which Gms2 writes as paths to the extension's yy file,
+    /// named after each function.
+    #[serde(default)]
+    pub order: Vec<FilesystemPath>,
+
+    /// The platform specific versions of this file, such as `.so` or `.dylib` builds of a dll.
+    #[serde(rename = "ProxyFiles", default)]
+    pub proxy_files: Vec<ExtensionProxyFile>,
+
+    /// Every other field, which we pass through untouched.
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+impl ExtensionFile {
+    /// Creates a new, empty file. The kind of the file is chosen from its file extension.
+    pub fn new(filename: &str) -> Self {
+        let kind = match std::path::Path::new(filename)
+            .extension()
+            .and_then(|v| v.to_str())
+        {
+            Some("dll") | Some("so") | Some("dylib") => 1,
+            Some("gml") => 2,
+            Some("js") => 5,
+            _ => 4,
+        };
+
+        let extra = json!({
+            "copyToTargets": -1,
+            "final": "",
+            "init": "",
+            "origname": "",
+            "uncompress": false,
+            "usesRunnerInterface": false,
+        });
+
+        Self {
+            resource_version: ResourceVersion::new(2, 0),
+            filename: filename.to_owned(),
+            kind,
+            extra: ExtraFields::from_json(extra),
+            ..Self::default()
+        }
+    }
+}
+
+/// A function defined by an extension file.
+#[derive(Debug, Serialize, Deserialize, Default, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtensionFunction {
+    pub resource_type: ConstGmExtensionFunction,
+
+    /// The resource version of this function.
+    pub resource_version: ResourceVersion,
+
+    /// The name of the function within gml.
+    pub name: String,
+
+    /// The name of the function within the file, such as the exported symbol of a dll.
+    pub external_name: String,
+
+    /// The kind of the function, which matches the kind of its file.
+    pub kind: u8,
+
+    /// The help text which autocomplete shows.
+    #[serde(default)]
+    pub help: String,
+
+    /// Hidden functions are not shown in autocomplete.
+    #[serde(default)]
+    pub hidden: bool,
+
+    /// The type which the function returns.
+    pub return_type: ExtensionValueType,
+
+    /// The number of arguments, or `-1` if the function takes any number of arguments.
+    pub arg_count: i32,
+
+    /// The types of each argument.
+    #[serde(default)]
+    pub args: Vec<ExtensionValueType>,
+
+    /// Every other field, which we pass through untouched.
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+impl ExtensionFunction {
+    /// Creates a new function, where the gml name and external name are the same.
+    pub fn new(name: &str, return_type: ExtensionValueType, args: Vec<ExtensionValueType>) -> Self {
+        Self {
+            resource_version: ResourceVersion::new(2, 0),
+            name: name.to_owned(),
+            external_name: name.to_owned(),
+            return_type,
+            arg_count: args.len() as i32,
+            args,
+            extra: ExtraFields::from_json(json!({ "documentation": "" })),
+            ..Self::default()
+        }
+    }
+}
+
+/// A constant defined by an extension file.
+#[derive(Debug, Serialize, Deserialize, Default, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtensionConstant {
+    pub resource_type: ConstGmExtensionConstant,
+
+    /// The resource version of this constant.
+    pub resource_version: ResourceVersion,
+
+    /// The name of the constant within gml.
+    pub name: String,
+
+    /// The gml expression which the constant expands to.
+    pub value: String,
+
+    /// Hidden constants are not shown in autocomplete.
+    #[serde(default)]
+    pub hidden: bool,
+
+    /// Every other field, which we pass through untouched.
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+impl ExtensionConstant {
+    /// Creates a new constant.
+    pub fn new(name: &str, value: &str) -> Self {
+        Self {
+            resource_version: ResourceVersion::new(2, 0),
+            name: name.to_owned(),
+            value: value.to_owned(),
+            ..Self::default()
+        }
+    }
+}
+
+/// A platform specific version of an extension file.
+#[derive(Debug, Serialize, Deserialize, Default, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtensionProxyFile {
+    pub resource_type: ConstGmProxyFile,
+
+    /// The resource version of this proxy file.
+    pub resource_version: ResourceVersion,
+
+    /// The name of the file on disk, such as `libsteam_api.so`.
+    pub name: String,
+
+    /// The platforms which use this file, as a bitmask.
+    #[serde(rename = "TargetMask")]
+    pub target_mask: i64,
+
+    /// Every other field, which we pass through untouched.
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+/// The type of an extension function's argument or return value.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Default)]
+#[serde(into = "u8", try_from = "u8")]
+pub enum ExtensionValueType {
+    String,
+    #[default]
+    Real,
+}
+
+impl From<ExtensionValueType> for u8 {
+    fn from(o: ExtensionValueType) -> Self {
+        match o {
+            ExtensionValueType::String => 1,
+            ExtensionValueType::Real => 2,
+        }
+    }
+}
+
+impl TryFrom<u8> for ExtensionValueType {
+    type Error = String;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(ExtensionValueType::String),
+            2 => Ok(ExtensionValueType::Real),
+            v => Err(format!("{} is not a valid extension value type", v)),
+        }
+    }
+}
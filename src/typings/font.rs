@@ -0,0 +1,32 @@
+use super::ExtraFields;
+use serde::{Deserialize, Serialize};
+use yy_typings::{CommonData, ViewPath};
+
+gm_const!(ConstGmFont -> "GMFont");
+
+/// A Gms2 Font. GameMaker bakes the glyphs of a font into a single atlas image,
+/// which sits next to the yy file as `<name>.png`.
+#[derive(Debug, Serialize, Deserialize, Default, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Font {
+    /// Common data
+    #[serde(flatten)]
+    pub common_data: CommonData<ConstGmFont>,
+
+    /// The parent in the Gms2 virtual file system, ie. the parent which
+    /// a user would see in the Navigation Pane in Gms2. This has no
+    /// relationship to the actual operating system's filesystem.
+    pub parent: ViewPath,
+
+    /// Every other field, such as the glyphs and the font's ranges, which we pass
+    /// through untouched.
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+impl Font {
+    /// The filename of the glyph atlas of this font.
+    pub fn atlas_filename(&self) -> String {
+        format!("{}.png", self.common_data.name)
+    }
+}
@@ -0,0 +1,127 @@
+use super::ExtraFields;
+use serde::{Deserialize, Serialize};
+use yy_typings::{CommonData, ViewPath};
+
+gm_const!(ConstGmPath -> "GMPath");
+
+/// A Gms2 Path, which is a series of points which instances can follow.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Path {
+    /// Common data
+    #[serde(flatten)]
+    pub common_data: CommonData<ConstGmPath>,
+
+    /// If the final point connects back to the first point.
+    #[serde(default)]
+    pub closed: bool,
+
+    /// If the path is made of straight lines or of curves.
+    #[serde(default)]
+    pub kind: PathKind,
+
+    /// The parent in the Gms2 virtual file system, ie. the parent which
+    /// a user would see in the Navigation Pane in Gms2. This has no
+    /// relationship to the actual operating system's filesystem.
+    pub parent: ViewPath,
+
+    /// The points of the path, in order.
+    #[serde(default)]
+    pub points: Vec<PathPoint>,
+
+    /// How smooth a curved path is, between 1 and 8. This is only meaningful
+    /// for [`PathKind::Smooth`].
+    #[serde(default = "Path::default_precision")]
+    pub precision: u8,
+
+    /// Every other field, which we pass through untouched.
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+impl Path {
+    /// The precision Gms2 gives new paths.
+    pub const DEFAULT_PRECISION: u8 = 4;
+
+    /// The largest precision Gms2 allows.
+    pub const MAX_PRECISION: u8 = 8;
+
+    fn default_precision() -> u8 {
+        Self::DEFAULT_PRECISION
+    }
+}
+
+impl Default for Path {
+    fn default() -> Self {
+        Self {
+            common_data: CommonData::default(),
+            closed: false,
+            kind: PathKind::default(),
+            parent: ViewPath::default(),
+            points: Vec::new(),
+            precision: Self::DEFAULT_PRECISION,
+            extra: ExtraFields::default(),
+        }
+    }
+}
+
+/// A single point along a Path.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
+pub struct PathPoint {
+    /// The speed of an instance at this point, as a percentage of its path speed.
+    pub speed: f64,
+    pub x: f64,
+    pub y: f64,
+}
+
+impl PathPoint {
+    /// The speed Gms2 gives new points.
+    pub const DEFAULT_SPEED: f64 = 100.0;
+
+    /// Creates a new point at the default speed.
+    pub fn new(x: f64, y: f64) -> Self {
+        Self {
+            speed: Self::DEFAULT_SPEED,
+            x,
+            y,
+        }
+    }
+}
+
+impl Default for PathPoint {
+    fn default() -> Self {
+        Self::new(0.0, 0.0)
+    }
+}
+
+/// The connection between the points of a Path.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Default)]
+#[serde(into = "u8", try_from = "u8")]
+pub enum PathKind {
+    /// Each point is connected with a straight line.
+    #[default]
+    Straight,
+    /// The points are connected by a curve, smoothed by the Path's precision.
+    Smooth,
+}
+
+impl From<PathKind> for u8 {
+    fn from(o: PathKind) -> Self {
+        match o {
+            PathKind::Straight => 0,
+            PathKind::Smooth => 1,
+        }
+    }
+}
+
+impl TryFrom<u8> for PathKind {
+    type Error = String;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(PathKind::Straight),
+            1 => Ok(PathKind::Smooth),
+            v => Err(format!("{} is not a valid path kind", v)),
+        }
+    }
+}
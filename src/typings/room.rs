@@ -0,0 +1,175 @@
+use super::ExtraFields;
+use serde::{Deserialize, Serialize};
+use yy_typings::{CommonData, FilesystemPath, ResourceVersion, VersionStamp, ViewPath};
+
+gm_const!(
+    ConstGmRoom -> "GMRoom",
+    ConstGmRInstance -> "GMRInstance",
+);
+
+/// A Gms2 Room.
+#[derive(Debug, Serialize, Deserialize, Default, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Room {
+    /// Common data
+    #[serde(flatten)]
+    pub common_data: CommonData<ConstGmRoom>,
+
+    #[serde(rename = "$GMRoom")]
+    pub gm_version_stamp: VersionStamp<1>,
+
+    /// The relative path of the creation code for this room, such as
+    /// `rooms/rm_level/RoomCreationCode.gml`, or an empty string if there is none.
+    #[serde(default)]
+    pub creation_code_file: String,
+
+    /// The order instances are created in. Each entry names an instance, with the path
+    /// pointing to the yy file of this room.
+    #[serde(default)]
+    pub instance_creation_order: Vec<FilesystemPath>,
+
+    /// The layers of the room. Layers can nest within each other.
+    #[serde(default)]
+    pub layers: Vec<RoomLayer>,
+
+    /// The parent in the Gms2 virtual file system, ie. the parent which
+    /// a user would see in the Navigation Pane in Gms2. This has no
+    /// relationship to the actual operating system's filesystem.
+    pub parent: ViewPath,
+
+    /// The room this room inherits from, if any.
+    #[serde(default)]
+    pub parent_room: Option<FilesystemPath>,
+
+    /// Every other field, such as views and physics settings, which we pass through untouched.
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+impl Room {
+    /// The filename of a room's creation code.
+    pub const CREATION_CODE_FILENAME: &'static str = "RoomCreationCode.gml";
+
+    /// The filename of the creation code of an instance within a room.
+    pub fn instance_creation_code_filename(instance_name: &str) -> String {
+        format!("InstanceCreationCode_{}.gml", instance_name)
+    }
+
+    /// Returns an iterator over every layer in the room, including nested layers.
+    pub fn all_layers(&self) -> impl Iterator<Item = &RoomLayer> {
+        let mut stack: Vec<&RoomLayer> = self.layers.iter().rev().collect();
+
+        std::iter::from_fn(move || {
+            let layer = stack.pop()?;
+            stack.extend(layer.layers.iter().rev());
+
+            Some(layer)
+        })
+    }
+
+    /// Returns an iterator over every instance in the room, in layer order.
+    pub fn instances(&self) -> impl Iterator<Item = &RoomInstance> {
+        self.all_layers()
+            .filter_map(|layer| layer.instances.as_ref())
+            .flatten()
+    }
+
+    /// Finds an instance within the room by name.
+    pub fn instance_mut(&mut self, instance_name: &str) -> Option<&mut RoomInstance> {
+        fn find<'a>(layers: &'a mut [RoomLayer], name: &str) -> Option<&'a mut RoomInstance> {
+            for layer in layers {
+                if let Some(instances) = layer.instances.as_mut() {
+                    if let Some(instance) = instances.iter_mut().find(|v| v.name == name) {
+                        return Some(instance);
+                    }
+                }
+
+                if let Some(instance) = find(&mut layer.layers, name) {
+                    return Some(instance);
+                }
+            }
+
+            None
+        }
+
+        find(&mut self.layers, instance_name)
+    }
+}
+
+/// A layer within a room.
+#[derive(Debug, Serialize, Deserialize, Default, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RoomLayer {
+    pub resource_type: RoomLayerKind,
+
+    /// The resource version of this layer.
+    pub resource_version: ResourceVersion,
+
+    /// The name of the layer. This is the human readable name used in the IDE.
+    pub name: String,
+
+    #[serde(default)]
+    pub depth: i32,
+
+    #[serde(default)]
+    pub visible: bool,
+
+    /// The child layers of this layer.
+    #[serde(default)]
+    pub layers: Vec<RoomLayer>,
+
+    /// The instances within the layer. Only instance layers have this field.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instances: Option<Vec<RoomInstance>>,
+
+    /// Every other field, which we pass through untouched.
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+/// The kind of a room layer, which is its `resourceType`.
+#[derive(
+    Debug, Serialize, Deserialize, Default, PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord,
+)]
+pub enum RoomLayerKind {
+    #[serde(rename = "GMRInstanceLayer")]
+    Instance,
+    #[serde(rename = "GMRTileLayer")]
+    Tile,
+    #[serde(rename = "GMRAssetLayer")]
+    Asset,
+    #[serde(rename = "GMRBackgroundLayer")]
+    Background,
+    #[serde(rename = "GMRPathLayer")]
+    Path,
+    #[serde(rename = "GMREffectLayer")]
+    Effect,
+    #[default]
+    #[serde(rename = "GMRLayer")]
+    Folder,
+}
+
+/// An instance of an object placed in a room.
+#[derive(Debug, Serialize, Deserialize, Default, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RoomInstance {
+    pub resource_type: ConstGmRInstance,
+
+    /// The resource version of this instance.
+    pub resource_version: ResourceVersion,
+
+    /// The name of the instance, such as `inst_3C5E1A2B`.
+    pub name: String,
+
+    /// The object this is an instance of.
+    pub object_id: FilesystemPath,
+
+    /// If the instance has creation code. If true, the room's directory will have an
+    /// `InstanceCreationCode_<name>.gml` file.
+    #[serde(default)]
+    pub has_creation_code: bool,
+
+    /// Every other field, which we pass through untouched.
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
@@ -0,0 +1,300 @@
+use super::ExtraFields;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use yy_typings::{
+    CommonData, FilesystemPath, PlaybackSpeed, ResourceVersion, VersionStamp, ViewPath,
+};
+
+gm_const!(ConstGmSequence -> "GMSequence");
+
+/// A Gms2 Sequence.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Sequence {
+    /// Common data
+    #[serde(flatten)]
+    pub common_data: CommonData<ConstGmSequence>,
+
+    #[serde(rename = "$GMSequence")]
+    pub gm_version_stamp: VersionStamp<1>,
+
+    /// The broadcast messages of the sequence.
+    pub events: KeyframeStore,
+
+    /// The length of the sequence, in frames.
+    pub length: f64,
+
+    /// The moments of the sequence, which call functions when reached.
+    pub moments: KeyframeStore,
+
+    /// The parent in the Gms2 virtual file system, ie. the parent which
+    /// a user would see in the Navigation Pane in Gms2. This has no
+    /// relationship to the actual operating system's filesystem.
+    pub parent: ViewPath,
+
+    /// The playback speed of the Sequence in terms of the PlaybackSpeed type.
+    pub playback_speed: f64,
+
+    /// The type of the playback speed.
+    pub playback_speed_type: PlaybackSpeed,
+
+    /// The tracks of the sequence. Tracks can nest within each other.
+    #[serde(default)]
+    pub tracks: Vec<SequenceTrack>,
+
+    /// Every other field, such as the backdrop settings, which we pass through untouched.
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+impl Default for Sequence {
+    fn default() -> Self {
+        let extra = json!({
+            "autoRecord": true,
+            "backdropHeight": 768,
+            "backdropImageOpacity": 0.5,
+            "backdropImagePath": "",
+            "backdropWidth": 1366,
+            "backdropXOffset": 0.0,
+            "backdropYOffset": 0.0,
+            "eventStubScript": null,
+            "eventToFunction": {},
+            "lockOrigin": false,
+            "playback": 1,
+            "showBackdrop": true,
+            "showBackdropImage": false,
+            "spriteId": null,
+            "timeUnits": 1,
+            "visibleRange": null,
+            "volume": 1.0,
+            "xorigin": 0,
+            "yorigin": 0,
+        });
+
+        Self {
+            common_data: CommonData::default(),
+            gm_version_stamp: VersionStamp,
+            events: KeyframeStore::new("MessageEventKeyframe"),
+            length: 60.0,
+            moments: KeyframeStore::new("MomentsEventKeyframe"),
+            parent: ViewPath::default(),
+            playback_speed: 30.0,
+            playback_speed_type: PlaybackSpeed::FramesPerSecond,
+            tracks: Vec::new(),
+            extra: ExtraFields::from_json(extra),
+        }
+    }
+}
+
+/// A track within a sequence.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SequenceTrack {
+    pub resource_type: SequenceTrackKind,
+
+    /// The resource version of this track.
+    pub resource_version: ResourceVersion,
+
+    /// The name of the track, which is generally the name of the asset it displays.
+    pub name: String,
+
+    /// The keyframes of the track.
+    pub keyframes: KeyframeStore,
+
+    /// The child tracks, such as the position or rotation parameter tracks of a graphic track.
+    #[serde(default)]
+    pub tracks: Vec<SequenceTrack>,
+
+    /// Every other field, which we pass through untouched.
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+impl SequenceTrack {
+    /// Creates a new, empty track of the given kind.
+    pub fn new(name: &str, kind: SequenceTrackKind) -> Self {
+        let keyframe_kind = kind.keyframe_kind().unwrap_or_default();
+        let extra = json!({
+            "builtinName": 0,
+            "events": [],
+            "inheritsTrackColour": true,
+            "interpolation": 1,
+            "isCreationTrack": false,
+            "modifiers": [],
+            "spriteId": null,
+            "trackColour": 0,
+            "traits": 0,
+        });
+
+        Self {
+            resource_type: kind,
+            resource_version: ResourceVersion::new(2, 0),
+            name: name.to_owned(),
+            keyframes: KeyframeStore::new(keyframe_kind),
+            tracks: Vec::new(),
+            extra: ExtraFields::from_json(extra),
+        }
+    }
+}
+
+/// The kind of a sequence track, which is its `resourceType`.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Hash, PartialOrd, Ord)]
+#[serde(from = "String", into = "String")]
+pub enum SequenceTrackKind {
+    Graphic,
+    Audio,
+    Instance,
+    Sequence,
+    /// Any track we don't have a name for, such as parameter tracks, with its `resourceType`.
+    Other(String),
+}
+
+impl From<String> for SequenceTrackKind {
+    fn from(o: String) -> Self {
+        match o.as_str() {
+            "GMGraphicTrack" => SequenceTrackKind::Graphic,
+            "GMAudioTrack" => SequenceTrackKind::Audio,
+            "GMInstanceTrack" => SequenceTrackKind::Instance,
+            "GMSequenceTrack" => SequenceTrackKind::Sequence,
+            _ => SequenceTrackKind::Other(o),
+        }
+    }
+}
+
+impl From<SequenceTrackKind> for String {
+    fn from(o: SequenceTrackKind) -> Self {
+        match o {
+            SequenceTrackKind::Graphic => "GMGraphicTrack".to_string(),
+            SequenceTrackKind::Audio => "GMAudioTrack".to_string(),
+            SequenceTrackKind::Instance => "GMInstanceTrack".to_string(),
+            SequenceTrackKind::Sequence => "GMSequenceTrack".to_string(),
+            SequenceTrackKind::Other(resource_type) => resource_type,
+        }
+    }
+}
+
+impl SequenceTrackKind {
+    /// The kind of keyframe which tracks of this kind hold, if it is a track which
+    /// displays or plays a single asset.
+    pub fn keyframe_kind(&self) -> Option<&'static str> {
+        match self {
+            SequenceTrackKind::Graphic => Some("AssetSpriteKeyframe"),
+            SequenceTrackKind::Audio => Some("AudioKeyframe"),
+            SequenceTrackKind::Instance => Some("AssetInstanceKeyframe"),
+            SequenceTrackKind::Sequence => Some("AssetSequenceKeyframe"),
+            SequenceTrackKind::Other(_) => None,
+        }
+    }
+
+    /// The subpath of the assets which tracks of this kind hold, such as `sprites`.
+    pub fn asset_subpath(&self) -> Option<&'static str> {
+        match self {
+            SequenceTrackKind::Graphic => Some("sprites"),
+            SequenceTrackKind::Audio => Some("sounds"),
+            SequenceTrackKind::Instance => Some("objects"),
+            SequenceTrackKind::Sequence => Some("sequences"),
+            SequenceTrackKind::Other(_) => None,
+        }
+    }
+}
+
+/// A list of keyframes, which GameMaker wraps in a `KeyframeStore<T>`.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyframeStore {
+    /// The resource type, such as `KeyframeStore<AssetSpriteKeyframe>`.
+    pub resource_type: String,
+
+    /// The resource version of this store.
+    pub resource_version: ResourceVersion,
+
+    /// The keyframes, ordered by their `key`.
+    #[serde(rename = "Keyframes")]
+    pub keyframes: Vec<Keyframe>,
+
+    /// Every other field, which we pass through untouched.
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+impl KeyframeStore {
+    /// Creates a new, empty store for the given kind of keyframe.
+    pub fn new(keyframe_kind: &str) -> Self {
+        Self {
+            resource_type: format!("KeyframeStore<{}>", keyframe_kind),
+            resource_version: ResourceVersion::new(2, 0),
+            keyframes: Vec::new(),
+            extra: ExtraFields::default(),
+        }
+    }
+
+    /// The kind of keyframe this store holds, such as `AssetSpriteKeyframe`.
+    pub fn keyframe_kind(&self) -> &str {
+        self.resource_type
+            .strip_prefix("KeyframeStore<")
+            .and_then(|v| v.strip_suffix('>'))
+            .unwrap_or_default()
+    }
+}
+
+/// A single keyframe within a track.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default)]
+pub struct Keyframe {
+    /// The resource type, such as `Keyframe<AssetSpriteKeyframe>`.
+    #[serde(rename = "resourceType")]
+    pub resource_type: String,
+
+    /// The resource version of this keyframe.
+    #[serde(rename = "resourceVersion")]
+    pub resource_version: ResourceVersion,
+
+    /// The unique id of the keyframe.
+    pub id: String,
+
+    /// The frame which this keyframe starts on.
+    #[serde(rename = "Key")]
+    pub key: f64,
+
+    /// How many frames this keyframe lasts for.
+    #[serde(rename = "Length")]
+    pub length: f64,
+
+    /// The data of the keyframe. Most keyframes only have a `"0"` channel.
+    #[serde(rename = "Channels")]
+    pub channels: BTreeMap<String, Value>,
+
+    /// Every other field, such as `Stretch` and `Disabled`, which we pass through untouched.
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+impl Keyframe {
+    /// Creates a new keyframe of the given kind, which shows or plays `asset`.
+    pub fn new_asset(keyframe_kind: &str, key: f64, length: f64, asset: &FilesystemPath) -> Self {
+        let mut channel = json!({
+            "Id": asset,
+            "resourceType": keyframe_kind,
+            "resourceVersion": "2.0",
+        });
+        if keyframe_kind == "AudioKeyframe" {
+            channel["Mode"] = json!(0);
+        }
+
+        let extra = json!({
+            "Disabled": false,
+            "IsCreationKey": false,
+            "Stretch": false,
+        });
+
+        Self {
+            resource_type: format!("Keyframe<{}>", keyframe_kind),
+            resource_version: ResourceVersion::new(2, 0),
+            id: uuid::Uuid::new_v4().to_string(),
+            key,
+            length,
+            channels: std::iter::once(("0".to_string(), channel)).collect(),
+            extra: ExtraFields::from_json(extra),
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use super::ExtraFields;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use yy_typings::{CommonData, ObjectEvent, ResourceVersion, ViewPath};
+
+gm_const!(
+    ConstGmTimeline -> "GMTimeline",
+    ConstMoment -> "Moment",
+);
+
+/// A Gms2 Timeline. Each moment of a timeline has its own gml, which sits next to
+/// the yy file as `moment_<N>.gml`.
+#[derive(Debug, Serialize, Deserialize, Default, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Timeline {
+    /// Common data
+    #[serde(flatten)]
+    pub common_data: CommonData<ConstGmTimeline>,
+
+    /// The moments of the timeline, ordered by their step.
+    #[serde(default)]
+    pub moment_list: Vec<TimelineMoment>,
+
+    /// The parent in the Gms2 virtual file system, ie. the parent which
+    /// a user would see in the Navigation Pane in Gms2. This has no
+    /// relationship to the actual operating system's filesystem.
+    pub parent: ViewPath,
+
+    /// Every other field, which we pass through untouched.
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+impl Timeline {
+    /// The filename of the gml of a moment.
+    pub fn moment_filename(moment: usize) -> String {
+        format!("moment_{}.gml", moment)
+    }
+}
+
+/// A single moment within a Timeline.
+#[derive(Debug, Serialize, Deserialize, Default, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineMoment {
+    pub resource_type: ConstMoment,
+
+    /// The resource version of this moment.
+    pub resource_version: ResourceVersion,
+
+    /// Appears to always be empty.
+    #[serde(default)]
+    pub name: String,
+
+    /// The step of the timeline this moment runs on.
+    pub moment: usize,
+
+    /// The event which holds the moment's code. Gms2 writes this as a Create event.
+    pub evnt: ObjectEvent,
+
+    /// Every other field, which we pass through untouched.
+    #[serde(flatten)]
+    pub extra: ExtraFields,
+}
+
+impl TimelineMoment {
+    /// Creates a new moment at the given step, with the same fields Gms2 writes for one. Its
+    /// `%Name` is written out from `name`, as with every other resource.
+    pub fn new(moment: usize) -> Self {
+        Self {
+            resource_version: ResourceVersion::new(2, 0),
+            moment,
+            extra: ExtraFields::from_json(json!({ "$Moment": "" })),
+            ..Self::default()
+        }
+    }
+}
@@ -12,8 +12,11 @@ pub enum FileSerializationError {
     Io(String),
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, Ord, PartialOrd, Hash, Serialize, Deserialize, Default,
+)]
 pub enum SerializationFormat {
+    #[default]
     Json,
     Yaml,
 }
@@ -60,12 +63,6 @@ impl SerializationFormat {
     }
 }
 
-impl Default for SerializationFormat {
-    fn default() -> Self {
-        Self::Json
-    }
-}
-
 pub fn serialize_json(
     absolute_path: &Path,
     data: &impl serde::Serialize,
@@ -124,3 +121,122 @@ where
 
     Ok(data)
 }
+
+/// Deserializes a single field of the top level object of a json file, such as the `parent` of a
+/// yy file. The file is still read from disk, but only the field's value is parsed.
+pub(crate) fn deserialize_json_field_tc<T, P: AsRef<Path>>(
+    path: P,
+    key: &str,
+    tcu: &TrailingCommaUtility,
+) -> Result<T, FileSerializationError>
+where
+    for<'de> T: serde::Deserialize<'de>,
+{
+    let file_string =
+        fs::read_to_string(path).map_err(|e| FileSerializationError::Io(e.to_string()))?;
+    let value = top_level_value(&file_string, key)
+        .ok_or_else(|| FileSerializationError::Serde(format!("missing field `{}`", key)))?;
+    let data = serde_json::from_str(&tcu.clear_trailing_comma(value))
+        .map_err(|e| FileSerializationError::Serde(e.to_string()))?;
+
+    Ok(data)
+}
+
+/// Finds the text of the value of `key` in the top level object of `json`, skipping over
+/// everything else without parsing it.
+fn top_level_value<'a>(json: &'a str, key: &str) -> Option<&'a str> {
+    let bytes = json.as_bytes();
+    let skip_whitespace = |mut i: usize| {
+        while bytes.get(i).is_some_and(|v| v.is_ascii_whitespace()) {
+            i += 1;
+        }
+        i
+    };
+
+    let mut depth = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' => {
+                let end = string_end(bytes, i)?;
+                let colon = skip_whitespace(end + 1);
+
+                // only keys are followed by a colon
+                if depth == 1 && &json[i + 1..end] == key && bytes.get(colon) == Some(&b':') {
+                    let start = skip_whitespace(colon + 1);
+                    return Some(&json[start..value_end(bytes, start)?]);
+                }
+                i = end;
+            }
+            b'{' | b'[' => depth += 1,
+            b'}' | b']' => depth -= 1,
+            _ => {}
+        }
+        i += 1;
+    }
+
+    None
+}
+
+/// The index of the quote which closes the string opened at `start`.
+fn string_end(bytes: &[u8], start: usize) -> Option<usize> {
+    let mut i = start + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'"' => return Some(i),
+            _ => i += 1,
+        }
+    }
+
+    None
+}
+
+/// The index just past the end of the value which starts at `start`.
+fn value_end(bytes: &[u8], start: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut i = start;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' => i = string_end(bytes, i)?,
+            b'{' | b'[' => depth += 1,
+            b'}' | b']' if depth == 0 => return Some(i),
+            b'}' | b']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i + 1);
+                }
+            }
+            b',' if depth == 0 => return Some(i),
+            _ => {}
+        }
+        i += 1;
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn top_level_values() {
+        let json = r#"{
+  "$GMSprite":"",
+  "frames":[
+    {"name":"parent","parent":{"name":"wrong",},},
+  ],
+  "name":"spr_a\\\"parent\"",
+  "parent" : {"name":"Sprites","path":"folders/Sprites.yy",},
+  "width":64,
+}"#;
+
+        assert_eq!(
+            top_level_value(json, "parent"),
+            Some(r#"{"name":"Sprites","path":"folders/Sprites.yy",}"#)
+        );
+        assert_eq!(top_level_value(json, "width"), Some("64"));
+        assert_eq!(top_level_value(json, "height"), None);
+    }
+}
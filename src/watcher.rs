@@ -0,0 +1,322 @@
+use crate::{Resource, YypBoss};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+    sync::mpsc::{channel, Receiver},
+};
+use yy_typings::ViewPathLocation;
+
+/// Watches the root directory of a project for changes made outside of the YypBoss, such as by
+/// Gms2 while it has the project open.
+///
+/// Watching is opt-in: create a watcher with [`new`], and then call [`apply`] whenever it's
+/// convenient, such as before running each command. Nothing is reloaded until then.
+///
+/// [`new`]: #method.new
+/// [`apply`]: #method.apply
+#[derive(Debug)]
+pub struct ProjectWatcher {
+    /// The root directory as the YypBoss knows it, and as the watcher reports it.
+    root_directory: PathBuf,
+    watched_directory: PathBuf,
+    events: Receiver<notify::Result<Event>>,
+    _watcher: RecommendedWatcher,
+}
+
+impl ProjectWatcher {
+    /// Starts watching the root directory of the project.
+    pub fn new(yyp_boss: &YypBoss) -> notify::Result<Self> {
+        let root_directory = yyp_boss.directory_manager.root_directory().to_owned();
+        let watched_directory = root_directory.canonicalize()?;
+
+        let (sender, events) = channel();
+        let mut watcher = notify::recommended_watcher(sender)?;
+        watcher.watch(&watched_directory, RecursiveMode::Recursive)?;
+
+        Ok(Self {
+            root_directory,
+            watched_directory,
+            events,
+            _watcher: watcher,
+        })
+    }
+
+    /// Reloads every resource whose `.yy`, `.gml` or `.png` files changed since the last call,
+    /// and catches up with the Yyp if it changed, as in [`YypBoss::apply_external_changes`].
+    ///
+    /// [`YypBoss::apply_external_changes`]: ../struct.YypBoss.html#method.apply_external_changes
+    pub fn apply(&self, yyp_boss: &mut YypBoss) -> ExternalChanges {
+        let mut paths = BTreeSet::new();
+
+        for event in self.events.try_iter() {
+            match event {
+                Ok(event) => {
+                    if matches!(event.kind, EventKind::Access(_)) == false {
+                        paths.extend(event.paths.iter().filter_map(|path| {
+                            let relative = path.strip_prefix(&self.watched_directory).ok()?;
+                            Some(self.root_directory.join(relative))
+                        }));
+                    }
+                }
+                Err(e) => log::error!("error while watching the project...{}", e),
+            }
+        }
+
+        let paths: Vec<PathBuf> = paths.into_iter().collect();
+        yyp_boss.apply_external_changes(&paths)
+    }
+}
+
+/// The resources and folders which changed outside of the YypBoss.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalChanges {
+    /// Resources which were added to the Yyp, which have been loaded in.
+    pub added: Vec<ExternalResource>,
+
+    /// Resources which were reloaded from disk.
+    pub reloaded: Vec<ExternalResource>,
+
+    /// Resources whose yy file was deleted, which have been removed from the project.
+    pub removed: Vec<ExternalResource>,
+
+    /// Resources which changed on disk but also have changes which haven't been serialized. These
+    /// are left alone, and serializing them needs a [`ConflictResolution`].
+    ///
+    /// [`ConflictResolution`]: ./enum.ConflictResolution.html
+    pub conflicts: Vec<ExternalResource>,
+
+    /// Folders which were added to the Yyp, which have been added to the Vfs.
+    pub added_folders: Vec<ViewPathLocation>,
+
+    /// Folders which were removed from the Yyp, which have been removed from the Vfs.
+    pub removed_folders: Vec<ViewPathLocation>,
+}
+
+impl ExternalChanges {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.reloaded.is_empty()
+            && self.removed.is_empty()
+            && self.conflicts.is_empty()
+            && self.added_folders.is_empty()
+            && self.removed_folders.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct ExternalResource {
+    pub resource: Resource,
+    pub name: String,
+}
+
+/// What happened to a single resource when we applied external changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ExternalChange {
+    Unchanged,
+    Added,
+    Reloaded,
+    Removed,
+    Conflict,
+}
+
+/// Finds the resource which a changed file belongs to, if it's a file we reload.
+pub(crate) fn changed_resource(root_directory: &Path, path: &Path) -> Option<ExternalResource> {
+    let extension = path.extension()?.to_str()?;
+    if matches!(extension, "yy" | "gml" | "png") == false {
+        return None;
+    }
+
+    let mut components = path.strip_prefix(root_directory).ok()?.components();
+    let resource = Resource::parse_subpath(components.next()?.as_os_str().to_str()?)?;
+    let name = components.next()?.as_os_str().to_str()?.to_owned();
+
+    Some(ExternalResource { resource, name })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TempDirectory;
+    use crate::{utils, ConflictResolution, ResourceDescriptor, Vfs, ViewPathLocationExt};
+    use std::fs;
+    use yy_typings::{CommonData, Script, ViewPath, YypFolder};
+
+    #[test]
+    fn changed_resources() {
+        let root = Path::new("project");
+        let changed = |path: &str| changed_resource(root, &root.join(path));
+
+        assert_eq!(
+            changed("sprites/spr_player/layers/frame/layer.png"),
+            Some(ExternalResource {
+                resource: Resource::Sprite,
+                name: "spr_player".to_string(),
+            })
+        );
+        assert_eq!(
+            changed("scripts/scr_jump/scr_jump.gml").map(|v| v.resource),
+            Some(Resource::Script)
+        );
+        assert_eq!(changed("scripts/scr_jump/notes.txt"), None);
+        assert_eq!(changed("datafiles/icon.png"), None);
+        assert_eq!(changed("project.yyp"), None);
+    }
+
+    #[test]
+    fn reload_and_conflict() {
+        let root = TempDirectory::new();
+        let mut yyp_boss = root.yyp_boss();
+
+        let scripts = yyp_boss
+            .new_folder_end(Vfs::root_folder(), "Scripts")
+            .unwrap();
+        let script = Script {
+            common_data: CommonData::new("scr_jump".to_string()),
+            parent: scripts,
+            ..Script::default()
+        };
+        yyp_boss
+            .add_resource(script, "jump();".to_string())
+            .unwrap();
+        yyp_boss.serialize().unwrap();
+
+        // our own writes aren't changes
+        let gml = root.join("scripts/scr_jump/scr_jump.gml");
+        assert!(yyp_boss
+            .apply_external_changes(std::slice::from_ref(&gml))
+            .is_empty());
+
+        fs::write(&gml, "jump_higher();").unwrap();
+        let changes = yyp_boss.apply_external_changes(std::slice::from_ref(&gml));
+        assert_eq!(changes.reloaded.len(), 1);
+        assert_eq!(
+            yyp_boss.scripts.get("scr_jump").unwrap().associated_data,
+            Some("jump_higher();".to_string())
+        );
+
+        // unsaved changes win, but we say so
+        yyp_boss
+            .replace_resource(
+                yyp_boss
+                    .scripts
+                    .get("scr_jump")
+                    .unwrap()
+                    .yy_resource
+                    .clone(),
+                "fall();".to_string(),
+            )
+            .unwrap();
+        fs::write(&gml, "jump_lower();").unwrap();
+        let changes = yyp_boss.apply_external_changes(std::slice::from_ref(&gml));
+        assert_eq!(changes.conflicts.len(), 1);
+        assert_eq!(
+            yyp_boss.scripts.get("scr_jump").unwrap().associated_data,
+            Some("fall();".to_string())
+        );
+
+        // and serializing won't overwrite theirs unless we ask it to
+        assert!(yyp_boss.serialize().is_err());
+        let report = yyp_boss
+            .serialize_with(ConflictResolution::Overwrite)
+            .unwrap();
+        assert_eq!(report.overwritten, changes.conflicts);
+        assert_eq!(fs::read_to_string(&gml).unwrap(), "fall();");
+
+        fs::remove_dir_all(root.join("scripts/scr_jump")).unwrap();
+        let changes = yyp_boss.apply_external_changes(&[gml]);
+        assert_eq!(changes.removed.len(), 1);
+        assert!(yyp_boss.scripts.get("scr_jump").is_none());
+        assert!(yyp_boss.vfs.resource_exists("scr_jump", Resource::Script) == false);
+    }
+
+    #[test]
+    fn gms2_added_resource() {
+        let root = TempDirectory::new();
+        let mut yyp_boss = root.yyp_boss();
+        yyp_boss.serialize().unwrap();
+
+        let yyp_path = root.join("project.yyp");
+        let yy_path = root.join("scripts/scr_run/scr_run.yy");
+        let base_yyp = yyp_boss.yyp().clone();
+        let gms2_writes = |folder: &str, listed: bool| {
+            let folder = ViewPath {
+                name: folder.to_string(),
+                path: Vfs::root_folder().join(folder),
+            };
+            let script = Script {
+                common_data: CommonData::new("scr_run".to_string()),
+                parent: folder.clone(),
+                ..Script::default()
+            };
+            fs::create_dir_all(yy_path.parent().unwrap()).unwrap();
+            utils::serialize_json(&yy_path, &script).unwrap();
+            fs::write(root.join("scripts/scr_run/scr_run.gml"), "run();").unwrap();
+
+            let mut yyp = base_yyp.clone();
+            yyp.folders = vec![YypFolder {
+                folder_path: folder.path.clone(),
+                common_data: CommonData {
+                    name: folder.name,
+                    ..Default::default()
+                },
+            }];
+            yyp.resources.clear();
+            if listed {
+                yyp.resources.push(
+                    ResourceDescriptor::new(Resource::Script, folder.path.clone())
+                        .to_yyp_resource("scr_run"),
+                );
+            }
+            utils::serialize_json(&yyp_path, &yyp).unwrap();
+
+            folder.path
+        };
+
+        // gms2 makes a folder with a script in it...
+        let scripts = gms2_writes("Scripts", true);
+        let changes = yyp_boss.apply_external_changes(&[yyp_path.clone(), yy_path.clone()]);
+        assert_eq!(
+            changes.added,
+            vec![ExternalResource {
+                resource: Resource::Script,
+                name: "scr_run".to_string(),
+            }]
+        );
+        assert_eq!(changes.added_folders, vec![scripts.clone()]);
+        assert!(yyp_boss.scripts.get("scr_run").is_some());
+        assert!(yyp_boss
+            .vfs
+            .get_folder(&scripts)
+            .unwrap()
+            .files
+            .contains_name("scr_run"));
+
+        // ...which we keep when we serialize
+        yyp_boss.serialize().unwrap();
+        let yyp: yy_typings::Yyp = utils::deserialize_json_tc(&yyp_path, yyp_boss.tcu()).unwrap();
+        assert!(yyp.resources.iter().any(|v| v.id.name == "scr_run"));
+
+        // ...and then renames the folder, moving the script with it
+        let code = gms2_writes("Code", true);
+        let changes = yyp_boss.apply_external_changes(&[yyp_path.clone(), yy_path.clone()]);
+        assert_eq!(changes.reloaded.len(), 1);
+        assert_eq!(changes.added_folders, vec![code.clone()]);
+        assert_eq!(changes.removed_folders, vec![scripts.clone()]);
+        assert!(yyp_boss.vfs.get_folder(&scripts).is_none());
+        assert!(yyp_boss
+            .vfs
+            .get_folder(&code)
+            .unwrap()
+            .files
+            .contains_name("scr_run"));
+
+        // ...and then takes the script out of the yyp
+        gms2_writes("Code", false);
+        let changes = yyp_boss.apply_external_changes(&[yyp_path]);
+        assert_eq!(changes.removed.len(), 1);
+        assert!(yyp_boss.vfs.resource_exists("scr_run", Resource::Script) == false);
+    }
+}
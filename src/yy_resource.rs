@@ -7,9 +7,9 @@ use std::{
 use yy_typings::{FilesystemPath, TrailingCommaUtility, ViewPath};
 
 pub trait YyResource:
-    Serialize + for<'de> Deserialize<'de> + Clone + Default + PartialEq + 'static
+    Serialize + for<'de> Deserialize<'de> + Clone + Default + PartialEq + Send + Sync + 'static
 {
-    type AssociatedData: Debug + Clone + PartialEq + Default;
+    type AssociatedData: Debug + Clone + PartialEq + Default + Send + Sync;
     const SUBPATH_NAME: &'static str;
     const RESOURCE: Resource;
 
@@ -1,22 +1,64 @@
 use super::{
     directory_manager::DirectoryManager,
-    dirty_handler::{DirtyDrain, DirtyHandler},
+    dirty_handler::DirtyHandler,
+    fingerprint::{self, Fingerprint},
+    staging::Staging,
     YyResource,
 };
-use crate::YyResourceHandlerError;
+use crate::{
+    utils, yyp_boss::TCU, ConflictResolution, FileChanges, FileHolder, FileSerializationError,
+    YyResourceHandlerError,
+};
 use anyhow::Result as AnyResult;
 use log::{error, info};
+use once_cell::sync::OnceCell;
 use std::{
     collections::HashMap,
-    fs,
+    fs, io,
     path::{Path, PathBuf},
 };
-use yy_typings::{FilesystemPath, TrailingCommaUtility, ViewPath};
+use yy_typings::{TrailingCommaUtility, ViewPath};
+
+/// Writes one resource into its staged directory. See [`YyResourceHandler::serialize`].
+///
+/// [`YyResourceHandler::serialize`]: struct.YyResourceHandler.html#method.serialize
+pub(crate) type SerializeJob<'a> = Box<dyn FnOnce() -> AnyResult<()> + Send + 'a>;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct YyResourceHandler<T: YyResource> {
     resources: HashMap<String, YyResourceData<T>>,
+    lazy_resources: HashMap<String, LazyResource<T>>,
     dirty_handler: DirtyHandler<String, Vec<PathBuf>>,
+    fingerprints: HashMap<String, Fingerprint>,
+}
+
+/// A resource in a lazy project whose yy file hasn't been read into `resources` yet. It's read
+/// the first time it's asked for, and moved into `resources` the first time it's changed.
+#[derive(Debug, PartialEq, Clone)]
+struct LazyResource<T: YyResource> {
+    yy_path: PathBuf,
+    loaded: OnceCell<(YyResourceData<T>, Fingerprint)>,
+}
+
+impl<T: YyResource> LazyResource<T> {
+    fn get(&self) -> Option<&(YyResourceData<T>, Fingerprint)> {
+        self.loaded
+            .get_or_try_init(|| {
+                let fingerprint = Fingerprint::of(self.yy_path.parent().unwrap())
+                    .map_err(|e| FileSerializationError::Io(e.to_string()))?;
+                let yy_resource: T = utils::deserialize_json_tc(&self.yy_path, &TCU)?;
+
+                Ok::<_, FileSerializationError>((
+                    YyResourceData {
+                        yy_resource,
+                        associated_data: None,
+                    },
+                    fingerprint,
+                ))
+            })
+            .map_err(|e| error!("couldn't read {:?}...{}", self.yy_path, e))
+            .ok()
+    }
 }
 
 impl<T: YyResource> Default for YyResourceHandler<T> {
@@ -29,7 +71,9 @@ impl<T: YyResource> YyResourceHandler<T> {
     pub(crate) fn new() -> Self {
         Self {
             resources: HashMap::new(),
+            lazy_resources: HashMap::new(),
             dirty_handler: DirtyHandler::new_assoc(),
+            fingerprints: HashMap::new(),
         }
     }
 
@@ -37,13 +81,17 @@ impl<T: YyResource> YyResourceHandler<T> {
     ///
     /// This operation is used to `add` or to `replace` the resource. If it is used
     /// to replace a resource, the resource will be returned.
+    ///
+    /// The resource might not have its associated data, such as when we put back a resource whose
+    /// associated data we couldn't load. In that case, only its yy file is written when we serialize.
     pub(crate) fn set(
         &mut self,
         value: T,
-        associated_data: T::AssociatedData,
+        associated_data: Option<T::AssociatedData>,
     ) -> Option<YyResourceData<T>> {
         let name = value.name().to_owned();
-        let ret = self.insert_resource(value, Some(associated_data));
+        self.realize_for_change(&name);
+        let ret = self.insert_resource(value, associated_data);
 
         if let Some(old) = &ret {
             self.dirty_handler
@@ -61,9 +109,35 @@ impl<T: YyResource> YyResourceHandler<T> {
     /// there may not be any associated data returned. You can request that data to be
     /// loaded using [`load_resource_associated_data`].
     ///
+    /// In a project opened with [`YypBoss::lazy`], this reads the resource's yy file the first
+    /// time it's asked for.
+    ///
     /// [`load_resource_associated_data`]: #method.load_resource_associated_data
+    /// [`YypBoss::lazy`]: ../struct.YypBoss.html#method.lazy
     pub fn get(&self, name: &str) -> Option<&YyResourceData<T>> {
-        self.resources.get(name)
+        match self.resources.get(name) {
+            Some(v) => Some(v),
+            None => self.lazy_resources.get(name)?.get().map(|(v, _)| v),
+        }
+    }
+
+    /// Returns the name of every resource, including those which haven't been read yet in a
+    /// lazy project.
+    pub fn names(&self) -> impl Iterator<Item = &String> {
+        self.resources.keys().chain(self.lazy_resources.keys())
+    }
+
+    /// Reads in every resource which hasn't been read yet in a project opened with
+    /// [`YypBoss::lazy`], so that [`resources`] lists them all. Resources which can't be read
+    /// are logged and left unread.
+    ///
+    /// [`YypBoss::lazy`]: ../struct.YypBoss.html#method.lazy
+    /// [`resources`]: #method.resources
+    pub fn load_all(&mut self) {
+        let names: Vec<String> = self.lazy_resources.keys().cloned().collect();
+        for name in names {
+            self.realize(&name);
+        }
     }
 
     /// Returns an **mutable** reference to a resource's data, if it exists.
@@ -85,11 +159,13 @@ impl<T: YyResource> YyResourceHandler<T> {
     /// [`load_resource_associated_data`]: #method.load_resource_associated_data
     /// [`mark_for_serialization`]: #method.mark_for_serialization
     pub unsafe fn get_mut(&mut self, name: &str) -> Option<&mut YyResourceData<T>> {
+        self.realize_for_change(name);
         self.resources.get_mut(name)
     }
 
     /// Attempts to mark a resource for serialization, and returns if it was succesfully marked.
     pub fn mark_for_serialization(&mut self, name: &str) -> Result<(), YyResourceHandlerError> {
+        self.realize_for_change(name);
         if let Some(inner) = self.resources.get(name) {
             if inner.associated_data.is_some() {
                 if self
@@ -115,11 +191,25 @@ impl<T: YyResource> YyResourceHandler<T> {
         }
     }
 
+    /// Marks a resource for serialization, and removes `files`, relative to the resource's
+    /// directory, when it's serialized. This is for edits after which the resource no longer
+    /// writes files it wrote before.
+    pub(crate) fn mark_for_cleanup(&mut self, name: &str, files: &[PathBuf]) {
+        self.realize_for_change(name);
+        self.dirty_handler
+            .replace_associated(name.to_owned(), |mut holder| {
+                for file in files {
+                    holder.push(file.clone());
+                }
+            });
+    }
+
     pub(crate) fn edit_parent(
         &mut self,
         name: &str,
         parent: ViewPath,
     ) -> Result<(), YyResourceHandlerError> {
+        self.realize_for_change(name);
         if let Some(inner) = self.resources.get_mut(name) {
             inner.yy_resource.set_parent_view_path(parent);
             self.dirty_handler.edit(name.to_string());
@@ -130,6 +220,26 @@ impl<T: YyResource> YyResourceHandler<T> {
         }
     }
 
+    /// Edits a resource's yy file and marks it for serialization. Unlike [`mark_for_serialization`],
+    /// the associated data does not need to be loaded, since it isn't touched.
+    ///
+    /// [`mark_for_serialization`]: #method.mark_for_serialization
+    pub(crate) fn edit_yy(
+        &mut self,
+        name: &str,
+        edit: impl FnOnce(&mut T),
+    ) -> Result<(), YyResourceHandlerError> {
+        self.realize_for_change(name);
+        if let Some(inner) = self.resources.get_mut(name) {
+            edit(&mut inner.yy_resource);
+            self.dirty_handler.edit(name.to_string());
+
+            Ok(())
+        } else {
+            Err(YyResourceHandlerError::ResourceNotFound)
+        }
+    }
+
     pub(crate) fn rename(
         &mut self,
         current_name: &str,
@@ -137,6 +247,7 @@ impl<T: YyResource> YyResourceHandler<T> {
         dir_path: &Path,
         tcu: &TrailingCommaUtility,
     ) -> Result<(), YyResourceHandlerError> {
+        self.realize_for_change(current_name);
         if let Some(mut inner) = self.resources.remove(current_name) {
             // Try to load this guy up...
             if inner.associated_data.is_none() {
@@ -179,6 +290,7 @@ impl<T: YyResource> YyResourceHandler<T> {
         dir_path: &Path,
         tcu: &TrailingCommaUtility,
     ) -> Option<(T, Option<T::AssociatedData>)> {
+        self.realize_for_change(value);
         let ret = self.resources.remove(value);
         if let Some(ret) = ret {
             self.dirty_handler.remove(value);
@@ -215,6 +327,7 @@ impl<T: YyResource> YyResourceHandler<T> {
         root: &Path,
         tcu: &TrailingCommaUtility,
     ) -> Result<&T::AssociatedData, YyResourceHandlerError> {
+        self.realize(resource_name);
         if let Some(resource) = self.resources.get_mut(resource_name) {
             let associated_data = resource.yy_resource.deserialize_associated_data(
                 &root.join(resource.yy_resource.relative_yy_directory()),
@@ -236,6 +349,11 @@ impl<T: YyResource> YyResourceHandler<T> {
         &mut self,
         resource_name: &str,
     ) -> Result<(), YyResourceHandlerError> {
+        if self.lazy_resources.contains_key(resource_name) {
+            // it has nothing to unload
+            return Ok(());
+        }
+
         if let Some(resource) = self.resources.get_mut(resource_name) {
             resource.associated_data = None;
             Ok(())
@@ -244,89 +362,337 @@ impl<T: YyResource> YyResourceHandler<T> {
         }
     }
 
-    /// Loads the resource in on startup. We don't track associated data by default,
-    /// and we don't mark the resource as dirty.
-    pub(crate) fn load_on_startup(&mut self, value: T) {
-        self.insert_resource(value, None);
+    /// Replaces a resource with what's on disk, such as after Gms2 edited it. Unlike [`set`],
+    /// the resource isn't marked as dirty, since it's already on disk.
+    ///
+    /// [`set`]: #method.set
+    pub(crate) fn reload(
+        &mut self,
+        value: T,
+        associated_data: Option<T::AssociatedData>,
+        fingerprint: Fingerprint,
+    ) {
+        self.lazy_resources.remove(value.name());
+        self.fingerprints
+            .insert(value.name().to_owned(), fingerprint);
+        self.insert_resource(value, associated_data);
+    }
+
+    /// Forgets a resource which is already gone from disk, without marking it for removal.
+    pub(crate) fn unload(&mut self, name: &str) {
+        self.resources.remove(name);
+        self.lazy_resources.remove(name);
+        self.fingerprints.remove(name);
+    }
+
+    /// Returns `true` if the resource has changes, or a removal, which haven't been serialized.
+    pub(crate) fn is_dirty(&self, name: &str) -> bool {
+        self.dirty_handler
+            .resources_to_reserialize()
+            .contains_key(name)
+            || self.dirty_handler.resources_to_remove().contains_key(name)
+    }
+
+    /// Loads the resource in on startup. We only have associated data if we were asked to scan
+    /// for it, and we don't mark the resource as dirty.
+    ///
+    /// The fingerprint is what the resource's directory held when we loaded it, so we can tell
+    /// if anything else changes it before we serialize.
+    pub(crate) fn load_on_startup(
+        &mut self,
+        value: T,
+        associated_data: Option<T::AssociatedData>,
+        fingerprint: Fingerprint,
+    ) {
+        self.fingerprints
+            .insert(value.name().to_owned(), fingerprint);
+        self.insert_resource(value, associated_data);
+    }
+
+    /// Lists a resource in a lazy project without reading it. Its yy file, at `yy_path`, is read
+    /// the first time the resource is asked for.
+    pub(crate) fn load_lazily(&mut self, name: String, yy_path: PathBuf) {
+        self.lazy_resources.insert(
+            name,
+            LazyResource {
+                yy_path,
+                loaded: OnceCell::new(),
+            },
+        );
+    }
+
+    /// Moves a resource which hasn't been read yet in a lazy project into `resources`, reading it
+    /// if it hasn't been asked for yet, so that it can be changed.
+    fn realize(&mut self, name: &str) {
+        let lazy = match self.lazy_resources.get(name) {
+            Some(v) => v,
+            None => return,
+        };
+
+        // if we can't read it, we leave it alone, and it'll act like it isn't here
+        if let Some((data, fingerprint)) = lazy.get().cloned() {
+            self.lazy_resources.remove(name);
+            self.fingerprints.insert(name.to_owned(), fingerprint);
+            self.resources.insert(name.to_owned(), data);
+        }
+    }
+
+    /// Realizes a resource which is about to be changed, and hashes what it holds on disk, so that
+    /// we can tell what we changed if it has to be merged when we serialize.
+    fn realize_for_change(&mut self, name: &str) {
+        self.realize(name);
+        if let Some(fingerprint) = self.fingerprints.get_mut(name) {
+            if let Err(e) = fingerprint.hash_unchanged() {
+                error!("couldn't hash {} before changing it...{}", name, e);
+            }
+        }
     }
 
-    /// Writes all of the resources to disk, and cleans up excess files.
-    pub(crate) fn serialize(&mut self, directory_manager: &DirectoryManager) -> AnyResult<()> {
-        let DirtyDrain {
-            resources_to_remove,
-            resources_to_reserialize,
-            associated_values,
-        } = self.dirty_handler.drain_all();
+    /// Returns the resources which would be written or removed when we serialize, but which
+    /// changed on disk since we loaded or last wrote them.
+    ///
+    /// A resource which was never on disk is expected to still not be there.
+    pub(crate) fn diverged(&self, directory_manager: &DirectoryManager) -> io::Result<Vec<String>> {
+        let base_path = directory_manager.resource_file(Path::new(T::SUBPATH_NAME));
+        let dirty = self
+            .dirty_handler
+            .resources_to_reserialize()
+            .keys()
+            .chain(self.dirty_handler.resources_to_remove().keys());
+
+        let mut output = Vec::new();
+        for name in dirty {
+            let fingerprint = self.fingerprints.get(name).cloned().unwrap_or_default();
+            if fingerprint.diverged(&base_path.join(name))? {
+                output.push(name.clone());
+            }
+        }
+        output.sort();
+
+        Ok(output)
+    }
+
+    /// Stages all of the dirty resources to be written, and excess files to be cleaned up.
+    ///
+    /// Writing each resource into its staged directory is pushed onto `jobs`, which can be run in
+    /// any order, or all at once. Anything which goes wrong is pushed onto `errors`, and the rest
+    /// of the resources are still staged.
+    ///
+    /// Nothing is written until the staging is committed, and the resources stay dirty until
+    /// [`mark_serialized`] is called.
+    ///
+    /// [`mark_serialized`]: #method.mark_serialized
+    pub(crate) fn serialize<'a>(
+        &'a self,
+        directory_manager: &DirectoryManager,
+        staging: &mut Staging,
+        jobs: &mut Vec<SerializeJob<'a>>,
+        errors: &mut Vec<anyhow::Error>,
+    ) {
+        let base_path = directory_manager.resource_file(Path::new(T::SUBPATH_NAME));
 
         // Remove files or folders...
-        if let Some(ass_values) = associated_values {
-            for (name, mut filepaths) in ass_values {
-                let base_path = directory_manager
-                    .resource_file(Path::new(T::SUBPATH_NAME))
-                    .join(name);
-
-                for fpath in filepaths.drain(..) {
-                    let path = base_path.join(fpath);
-                    if path.is_dir() {
-                        match fs::remove_dir_all(&path) {
-                            Ok(()) => {
-                                info!("removed folder {:?}", path);
-                            }
-                            Err(e) => {
-                                error!("couldn't remove folder {:#?}, {:#?}", path, e);
-                            }
-                        }
-                    } else {
-                        match fs::remove_file(&path) {
-                            Ok(()) => {
-                                info!("removed file {:?}", path);
-                            }
-                            Err(e) => {
-                                error!("couldn't remove file {:#?}, {:#?}", path, e);
-                            }
-                        }
+        if let Some(ass_values) = self.dirty_handler.associated_values() {
+            for (name, filepaths) in ass_values {
+                let directory = match staging.directory(&base_path.join(name)) {
+                    Ok(v) => v,
+                    Err(e) => {
+                        errors.push(e.into());
+                        continue;
                     }
-                }
+                };
+
+                clean_up(&directory, filepaths);
             }
         }
 
         // Removes the resources!
-        for (resource_to_remove, _) in resources_to_remove {
-            let path = FilesystemPath::new_path(T::SUBPATH_NAME, &resource_to_remove);
-            let yy_path = directory_manager.resource_file(&path);
-            fs::remove_dir_all(yy_path.parent().unwrap())?;
+        for resource_to_remove in self.dirty_handler.resources_to_remove().keys() {
+            staging.remove(&base_path.join(resource_to_remove));
         }
 
         // Finally, reserialize resources
-        for (resource_to_reserialize, _) in resources_to_reserialize {
+        for resource_to_reserialize in self.dirty_handler.resources_to_reserialize().keys() {
             let resource = self
                 .resources
-                .get(&resource_to_reserialize)
+                .get(resource_to_reserialize)
                 .expect("This should always be valid.");
 
             let yy_path =
                 directory_manager.resource_file(&resource.yy_resource.relative_yy_filepath());
+            let directory = match staging.directory(yy_path.parent().expect("impossible")) {
+                Ok(v) => v,
+                Err(e) => {
+                    errors.push(e.into());
+                    continue;
+                }
+            };
 
-            let parent_dir = yy_path.parent().expect("impossible");
-            fs::create_dir_all(parent_dir)?;
+            jobs.push(Box::new(move || {
+                if let Some(associated_data) = &resource.associated_data {
+                    resource
+                        .yy_resource
+                        .serialize_associated_data(&directory, associated_data)?;
+                } else {
+                    error!(
+                        "{} {} was marked for serialization but we didn't have its associated data",
+                        T::RESOURCE,
+                        resource.yy_resource.name()
+                    )
+                }
+                let output_string = yy_typings::serialize_file(&resource.yy_resource);
+                std::fs::write(directory.join(yy_path.file_name().unwrap()), output_string)
+                    .map_err(|e| crate::FileSerializationError::Io(e.to_string()))?;
 
-            if let Some(associated_data) = &resource.associated_data {
-                resource
-                    .yy_resource
-                    .serialize_associated_data(parent_dir, associated_data)?;
-            } else {
-                error!(
-                    "{} {} was marked for serialization but we didn't have its associated data",
-                    T::RESOURCE,
-                    resource.yy_resource.name()
-                )
+                Ok(())
+            }));
+        }
+    }
+
+    /// Lists the files which [`serialize`] would write or delete, relative to the root of the
+    /// project.
+    ///
+    /// Removed resources are listed from disk, and yy files are compared with what's on disk in
+    /// memory. Only associated data is written, into `staging`, which is never committed, to find
+    /// out which of its files would change.
+    ///
+    /// [`serialize`]: #method.serialize
+    pub(crate) fn plan(
+        &self,
+        directory_manager: &DirectoryManager,
+        staging: &mut Staging,
+    ) -> AnyResult<FileChanges> {
+        let root_directory = directory_manager.root_directory();
+        let base_path = directory_manager.resource_file(Path::new(T::SUBPATH_NAME));
+        let mut output = FileChanges::default();
+
+        for name in self.dirty_handler.resources_to_remove().keys() {
+            let path = base_path.join(name);
+            staging.remove(&path);
+            output.extend(staging.changes_at(&path, root_directory)?);
+        }
+
+        for name in self.dirty_handler.resources_to_reserialize().keys() {
+            let resource = self
+                .resources
+                .get(name)
+                .expect("This should always be valid.");
+            let yy_path =
+                directory_manager.resource_file(&resource.yy_resource.relative_yy_filepath());
+            let directory = yy_path.parent().expect("impossible");
+
+            let cleanup = self
+                .dirty_handler
+                .associated_values()
+                .and_then(|v| v.get(name));
+            if resource.associated_data.is_some() || cleanup.is_some() {
+                let staged = staging.directory(directory)?;
+                if let Some(filepaths) = cleanup {
+                    clean_up(&staged, filepaths);
+                }
+                if let Some(associated_data) = &resource.associated_data {
+                    resource
+                        .yy_resource
+                        .serialize_associated_data(&staged, associated_data)?;
+                }
+
+                // the staged yy is still the one on disk, so it's never listed here
+                output.extend(staging.changes_at(directory, root_directory)?);
+            }
+
+            let yy = yy_typings::serialize_file(&resource.yy_resource);
+            if fs::read_to_string(&yy_path).map_or(true, |v| v != yy) {
+                output.written.push(
+                    yy_path
+                        .strip_prefix(root_directory)
+                        .unwrap_or(&yy_path)
+                        .to_owned(),
+                );
             }
-            let output_string = yy_typings::serialize_file(&resource.yy_resource);
-            std::fs::write(yy_path, output_string)
-                .map_err(|e| crate::FileSerializationError::Io(e.to_string()))?;
         }
 
-        Ok(())
+        output.written.sort();
+        output.deleted.sort();
+
+        Ok(output)
+    }
+
+    /// Handles the `diverged` resources, which changed on disk, with `resolution`, once the
+    /// [`serialize`] jobs have run, and returns the ones which were skipped.
+    /// [`ConflictResolution::Refuse`] is treated as [`ConflictResolution::Overwrite`], since it's
+    /// up to the caller to refuse.
+    ///
+    /// [`serialize`]: #method.serialize
+    /// [`ConflictResolution::Refuse`]: ../enum.ConflictResolution.html#variant.Refuse
+    /// [`ConflictResolution::Overwrite`]: ../enum.ConflictResolution.html#variant.Overwrite
+    pub(crate) fn resolve_conflicts(
+        &self,
+        directory_manager: &DirectoryManager,
+        staging: &mut Staging,
+        diverged: &[String],
+        resolution: ConflictResolution,
+    ) -> AnyResult<Vec<String>> {
+        let base_path = directory_manager.resource_file(Path::new(T::SUBPATH_NAME));
+
+        let mut skipped = Vec::new();
+        for name in diverged {
+            let path = base_path.join(name);
+            let keep = match resolution {
+                ConflictResolution::Refuse | ConflictResolution::Overwrite => true,
+                ConflictResolution::Skip => false,
+                ConflictResolution::Merge => {
+                    // we can't merge a removal with a change
+                    self.resources.contains_key(name)
+                        && fingerprint::merge(
+                            &self.fingerprints.get(name).cloned().unwrap_or_default(),
+                            &path,
+                            &staging.directory(&path)?,
+                        )?
+                }
+            };
+
+            if keep == false {
+                staging.unstage(&path)?;
+                skipped.push(name.clone());
+            }
+        }
+
+        Ok(skipped)
+    }
+
+    /// Clears every dirty resource except the `skipped` ones, once what [`serialize`] staged has
+    /// been committed, and remembers what's now on disk.
+    ///
+    /// [`serialize`]: #method.serialize
+    pub(crate) fn mark_serialized(
+        &mut self,
+        directory_manager: &DirectoryManager,
+        skipped: &[String],
+    ) {
+        let base_path = directory_manager.resource_file(Path::new(T::SUBPATH_NAME));
+
+        for name in self.dirty_handler.resources_to_remove().keys() {
+            if skipped.contains(name) == false {
+                self.fingerprints.remove(name);
+            }
+        }
+        for name in self.dirty_handler.resources_to_reserialize().keys() {
+            if skipped.contains(name) {
+                continue;
+            }
+
+            match Fingerprint::of(&base_path.join(name)) {
+                Ok(fingerprint) => {
+                    self.fingerprints.insert(name.clone(), fingerprint);
+                }
+                Err(e) => {
+                    error!("couldn't read {} back after writing it...{}", name, e);
+                    self.fingerprints.remove(name);
+                }
+            }
+        }
+
+        self.dirty_handler.retain(|name| skipped.contains(name));
     }
 
     /// Wrapper around inserting the resource into `self.resources`.
@@ -344,18 +710,35 @@ impl<T: YyResource> YyResourceHandler<T> {
         )
     }
 
+    pub(crate) fn dirty_handler(&self) -> &DirtyHandler<String, Vec<PathBuf>> {
+        &self.dirty_handler
+    }
+
     /// Get a reference to the yy resource handler's resources.
+    ///
+    /// In a project opened with [`YypBoss::lazy`], this only holds resources which have been
+    /// changed, so any which have only been listed or read with [`get`] are left out. Call
+    /// [`load_all`] before iterating over it, or use [`names`] to list every resource instead.
+    ///
+    /// [`YypBoss::lazy`]: ../struct.YypBoss.html#method.lazy
+    /// [`get`]: #method.get
+    /// [`load_all`]: #method.load_all
+    /// [`names`]: #method.names
     pub fn resources(&self) -> &HashMap<String, YyResourceData<T>> {
         &self.resources
     }
 
-    /// Get a mutable reference to the yy resource handler's resources.
+    /// Get a mutable reference to the yy resource handler's resources. Every resource is read in
+    /// first, as in [`load_all`].
+    ///
+    /// [`load_all`]: #method.load_all
     pub fn resources_mut(&mut self) -> &mut HashMap<String, YyResourceData<T>> {
+        self.load_all();
         &mut self.resources
     }
 }
 
-#[derive(Default, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Default, PartialEq, Eq, PartialOrd, Ord, Clone)]
 pub struct YyResourceData<T: YyResource> {
     pub yy_resource: T,
     pub associated_data: Option<T::AssociatedData>,
@@ -385,3 +768,80 @@ impl<'a, T: YyResource> IntoIterator for &'a YyResourceHandler<T> {
         self.resources.values()
     }
 }
+
+/// Removes the files or folders at `filepaths`, relative to `directory`, which a resource left
+/// behind when it was replaced.
+fn clean_up(directory: &Path, filepaths: &[PathBuf]) {
+    for fpath in filepaths {
+        let path = directory.join(fpath);
+        if path.is_dir() {
+            match fs::remove_dir_all(&path) {
+                Ok(()) => {
+                    info!("removed folder {:?}", path);
+                }
+                Err(e) => {
+                    error!("couldn't remove folder {:#?}, {:#?}", path, e);
+                }
+            }
+        } else if path.exists() {
+            match fs::remove_file(&path) {
+                Ok(()) => {
+                    info!("removed file {:?}", path);
+                }
+                Err(e) => {
+                    error!("couldn't remove file {:#?}, {:#?}", path, e);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TempDirectory;
+    use yy_typings::{CommonData, Script};
+
+    #[test]
+    fn lazy_resources() {
+        let root = TempDirectory::new();
+        let mut yyp_boss = root.yyp_boss();
+
+        let script = Script {
+            common_data: CommonData::new("scr_lazy".to_string()),
+            parent: yyp_boss.project_metadata().root_file,
+            ..Script::default()
+        };
+        yyp_boss
+            .add_resource(script.clone(), "lazy();".to_string())
+            .unwrap();
+        yyp_boss.serialize().unwrap();
+
+        // reading it doesn't move it in
+        let mut handler: YyResourceHandler<Script> = YyResourceHandler::new();
+        handler.load_lazily(
+            "scr_lazy".to_string(),
+            root.join("scripts/scr_lazy/scr_lazy.yy"),
+        );
+        assert_eq!(handler.names().count(), 1);
+        assert_eq!(handler.get("scr_lazy").unwrap().yy_resource, script);
+        assert!(handler.resources().is_empty());
+
+        // but changing it does, and it hasn't diverged since we read it
+        handler
+            .edit_parent("scr_lazy", yyp_boss.project_metadata().root_file)
+            .unwrap();
+        assert_eq!(handler.resources().len(), 1);
+        assert!(handler
+            .diverged(&yyp_boss.directory_manager)
+            .unwrap()
+            .is_empty());
+
+        handler.load_lazily(
+            "scr_missing".to_string(),
+            root.join("scripts/scr_missing/scr_missing.yy"),
+        );
+        assert!(handler.get("scr_missing").is_none());
+        assert!(handler.remove("scr_missing", &root, &TCU).is_none());
+    }
+}
@@ -1,22 +1,41 @@
 use crate::{
-    directory_manager::DirectoryManager, errors::*, folders::*, utils, FileSerializationError,
-    ProjectMetadata, Resource, YyResource, YyResourceData, YyResourceHandler,
+    audio_groups::AudioGroupManager,
+    directory_manager::DirectoryManager,
+    errors::*,
+    fingerprint::Fingerprint,
+    folders::*,
+    history::{ErasedResource, History, Operation},
+    included_files::IncludedFilesHandler,
+    serialize_plan::{ConflictResolution, ResourcePlan, SerializePlan, SerializeReport, YypDiff},
+    staging::{self, Staging},
+    texture_groups::TextureGroupManager,
+    typings::{AnimationCurve, Extension, Font, Path as YyPath, Room, Sequence, Timeline},
+    utils,
+    watcher::{self, ExternalChange, ExternalChanges, ExternalResource},
+    yy_resource_handler::SerializeJob,
+    FileSerializationError, ProjectMetadata, Resource, SpriteFrameLayers, ViewPathLocationExt,
+    YyResource, YyResourceData, YyResourceHandler,
 };
 use anyhow::Result as AnyResult;
-use std::{fs, path::Path};
+use rayon::prelude::*;
+use std::{
+    collections::BTreeSet,
+    fs,
+    path::{Path, PathBuf},
+};
 use yy_typings::{
-    AnimationCurve, Extension, Font, Note, Object, Path as YyPath, ResourceNameValidator, Room,
-    Script, Sequence, Shader, Sound, Sprite, TexturePath, TileSet, Timeline, TrailingCommaUtility,
-    ViewPath, ViewPathLocation, Yyp, YypResource,
+    EventType, FilesystemPath, FrameId, Note, Object, ResourceNameValidator, RoomOrderId, Script,
+    Shader, Sound, Sprite, TileSet, TrailingCommaUtility, ViewPath, ViewPathLocation, Yyp,
+    YypFolder, YypIncludedFile, YypResource,
 };
 
-static TCU: once_cell::sync::Lazy<TrailingCommaUtility> =
+pub(crate) static TCU: once_cell::sync::Lazy<TrailingCommaUtility> =
     once_cell::sync::Lazy::new(TrailingCommaUtility::new);
 
-static RNV: once_cell::sync::Lazy<ResourceNameValidator> =
+pub(crate) static RNV: once_cell::sync::Lazy<ResourceNameValidator> =
     once_cell::sync::Lazy::new(ResourceNameValidator::new);
 
-#[derive(Debug, PartialEq, Default)]
+#[derive(Debug, PartialEq, Default, Clone)]
 pub struct YypBoss {
     pub directory_manager: DirectoryManager,
     pub sprites: YyResourceHandler<Sprite>,
@@ -36,7 +55,11 @@ pub struct YypBoss {
     pub timelines: YyResourceHandler<Timeline>,
 
     pub vfs: Vfs,
+    pub included_files: IncludedFilesHandler,
+    pub texture_groups: TextureGroupManager,
+    pub audio_groups: AudioGroupManager,
     yyp: Yyp,
+    history: History,
 }
 
 impl YypBoss {
@@ -46,52 +69,62 @@ impl YypBoss {
     ) -> Result<YypBoss, StartupError> {
         let mut yyp_boss = Self::without_resources(path_to_yyp)?;
 
-        // load in all of our resources...
-        for yyp_resource in yyp_boss.yyp.resources.clone().into_iter() {
-            let path_as_str = yyp_resource.id.path.to_string_lossy();
-
-            let subpath = path_as_str
-                .split('/')
-                .next()
-                .ok_or_else(|| StartupError::BadResourceListing(yyp_resource.id.path.clone()))?;
-
-            let resource = Resource::parse_subpath(subpath)
-                .ok_or_else(|| StartupError::BadResourceListing(yyp_resource.id.path.clone()))?;
-            let assoc = resources_to_scan.contains(&resource);
-
-            match resource {
-                Resource::Sprite => load_in_file::<Sprite>(yyp_resource, &mut yyp_boss, assoc),
-                Resource::Script => load_in_file::<Script>(yyp_resource, &mut yyp_boss, assoc),
-                Resource::Object => load_in_file::<Object>(yyp_resource, &mut yyp_boss, assoc),
-                Resource::Note => load_in_file::<Note>(yyp_resource, &mut yyp_boss, assoc),
-                Resource::Shader => load_in_file::<Shader>(yyp_resource, &mut yyp_boss, assoc),
-                Resource::AnimationCurve => {
-                    load_in_file::<AnimationCurve>(yyp_resource, &mut yyp_boss, assoc)
+        // read in all of our resources on the thread pool...
+        let root_directory = yyp_boss.directory_manager.root_directory().to_owned();
+        let read_in: Vec<Result<LoadInFile, StartupError>> = yyp_boss
+            .yyp
+            .resources
+            .par_iter()
+            .map(|yyp_resource| {
+                let resource = resource_of(yyp_resource)?;
+                let assoc = resources_to_scan.contains(&resource);
+
+                let root = &root_directory;
+                match resource {
+                    Resource::Sprite => read_in_file::<Sprite>(root, yyp_resource, assoc),
+                    Resource::Script => read_in_file::<Script>(root, yyp_resource, assoc),
+                    Resource::Object => read_in_file::<Object>(root, yyp_resource, assoc),
+                    Resource::Note => read_in_file::<Note>(root, yyp_resource, assoc),
+                    Resource::Shader => read_in_file::<Shader>(root, yyp_resource, assoc),
+                    Resource::AnimationCurve => {
+                        read_in_file::<AnimationCurve>(root, yyp_resource, assoc)
+                    }
+                    Resource::Room => read_in_file::<Room>(root, yyp_resource, assoc),
+                    Resource::Extension => read_in_file::<Extension>(root, yyp_resource, assoc),
+                    Resource::Font => read_in_file::<Font>(root, yyp_resource, assoc),
+                    Resource::Path => read_in_file::<YyPath>(root, yyp_resource, assoc),
+                    Resource::Sequence => read_in_file::<Sequence>(root, yyp_resource, assoc),
+                    Resource::Sound => read_in_file::<Sound>(root, yyp_resource, assoc),
+                    Resource::TileSet => read_in_file::<TileSet>(root, yyp_resource, assoc),
+                    Resource::Timeline => read_in_file::<Timeline>(root, yyp_resource, assoc),
                 }
-                Resource::Room => load_in_file::<Room>(yyp_resource, &mut yyp_boss, assoc),
-                Resource::Extension => {
-                    load_in_file::<Extension>(yyp_resource, &mut yyp_boss, assoc)
-                }
-                Resource::Font => load_in_file::<Font>(yyp_resource, &mut yyp_boss, assoc),
-                Resource::Path => load_in_file::<YyPath>(yyp_resource, &mut yyp_boss, assoc),
-                Resource::Sequence => load_in_file::<Sequence>(yyp_resource, &mut yyp_boss, assoc),
-                Resource::Sound => load_in_file::<Sound>(yyp_resource, &mut yyp_boss, assoc),
-                Resource::TileSet => load_in_file::<TileSet>(yyp_resource, &mut yyp_boss, assoc),
-                Resource::Timeline => load_in_file::<Timeline>(yyp_resource, &mut yyp_boss, assoc),
-            }?;
+            })
+            .collect();
+
+        // ...and then load them in the order of the yyp, so the Vfs and our errors never depend
+        // on which thread finished first
+        for load_in_file in read_in {
+            load_in_file?(&mut yyp_boss)?;
         }
 
         return Ok(yyp_boss);
 
-        fn load_in_file<T: YyResource>(
-            yyp_resource: YypResource,
-            yyp_boss: &mut YypBoss,
+        /// Finishes loading a resource which was read in on the thread pool.
+        type LoadInFile = Box<dyn FnOnce(&mut YypBoss) -> Result<(), StartupError> + Send>;
+
+        fn read_in_file<T: YyResource>(
+            root_directory: &Path,
+            yyp_resource: &YypResource,
             load_in_associated_data: bool,
-        ) -> Result<(), StartupError> {
-            let yy_file_path = yyp_boss
-                .directory_manager
-                .root_directory()
-                .join(yyp_resource.id.path);
+        ) -> Result<LoadInFile, StartupError> {
+            let yy_file_path = root_directory.join(&yyp_resource.id.path);
+
+            let fingerprint = Fingerprint::of(yy_file_path.parent().unwrap()).map_err(|e| {
+                StartupError::BadYyFile {
+                    filepath: yy_file_path.clone(),
+                    error: e.to_string(),
+                }
+            })?;
 
             let yy_file: T = utils::deserialize_json_tc(&yy_file_path, &TCU).map_err(|e| {
                 StartupError::BadYyFile {
@@ -100,31 +133,41 @@ impl YypBoss {
                 }
             })?;
 
-            yyp_boss
-                .vfs
-                .load_in_file(&yy_file)
-                .map_err(|e| StartupError::BadResourceTree {
-                    name: yy_file.name().to_owned(),
-                    error: e.to_string(),
+            let associated_data = load_in_associated_data.then(|| {
+                yy_file.deserialize_associated_data(
+                    &root_directory.join(yy_file.relative_yy_directory()),
+                    &TCU,
+                )
+            });
+
+            Ok(Box::new(move |yyp_boss: &mut YypBoss| {
+                yyp_boss
+                    .vfs
+                    .load_in_file(&yy_file)
+                    .map_err(|e| StartupError::BadResourceTree {
+                        name: yy_file.name().to_owned(),
+                        error: e.to_string(),
+                    })?;
+
+                let associated_data = associated_data.transpose().map_err(|e| {
+                    StartupError::BadAssociatedData(yy_file.name().to_owned(), e.into())
                 })?;
 
-            let name = yy_file.name().to_owned();
-            let root_path = yyp_boss.directory_manager.root_directory().to_owned();
-            let handler = T::get_handler_mut(yyp_boss);
-            handler.load_on_startup(yy_file);
+                T::get_handler_mut(yyp_boss).load_on_startup(yy_file, associated_data, fingerprint);
 
-            if load_in_associated_data {
-                if let Err(e) = handler.load_resource_associated_data(&name, &root_path, &TCU) {
-                    return Err(StartupError::BadAssociatedData(name, e));
-                }
-            }
-
-            Ok(())
+                Ok(())
+            }))
         }
     }
 
     /// Loads the yyp *without* any resources. This is very fast and ideal for quick edits.
+    ///
+    /// Any serialization which was interrupted partway through is rolled back first.
     pub fn without_resources<P: AsRef<Path>>(path_to_yyp: P) -> Result<YypBoss, StartupError> {
+        let directory_manager = DirectoryManager::new(path_to_yyp.as_ref())?;
+        staging::recover(directory_manager.root_directory())
+            .map_err(|e| StartupError::InterruptedSerialization(e.to_string()))?;
+
         let yyp: Yyp = utils::deserialize_json_tc(&path_to_yyp, &TCU).map_err(|e| match e {
             FileSerializationError::Serde(e) => StartupError::BadYypDeserialize(e),
             FileSerializationError::Io(error) => StartupError::BadYypPath {
@@ -156,10 +199,11 @@ impl YypBoss {
             return Err(StartupError::YypDoesNotMatch(requirement, r_actual));
         }
 
-        let directory_manager = DirectoryManager::new(path_to_yyp.as_ref())?;
-
         let mut yyp_boss = Self {
             vfs: Vfs::new(&yyp.common_data.name),
+            included_files: IncludedFilesHandler::new(&yyp.included_files),
+            texture_groups: TextureGroupManager::new(&yyp.texture_groups),
+            audio_groups: AudioGroupManager::new(&yyp.audio_groups),
             directory_manager,
             yyp,
             ..Self::default()
@@ -171,19 +215,84 @@ impl YypBoss {
         Ok(yyp_boss)
     }
 
+    /// Loads the yyp and lists every resource in it without deserializing them. Each resource's
+    /// parent is needed for the Vfs, so every yy file is still read from disk, but only its
+    /// `parent` field is parsed; the rest is deserialized the first time the resource is asked
+    /// for, such as with [`get_resource`]. Startup is faster than [`new`], but slower than
+    /// [`without_resources`], which reads no yy files at all.
+    ///
+    /// Changing a resource reads it in first. Associated data is loaded on demand, as it is
+    /// after [`new`].
+    ///
+    /// [`get_resource`]: #method.get_resource
+    /// [`without_resources`]: #method.without_resources
+    /// [`new`]: #method.new
+    pub fn lazy<P: AsRef<Path>>(path_to_yyp: P) -> Result<YypBoss, StartupError> {
+        let mut yyp_boss = Self::without_resources(path_to_yyp)?;
+
+        let root_directory = yyp_boss.directory_manager.root_directory().to_owned();
+        let listings: Vec<Result<(Resource, String, PathBuf, ViewPath), StartupError>> = yyp_boss
+            .yyp
+            .resources
+            .par_iter()
+            .map(|yyp_resource| {
+                let resource = resource_of(yyp_resource)?;
+
+                let yy_file_path = root_directory.join(&yyp_resource.id.path);
+                let parent: ViewPath =
+                    utils::deserialize_json_field_tc(&yy_file_path, "parent", &TCU).map_err(
+                        |e| StartupError::BadYyFile {
+                            filepath: yy_file_path.clone(),
+                            error: e.to_string(),
+                        },
+                    )?;
+
+                Ok((resource, yyp_resource.id.name.clone(), yy_file_path, parent))
+            })
+            .collect();
+
+        for listing in listings {
+            let (resource, name, yy_file_path, parent) = listing?;
+
+            yyp_boss
+                .vfs
+                .load_in_listing(&name, resource, &parent)
+                .map_err(|e| StartupError::BadResourceTree {
+                    name: name.clone(),
+                    error: e.to_string(),
+                })?;
+
+            match resource {
+                Resource::Sprite => yyp_boss.sprites.load_lazily(name, yy_file_path),
+                Resource::Script => yyp_boss.scripts.load_lazily(name, yy_file_path),
+                Resource::Object => yyp_boss.objects.load_lazily(name, yy_file_path),
+                Resource::Note => yyp_boss.notes.load_lazily(name, yy_file_path),
+                Resource::Shader => yyp_boss.shaders.load_lazily(name, yy_file_path),
+                Resource::AnimationCurve => {
+                    yyp_boss.animation_curves.load_lazily(name, yy_file_path)
+                }
+                Resource::Room => yyp_boss.rooms.load_lazily(name, yy_file_path),
+                Resource::Extension => yyp_boss.extensions.load_lazily(name, yy_file_path),
+                Resource::Font => yyp_boss.fonts.load_lazily(name, yy_file_path),
+                Resource::Path => yyp_boss.paths.load_lazily(name, yy_file_path),
+                Resource::Sequence => yyp_boss.sequences.load_lazily(name, yy_file_path),
+                Resource::Sound => yyp_boss.sounds.load_lazily(name, yy_file_path),
+                Resource::TileSet => yyp_boss.tilesets.load_lazily(name, yy_file_path),
+                Resource::Timeline => yyp_boss.timelines.load_lazily(name, yy_file_path),
+            }
+        }
+
+        Ok(yyp_boss)
+    }
+
     /// This fills in names, internally, without loading those resources. This makes startup very fast,
-    /// but *only* do this if you know what you're doing, as the Vfs will no longer be accurate.
+    /// but *only* do this if you know what you're doing, as the Vfs will no longer be accurate. To
+    /// start up quickly with an accurate Vfs, use [`lazy`] instead.
+    ///
+    /// [`lazy`]: #method.lazy
     pub fn quick_name(&mut self) -> Result<(), StartupError> {
         for yyp_resource in self.yyp.resources.clone() {
-            let path_as_str = yyp_resource.id.path.to_string_lossy();
-
-            let subpath = path_as_str
-                .split('/')
-                .next()
-                .ok_or_else(|| StartupError::BadResourceListing(yyp_resource.id.path.clone()))?;
-
-            let resource = Resource::parse_subpath(subpath)
-                .ok_or_else(|| StartupError::BadResourceListing(yyp_resource.id.path.clone()))?;
+            let resource = resource_of(&yyp_resource)?;
 
             self.vfs.resource_names.insert(
                 yyp_resource.id.name.clone(),
@@ -197,49 +306,381 @@ impl YypBoss {
         Ok(())
     }
 
-    /// Gets the default texture path, if it exists. The "Default" group simply
-    /// has the name `"Default"`.
+    /// Serializes the YypBoss data to disk at the path of the Yyp.
     ///
-    /// This method will almost certainly be refactored soon to a dedicated TextureManager.
-    pub fn default_texture_path(&self) -> Option<TexturePath> {
-        self.yyp
-            .texture_groups
+    /// Every change is staged in a temporary directory within the project first, and then moved
+    /// into place, with the Yyp written after every resource it points to. If anything fails, the
+    /// project on disk is left as it was, and every change is still pending, so serializing can be
+    /// tried again.
+    ///
+    /// If a resource we would write or remove changed on disk since we loaded or last wrote it,
+    /// nothing is written and a [`ConflictError`] is returned. To overwrite, skip or merge those
+    /// resources instead, use [`serialize_with`].
+    ///
+    /// [`ConflictError`]: ./struct.ConflictError.html
+    /// [`serialize_with`]: #method.serialize_with
+    pub fn serialize(&mut self) -> AnyResult<()> {
+        self.serialize_with(ConflictResolution::Refuse).map(|_| ())
+    }
+
+    /// Serializes the YypBoss data to disk, as in [`serialize`], handling resources which changed
+    /// on disk with `resolution`.
+    ///
+    /// [`serialize`]: #method.serialize
+    pub fn serialize_with(&mut self, resolution: ConflictResolution) -> AnyResult<SerializeReport> {
+        let conflicts = self.conflicts()?;
+        if resolution == ConflictResolution::Refuse && conflicts.is_empty() == false {
+            return Err(ConflictError(conflicts).into());
+        }
+
+        let mut yyp = self.yyp.clone();
+        let mut staging = Staging::new(self.directory_manager.root_directory())?;
+
+        let mut skipped = Vec::new();
+        if let Err(e) = self.stage(&mut staging, &mut yyp, &conflicts, resolution, &mut skipped) {
+            // nothing has been committed, so the project is untouched
+            staging.discard();
+            return Err(e);
+        }
+        staging.commit()?;
+
+        // everything's on disk, so we can forget our changes
+        self.yyp = yyp;
+        self.vfs.mark_serialized();
+        self.mark_resources_serialized::<Sprite>(&skipped);
+        self.mark_resources_serialized::<Object>(&skipped);
+        self.mark_resources_serialized::<Script>(&skipped);
+        self.mark_resources_serialized::<Note>(&skipped);
+        self.mark_resources_serialized::<Shader>(&skipped);
+        self.mark_resources_serialized::<TileSet>(&skipped);
+        self.mark_resources_serialized::<Sound>(&skipped);
+        self.mark_resources_serialized::<AnimationCurve>(&skipped);
+        self.mark_resources_serialized::<Extension>(&skipped);
+        self.mark_resources_serialized::<Font>(&skipped);
+        self.mark_resources_serialized::<YyPath>(&skipped);
+        self.mark_resources_serialized::<Room>(&skipped);
+        self.mark_resources_serialized::<Sequence>(&skipped);
+        self.mark_resources_serialized::<Timeline>(&skipped);
+        self.included_files.mark_serialized();
+
+        let (mut overwritten, mut merged) = (Vec::new(), Vec::new());
+        for conflict in conflicts {
+            if skipped.contains(&conflict) == false {
+                match resolution {
+                    ConflictResolution::Merge => merged.push(conflict),
+                    _ => overwritten.push(conflict),
+                }
+            }
+        }
+
+        // what's on disk is now a mix of our changes and theirs, so we need to catch up
+        let root_directory = self.directory_manager.root_directory().to_owned();
+        let merged_paths: Vec<PathBuf> = merged
             .iter()
-            .find(|tex| tex.common_data.name == "Default")
-            .map(|texture_group| texture_group.into())
+            .map(|v| {
+                root_directory
+                    .join(v.resource.subpath_name())
+                    .join(&v.name)
+                    .join(format!("{}.yy", v.name))
+            })
+            .collect();
+        self.apply_external_changes(&merged_paths);
+
+        Ok(SerializeReport {
+            overwritten,
+            skipped,
+            merged,
+        })
     }
 
-    /// Serializes the YypBoss data to disk at the path of the Yyp.
-    pub fn serialize(&mut self) -> AnyResult<()> {
-        // serialize the vfs
-        self.vfs
-            .serialize(&mut self.yyp.folders, &mut self.yyp.resources);
+    /// Returns every resource which serializing would write or remove, but which changed on disk
+    /// since we loaded or last wrote it.
+    pub fn conflicts(&self) -> std::io::Result<Vec<ExternalResource>> {
+        let mut output = Vec::new();
+        self.resource_conflicts::<Sprite>(&mut output)?;
+        self.resource_conflicts::<Object>(&mut output)?;
+        self.resource_conflicts::<Script>(&mut output)?;
+        self.resource_conflicts::<Note>(&mut output)?;
+        self.resource_conflicts::<Shader>(&mut output)?;
+        self.resource_conflicts::<TileSet>(&mut output)?;
+        self.resource_conflicts::<Sound>(&mut output)?;
+        self.resource_conflicts::<AnimationCurve>(&mut output)?;
+        self.resource_conflicts::<Extension>(&mut output)?;
+        self.resource_conflicts::<Font>(&mut output)?;
+        self.resource_conflicts::<YyPath>(&mut output)?;
+        self.resource_conflicts::<Room>(&mut output)?;
+        self.resource_conflicts::<Sequence>(&mut output)?;
+        self.resource_conflicts::<Timeline>(&mut output)?;
+
+        Ok(output)
+    }
+
+    fn resource_conflicts<T: YyResource>(
+        &self,
+        output: &mut Vec<ExternalResource>,
+    ) -> std::io::Result<()> {
+        let names = T::get_handler(self).diverged(&self.directory_manager)?;
+        output.extend(names.into_iter().map(|name| ExternalResource {
+            resource: T::RESOURCE,
+            name,
+        }));
+
+        Ok(())
+    }
+
+    /// Stages every change into `staging`, and into `yyp`, which is staged last. The `conflicts`
+    /// which `resolution` skipped are added to `skipped`.
+    ///
+    /// Every resource is written out on the thread pool. If anything goes wrong, we keep going,
+    /// and every error is returned together as a [`SerializeErrors`].
+    ///
+    /// [`SerializeErrors`]: ./struct.SerializeErrors.html
+    fn stage(
+        &self,
+        staging: &mut Staging,
+        yyp: &mut Yyp,
+        conflicts: &[ExternalResource],
+        resolution: ConflictResolution,
+        skipped: &mut Vec<ExternalResource>,
+    ) -> AnyResult<()> {
+        let mut jobs = Vec::new();
+        let mut errors = Vec::new();
 
         // serialize all the tracked components
-        self.sprites.serialize(&self.directory_manager)?;
-        self.objects.serialize(&self.directory_manager)?;
-        self.scripts.serialize(&self.directory_manager)?;
-        self.notes.serialize(&self.directory_manager)?;
-        self.shaders.serialize(&self.directory_manager)?;
-        self.tilesets.serialize(&self.directory_manager)?;
-        self.sounds.serialize(&self.directory_manager)?;
-
-        // THESE DO NOT HAVE EXCELLENT TYPINGS YET.
-        self.animation_curves.serialize(&self.directory_manager)?;
-        self.extensions.serialize(&self.directory_manager)?;
-        self.fonts.serialize(&self.directory_manager)?;
-        self.paths.serialize(&self.directory_manager)?;
-        self.rooms.serialize(&self.directory_manager)?;
-        self.sequences.serialize(&self.directory_manager)?;
-        self.timelines.serialize(&self.directory_manager)?;
+        let (j, e) = (&mut jobs, &mut errors);
+        self.stage_resources::<Sprite>(staging, j, e);
+        self.stage_resources::<Object>(staging, j, e);
+        self.stage_resources::<Script>(staging, j, e);
+        self.stage_resources::<Note>(staging, j, e);
+        self.stage_resources::<Shader>(staging, j, e);
+        self.stage_resources::<TileSet>(staging, j, e);
+        self.stage_resources::<Sound>(staging, j, e);
+        self.stage_resources::<AnimationCurve>(staging, j, e);
+        self.stage_resources::<Extension>(staging, j, e);
+        self.stage_resources::<Font>(staging, j, e);
+        self.stage_resources::<YyPath>(staging, j, e);
+        self.stage_resources::<Room>(staging, j, e);
+        self.stage_resources::<Sequence>(staging, j, e);
+        self.stage_resources::<Timeline>(staging, j, e);
+
+        errors.extend(run_serialize_jobs(jobs));
+
+        // we can only merge what we managed to write
+        if errors.is_empty() {
+            let (c, r, e) = (conflicts, resolution, &mut errors);
+            self.resolve_conflicts::<Sprite>(staging, c, r, skipped, e);
+            self.resolve_conflicts::<Object>(staging, c, r, skipped, e);
+            self.resolve_conflicts::<Script>(staging, c, r, skipped, e);
+            self.resolve_conflicts::<Note>(staging, c, r, skipped, e);
+            self.resolve_conflicts::<Shader>(staging, c, r, skipped, e);
+            self.resolve_conflicts::<TileSet>(staging, c, r, skipped, e);
+            self.resolve_conflicts::<Sound>(staging, c, r, skipped, e);
+            self.resolve_conflicts::<AnimationCurve>(staging, c, r, skipped, e);
+            self.resolve_conflicts::<Extension>(staging, c, r, skipped, e);
+            self.resolve_conflicts::<Font>(staging, c, r, skipped, e);
+            self.resolve_conflicts::<YyPath>(staging, c, r, skipped, e);
+            self.resolve_conflicts::<Room>(staging, c, r, skipped, e);
+            self.resolve_conflicts::<Sequence>(staging, c, r, skipped, e);
+            self.resolve_conflicts::<Timeline>(staging, c, r, skipped, e);
+        }
+
+        if let Err(e) = self.stage_yyp(staging, yyp) {
+            errors.push(e);
+        }
 
         // Serialize Ourselves:
-        let string = yy_typings::serialize_file(&self.yyp);
-        fs::write(self.directory_manager.yyp(), string)?;
+        let string = yy_typings::serialize_file(yyp);
+        if let Err(e) = staging
+            .file(self.directory_manager.yyp())
+            .and_then(|path| fs::write(path, string))
+        {
+            errors.push(e.into());
+        }
+
+        into_serialize_result(errors)
+    }
+
+    fn stage_resources<'a, T: YyResource>(
+        &'a self,
+        staging: &mut Staging,
+        jobs: &mut Vec<SerializeJob<'a>>,
+        errors: &mut Vec<anyhow::Error>,
+    ) {
+        T::get_handler(self).serialize(&self.directory_manager, staging, jobs, errors);
+    }
+
+    fn resolve_conflicts<T: YyResource>(
+        &self,
+        staging: &mut Staging,
+        conflicts: &[ExternalResource],
+        resolution: ConflictResolution,
+        skipped: &mut Vec<ExternalResource>,
+        errors: &mut Vec<anyhow::Error>,
+    ) {
+        let diverged: Vec<String> = conflicts
+            .iter()
+            .filter(|v| v.resource == T::RESOURCE)
+            .map(|v| v.name.clone())
+            .collect();
+        if diverged.is_empty() {
+            return;
+        }
+
+        let handler = T::get_handler(self);
+        match handler.resolve_conflicts(&self.directory_manager, staging, &diverged, resolution) {
+            Ok(names) => skipped.extend(names.into_iter().map(|name| ExternalResource {
+                resource: T::RESOURCE,
+                name,
+            })),
+            Err(e) => errors.push(e),
+        }
+    }
+
+    fn mark_resources_serialized<T: YyResource>(&mut self, skipped: &[ExternalResource]) {
+        let skipped: Vec<String> = skipped
+            .iter()
+            .filter(|v| v.resource == T::RESOURCE)
+            .map(|v| v.name.clone())
+            .collect();
+
+        let directory_manager = self.directory_manager.clone();
+        T::get_handler_mut(self).mark_serialized(&directory_manager, &skipped);
+    }
+
+    /// Stages the included files, and writes everything else the Yyp lists into `yyp`.
+    fn stage_yyp(&self, staging: &mut Staging, yyp: &mut Yyp) -> AnyResult<()> {
+        self.included_files
+            .serialize(&self.directory_manager, staging, &mut yyp.included_files)?;
+        self.serialize_listings(yyp);
 
         Ok(())
     }
 
+    /// Writes the folders, resources and groups into `yyp`. The included files are listed when
+    /// they're staged.
+    fn serialize_listings(&self, yyp: &mut Yyp) {
+        // serialize the vfs
+        self.vfs.serialize(&mut yyp.folders, &mut yyp.resources);
+
+        self.texture_groups.serialize(&mut yyp.texture_groups);
+        self.audio_groups.serialize(&mut yyp.audio_groups);
+    }
+
+    /// Returns what [`serialize`] would do if it was called now, without changing anything in the
+    /// project, in memory or on disk.
+    ///
+    /// The plan is built from the resources which are marked to be written or removed. Yy files,
+    /// included files and the Yyp are compared with what's on disk in memory. Only the associated
+    /// data of the resources to be written, such as a sprite's images, is written, into a single
+    /// directory in the system's temporary directory, to find out which of its files would change.
+    ///
+    /// [`serialize`]: #method.serialize
+    pub fn serialize_plan(&self) -> AnyResult<SerializePlan> {
+        let mut staging = Staging::new(&std::env::temp_dir())?;
+        let output = self.plan(&mut staging);
+        staging.discard();
+
+        output
+    }
+
+    fn plan(&self, staging: &mut Staging) -> AnyResult<SerializePlan> {
+        let resources = [
+            self.resource_plan::<Sprite>(staging)?,
+            self.resource_plan::<Object>(staging)?,
+            self.resource_plan::<Script>(staging)?,
+            self.resource_plan::<Note>(staging)?,
+            self.resource_plan::<Shader>(staging)?,
+            self.resource_plan::<TileSet>(staging)?,
+            self.resource_plan::<Sound>(staging)?,
+            self.resource_plan::<AnimationCurve>(staging)?,
+            self.resource_plan::<Extension>(staging)?,
+            self.resource_plan::<Font>(staging)?,
+            self.resource_plan::<YyPath>(staging)?,
+            self.resource_plan::<Room>(staging)?,
+            self.resource_plan::<Sequence>(staging)?,
+            self.resource_plan::<Timeline>(staging)?,
+        ];
+
+        let mut yyp = self.yyp.clone();
+        self.serialize_listings(&mut yyp);
+        yyp.included_files = self.included_files.files().to_vec();
+        let included_files = self.included_files.plan(&self.directory_manager);
+
+        let new_yyp = yy_typings::serialize_file(&yyp);
+        let changed = fs::read_to_string(self.directory_manager.yyp())
+            .map_or(true, |old_yyp| old_yyp != new_yyp);
+
+        let resource_names = |yyp: &Yyp| {
+            yyp.resources
+                .iter()
+                .map(|v| v.id.name.clone())
+                .collect::<BTreeSet<_>>()
+        };
+        let folders = |yyp: &Yyp| {
+            yyp.folders
+                .iter()
+                .map(|v| v.folder_path.clone())
+                .collect::<BTreeSet<_>>()
+        };
+        let included_file_paths = |yyp: &Yyp| {
+            yyp.included_files
+                .iter()
+                .map(|v| v.file_path.join(&v.common_data.name))
+                .collect::<BTreeSet<_>>()
+        };
+        fn difference<T: Ord + Clone>(a: &BTreeSet<T>, b: &BTreeSet<T>) -> Vec<T> {
+            a.difference(b).cloned().collect()
+        }
+
+        let (old_names, new_names) = (resource_names(&self.yyp), resource_names(&yyp));
+        let (old_folders, new_folders) = (folders(&self.yyp), folders(&yyp));
+        let (old_files, new_files) = (included_file_paths(&self.yyp), included_file_paths(&yyp));
+
+        Ok(SerializePlan {
+            conflicts: self.conflicts()?,
+            resources: resources.into_iter().flatten().collect(),
+            included_files,
+            yyp: YypDiff {
+                changed,
+                added_resources: difference(&new_names, &old_names),
+                removed_resources: difference(&old_names, &new_names),
+                added_folders: difference(&new_folders, &old_folders),
+                removed_folders: difference(&old_folders, &new_folders),
+                added_included_files: difference(&new_files, &old_files),
+                removed_included_files: difference(&old_files, &new_files),
+            },
+        })
+    }
+
+    /// What serializing the resources of type `T` would do, if it would do anything.
+    fn resource_plan<T: YyResource>(
+        &self,
+        staging: &mut Staging,
+    ) -> AnyResult<Option<ResourcePlan>> {
+        let handler = T::get_handler(self);
+        let files = handler.plan(&self.directory_manager, staging)?;
+
+        let dirty_handler = handler.dirty_handler();
+        let sorted = |names: Vec<&String>| {
+            let mut names: Vec<String> = names.into_iter().cloned().collect();
+            names.sort();
+            names
+        };
+        let reserialized = sorted(dirty_handler.resources_to_reserialize().keys().collect());
+        let removed = sorted(dirty_handler.resources_to_remove().keys().collect());
+
+        if reserialized.is_empty() && removed.is_empty() && files.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(ResourcePlan {
+            resource: T::RESOURCE,
+            reserialized,
+            removed,
+            files,
+        }))
+    }
+
     pub fn version_string(&self) -> &str {
         &self.yyp.meta_data.ide_version
     }
@@ -272,6 +713,29 @@ impl YypBoss {
         &mut self,
         yy_file: T,
         associated_data: T::AssociatedData,
+    ) -> Result<(), ResourceManipulationError> {
+        self.insert_resource(yy_file, Some(associated_data))
+    }
+
+    /// Adds a resource, which might not have its associated data if we're putting back a resource
+    /// whose associated data we couldn't load. In that case, whatever is on disk is left alone.
+    pub(crate) fn insert_resource<T: YyResource>(
+        &mut self,
+        yy_file: T,
+        associated_data: Option<T::AssociatedData>,
+    ) -> Result<(), ResourceManipulationError> {
+        self.insert_resource_at(yy_file, associated_data, None)
+    }
+
+    /// Adds a resource as in [`insert_resource`]. A room is put at `room_order` in the room order,
+    /// such as when we put back a room which was removed, or at the end if it's `None`.
+    ///
+    /// [`insert_resource`]: #method.insert_resource
+    pub(crate) fn insert_resource_at<T: YyResource>(
+        &mut self,
+        yy_file: T,
+        associated_data: Option<T::AssociatedData>,
+        room_order: Option<usize>,
     ) -> Result<(), ResourceManipulationError> {
         self.can_use_name(yy_file.name())?;
         if T::RESOURCE.can_manipulate() == false {
@@ -279,28 +743,151 @@ impl YypBoss {
         }
 
         self.vfs.new_resource_end(&yy_file)?;
+        if T::RESOURCE == Resource::Room {
+            self.add_to_room_order(yy_file.name(), room_order);
+        }
+
+        let name = yy_file.name().to_owned();
         let handler = T::get_handler_mut(self);
 
         if handler.set(yy_file, associated_data).is_some() {
             Err(ResourceManipulationError::InternalError)
         } else {
+            self.history.record(Operation::RemoveResource {
+                resource: T::RESOURCE,
+                name,
+            });
+
             Ok(())
         }
     }
 
+    /// Replaces a resource, which must already exist within the project, returning the old resource.
+    ///
+    /// The replacement keeps the resource's place in the Asset Tree, whatever parent it was given,
+    /// and any files which the old resource wrote and the new one doesn't, such as old frames,
+    /// are removed when the project is serialized. To move the resource, use [`move_resource`].
+    ///
+    /// [`move_resource`]: #method.move_resource
+    pub fn replace_resource<T: YyResource>(
+        &mut self,
+        yy_file: T,
+        associated_data: T::AssociatedData,
+    ) -> Result<(T, Option<T::AssociatedData>), ResourceManipulationError> {
+        self.replace_resource_inner(yy_file, Some(associated_data))
+    }
+
+    pub(crate) fn replace_resource_inner<T: YyResource>(
+        &mut self,
+        mut yy_file: T,
+        associated_data: Option<T::AssociatedData>,
+    ) -> Result<(T, Option<T::AssociatedData>), ResourceManipulationError> {
+        if T::RESOURCE.can_manipulate() == false {
+            return Err(ResourceManipulationError::ResourceCannotBeManipulated);
+        }
+
+        let handler = T::get_handler_mut(self);
+        let parent = match handler.get(yy_file.name()) {
+            Some(old) => old.yy_resource.parent_view_path(),
+            None => {
+                // is it there, but some other kind of resource?
+                return if self.vfs.resource_names.get(yy_file.name()).is_some() {
+                    Err(ResourceManipulationError::NameCollision)
+                } else {
+                    Err(ResourceManipulationError::BadGet)
+                };
+            }
+        };
+        yy_file.set_parent_view_path(parent);
+
+        let (old_yy, mut old_data): (T, _) = handler
+            .set(yy_file, associated_data)
+            .map(|v| v.into())
+            .ok_or(ResourceManipulationError::InternalError)?;
+
+        // we need the old data to put it back, and it's still on disk until we serialize
+        if old_data.is_none() {
+            let directory = self
+                .directory_manager
+                .root_directory()
+                .join(old_yy.relative_yy_directory());
+
+            old_data = old_yy
+                .deserialize_associated_data(&directory, &TCU)
+                .map_err(|e| {
+                    log::error!(
+                        "couldn't load {}'s associated data, so undoing its replacement will only restore its yy file...{}",
+                        old_yy.name(),
+                        e
+                    );
+                })
+                .ok();
+        }
+
+        if let Some(erased) = ErasedResource::new(old_yy.clone(), old_data.clone()) {
+            self.history.record(Operation::ReplaceResource(erased));
+        }
+
+        Ok((old_yy, old_data))
+    }
+
+    /// Adds a resource if it doesn't exist yet, or replaces it if it does. If a resource was
+    /// replaced, it is returned.
+    ///
+    /// See [`add_resource`] and [`replace_resource`] for the details of each.
+    ///
+    /// [`add_resource`]: #method.add_resource
+    /// [`replace_resource`]: #method.replace_resource
+    #[allow(clippy::type_complexity)]
+    pub fn set_resource<T: YyResource>(
+        &mut self,
+        yy_file: T,
+        associated_data: T::AssociatedData,
+    ) -> Result<Option<(T, Option<T::AssociatedData>)>, ResourceManipulationError> {
+        if T::get_handler(self).get(yy_file.name()).is_some() {
+            self.replace_resource(yy_file, associated_data).map(Some)
+        } else {
+            self.add_resource(yy_file, associated_data).map(|_| None)
+        }
+    }
+
     /// Removes a resource, which must already exist within the project.
     pub fn remove_resource<T: YyResource>(
         &mut self,
         name: &str,
     ) -> Result<(T, Option<T::AssociatedData>), ResourceManipulationError> {
+        // make sure we can remove it before anything changes...
+        if T::get_handler(self).get(name).is_none() {
+            self.vfs
+                .resource_names
+                .get_checked(name, T::RESOURCE)
+                .map_err(FolderGraphError::ResourceNameError)?;
+
+            return Err(ResourceManipulationError::InternalError);
+        }
+
         // remove the file from the VFS...
         self.vfs.remove_resource(name, T::RESOURCE)?;
+        let room_order = if T::RESOURCE == Resource::Room {
+            self.remove_from_room_order(name)
+        } else {
+            None
+        };
 
         let path = self.directory_manager.root_directory().to_path_buf();
         let handler = T::get_handler_mut(self);
-        handler
+        let (yy, associated_data) = handler
             .remove(name, &path, &TCU)
-            .ok_or(ResourceManipulationError::InternalError)
+            .ok_or(ResourceManipulationError::InternalError)?;
+
+        if let Some(erased) = ErasedResource::new(yy.clone(), associated_data.clone()) {
+            self.history.record(Operation::AddResource {
+                resource: erased,
+                room_order,
+            });
+        }
+
+        Ok((yy, associated_data))
     }
 
     /// Adds a new resource, which must not already exist within the project.
@@ -327,13 +914,22 @@ impl YypBoss {
         // rename the file in the VFS...
         self.vfs
             .rename_resource(name, T::RESOURCE, new_name.clone())?;
+        if T::RESOURCE == Resource::Room {
+            self.rename_in_room_order(name, &new_name);
+        }
 
         let path = self.directory_manager.root_directory().to_path_buf();
         let handler = T::get_handler_mut(self);
         handler
-            .rename(name, new_name, &path, &TCU)
+            .rename(name, new_name.clone(), &path, &TCU)
             .map_err(|_| ResourceManipulationError::InternalError)?;
 
+        self.history.record(Operation::RenameResource {
+            resource: T::RESOURCE,
+            name: new_name,
+            new_name: name.to_owned(),
+        });
+
         Ok(())
     }
 
@@ -355,7 +951,7 @@ impl YypBoss {
         name: &str,
         new_parent: ViewPath,
     ) -> Result<(), ResourceManipulationError> {
-        // cannot move them because we cannot reserialize them
+        // we can only move resources which we can reserialize
         if T::RESOURCE.can_manipulate() == false {
             return Err(ResourceManipulationError::ResourceCannotBeManipulated);
         }
@@ -366,10 +962,20 @@ impl YypBoss {
             .map_err(ResourceManipulationError::FolderGraphError)?;
 
         let handler = T::get_handler_mut(self);
+        let old_parent = handler
+            .get(name)
+            .map(|v| v.yy_resource.parent_view_path())
+            .ok_or(ResourceManipulationError::InternalError)?;
         handler
             .edit_parent(name, new_parent)
             .map_err(|_| ResourceManipulationError::InternalError)?;
 
+        self.history.record(Operation::MoveResource {
+            resource: T::RESOURCE,
+            name: name.to_owned(),
+            new_parent: old_parent,
+        });
+
         Ok(())
     }
 
@@ -387,17 +993,20 @@ impl YypBoss {
     /// Ensures some associated data is loaded by generic type. If you aren't working generically, just access
     /// the individual handlers for this.
     ///
-    /// If `force` is passed in, then this will *always* reload the associated data. Be careful out there -- hot
-    /// reloading isn't a feature we really support yet.
+    /// If `force` is passed in, then this will *always* reload the associated data from disk, dropping any
+    /// changes to it which haven't been serialized. To pick up changes made outside of the YypBoss, such as
+    /// by Gms2, use a [`ProjectWatcher`] and [`apply_external_changes`] instead, which reload whole resources
+    /// and the Vfs, and leave resources with unserialized changes alone.
     ///
     /// This operation will return a reference to the associated data if we succeeded.
+    ///
+    /// [`ProjectWatcher`]: ./struct.ProjectWatcher.html
+    /// [`apply_external_changes`]: #method.apply_external_changes
     pub fn ensure_associated_data_is_loaded<T: YyResource>(
         &mut self,
         name: &str,
         force: bool,
     ) -> Result<(), YyResourceHandlerError> {
-        // cannot move them because we cannot reserialize them
-
         let path = self.directory_manager.root_directory().to_path_buf();
         let handler = T::get_handler_mut(self);
 
@@ -414,6 +1023,255 @@ impl YypBoss {
     }
 }
 
+// external changes
+impl YypBoss {
+    /// Reloads the resources which the given files belong to, after they were changed outside of
+    /// the YypBoss, such as by Gms2. Only the Yyp, and `.yy`, `.gml` and `.png` files within a
+    /// resource's directory, are considered, and every other path is ignored. A [`ProjectWatcher`]
+    /// can find these paths for you.
+    ///
+    /// A resource which matches what we already have, such as one we just serialized, is left
+    /// alone. A resource with changes which haven't been serialized isn't reloaded, and is reported
+    /// as a conflict instead. If its yy file is gone, it's removed from the project.
+    ///
+    /// If the Yyp changed, the resources and folders it lists are compared with what it listed
+    /// before. Resources and folders which Gms2 added are loaded into the Vfs, and those it removed
+    /// are removed from it, so a resource or folder which was created or renamed in Gms2 shows up
+    /// here too. A folder which still holds anything isn't removed.
+    ///
+    /// Reloads and removals can't be undone.
+    ///
+    /// [`ProjectWatcher`]: ./struct.ProjectWatcher.html
+    pub fn apply_external_changes(&mut self, paths: &[PathBuf]) -> ExternalChanges {
+        let root_directory = self.directory_manager.root_directory().to_owned();
+        let mut resources: BTreeSet<ExternalResource> = paths
+            .iter()
+            .filter_map(|path| watcher::changed_resource(&root_directory, path))
+            .collect();
+
+        let mut output = ExternalChanges::default();
+        let mut unlisted = BTreeSet::new();
+        let mut unlisted_folders = Vec::new();
+        let mut room_order = Vec::new();
+        if paths
+            .iter()
+            .any(|path| path == self.directory_manager.yyp())
+        {
+            match utils::deserialize_json_tc::<Yyp, _>(self.directory_manager.yyp(), &TCU) {
+                Ok(yyp) => {
+                    let listed = |yyp: &Yyp| -> BTreeSet<ExternalResource> {
+                        yyp.resources
+                            .iter()
+                            .filter_map(|v| {
+                                Some(ExternalResource {
+                                    resource: resource_of(v).ok()?,
+                                    name: v.id.name.clone(),
+                                })
+                            })
+                            .collect()
+                    };
+                    let (old, new) = (listed(&self.yyp), listed(&yyp));
+                    unlisted = old.difference(&new).cloned().collect();
+                    resources.extend(new.difference(&old).cloned());
+                    resources.extend(unlisted.iter().cloned());
+
+                    // new folders go in first, so new and moved resources have somewhere to go
+                    let is_listed = |folders: &[YypFolder], folder: &YypFolder| {
+                        folders.iter().any(|v| v.folder_path == folder.folder_path)
+                    };
+                    for folder in yyp.folders.iter() {
+                        if is_listed(&self.yyp.folders, folder) == false
+                            && self.vfs.get_folder(&folder.folder_path).is_none()
+                        {
+                            self.vfs.load_in_folders(std::slice::from_ref(folder));
+                            output.added_folders.push(folder.folder_path.clone());
+                        }
+                    }
+                    unlisted_folders = self
+                        .yyp
+                        .folders
+                        .iter()
+                        .filter(|folder| is_listed(&yyp.folders, folder) == false)
+                        .map(|folder| folder.folder_path.clone())
+                        .collect();
+
+                    // the yyp is what's on disk, and our own changes go on top of it
+                    self.yyp.folders = yyp.folders;
+                    self.yyp.resources = yyp.resources;
+                    room_order = yyp.room_order_nodes;
+                }
+                Err(e) => log::warn!("couldn't reload the yyp...{}", e),
+            }
+        }
+
+        for resource in resources {
+            let name = &resource.name;
+            let unlisted = unlisted.contains(&resource);
+            let change = match resource.resource {
+                Resource::Sprite => self.reload_external::<Sprite>(name, unlisted),
+                Resource::Script => self.reload_external::<Script>(name, unlisted),
+                Resource::Object => self.reload_external::<Object>(name, unlisted),
+                Resource::Note => self.reload_external::<Note>(name, unlisted),
+                Resource::Shader => self.reload_external::<Shader>(name, unlisted),
+                Resource::Room => self.reload_external::<Room>(name, unlisted),
+                Resource::Font => self.reload_external::<Font>(name, unlisted),
+                Resource::Path => self.reload_external::<YyPath>(name, unlisted),
+                Resource::Sequence => self.reload_external::<Sequence>(name, unlisted),
+                Resource::Timeline => self.reload_external::<Timeline>(name, unlisted),
+                Resource::Extension => self.reload_external::<Extension>(name, unlisted),
+                Resource::AnimationCurve => self.reload_external::<AnimationCurve>(name, unlisted),
+                Resource::Sound => self.reload_external::<Sound>(name, unlisted),
+                Resource::TileSet => self.reload_external::<TileSet>(name, unlisted),
+            };
+
+            match change {
+                ExternalChange::Added => output.added.push(resource),
+                ExternalChange::Reloaded => output.reloaded.push(resource),
+                ExternalChange::Removed => output.removed.push(resource),
+                ExternalChange::Conflict => output.conflicts.push(resource),
+                ExternalChange::Unchanged => {}
+            }
+        }
+
+        // new rooms go where gms2 put them in the room order
+        for room in output.added.iter().filter(|v| v.resource == Resource::Room) {
+            let index = room_order.iter().position(|v| v.room_id.name == room.name);
+            self.add_to_room_order(&room.name, index);
+        }
+
+        // and folders go last, once everything has moved out of them. children come first, since
+        // their paths are longer
+        unlisted_folders.sort_by_key(|v| std::cmp::Reverse(v.inner().len()));
+        for folder in unlisted_folders {
+            if self.vfs.get_folder(&folder).is_none() {
+                continue;
+            }
+
+            match self.vfs.unload_empty_folder(&folder) {
+                Ok(()) => output.removed_folders.push(folder),
+                Err(e) => log::warn!("couldn't remove folder {}...{}", folder, e),
+            }
+        }
+
+        output
+    }
+
+    /// Reloads the resource `name`, or removes it if its yy file is gone or the Yyp on disk no
+    /// longer lists it, which `unlisted` says.
+    fn reload_external<T: YyResource>(&mut self, name: &str, unlisted: bool) -> ExternalChange {
+        let root_directory = self.directory_manager.root_directory().to_owned();
+        let handler = T::get_handler(self);
+        let current = match handler.get(name) {
+            Some(v) => v,
+            // it's not in our project, so gms2 must have just made it, unless we removed it
+            None => {
+                if handler.is_dirty(name) {
+                    return ExternalChange::Unchanged;
+                }
+                return self.load_external::<T>(name);
+            }
+        };
+        let is_dirty = handler.is_dirty(name);
+
+        let yy_path = root_directory.join(current.yy_resource.relative_yy_filepath());
+        if unlisted || yy_path.exists() == false {
+            if is_dirty {
+                return ExternalChange::Conflict;
+            }
+
+            if let Err(e) = self.vfs.remove_resource(name, T::RESOURCE) {
+                log::error!("couldn't remove {} from the vfs...{}", name, e);
+            }
+            if T::RESOURCE == Resource::Room {
+                self.remove_from_room_order(name);
+            }
+            T::get_handler_mut(self).unload(name);
+
+            return ExternalChange::Removed;
+        }
+
+        // gms2 might be halfway through writing, in which case we'll hear about it again
+        let yy: T = match utils::deserialize_json_tc(&yy_path, &TCU) {
+            Ok(v) => v,
+            Err(e) => {
+                log::warn!("couldn't reload {}...{}", name, e);
+                return ExternalChange::Unchanged;
+            }
+        };
+        let associated_data = match &current.associated_data {
+            Some(_) => match yy.deserialize_associated_data(yy_path.parent().unwrap(), &TCU) {
+                Ok(v) => Some(v),
+                Err(e) => {
+                    log::warn!("couldn't reload {}'s associated data...{}", name, e);
+                    return ExternalChange::Unchanged;
+                }
+            },
+            None => None,
+        };
+
+        if yy == current.yy_resource && associated_data == current.associated_data {
+            return ExternalChange::Unchanged;
+        }
+        if is_dirty {
+            return ExternalChange::Conflict;
+        }
+        let fingerprint = match Fingerprint::of(yy_path.parent().unwrap()) {
+            Ok(v) => v,
+            Err(e) => {
+                log::warn!("couldn't reload {}...{}", name, e);
+                return ExternalChange::Unchanged;
+            }
+        };
+
+        let new_parent = yy.parent_view_path();
+        if new_parent != current.yy_resource.parent_view_path() {
+            if let Err(e) = self.vfs.move_resource(name, T::RESOURCE, &new_parent.path) {
+                log::error!("couldn't move {} in the vfs...{}", name, e);
+            }
+        }
+        T::get_handler_mut(self).reload(yy, associated_data, fingerprint);
+
+        ExternalChange::Reloaded
+    }
+
+    /// Loads in a resource which gms2 added, if the Yyp on disk lists it.
+    fn load_external<T: YyResource>(&mut self, name: &str) -> ExternalChange {
+        let yy_path = match self.yyp.resources.iter().find(|v| v.id.name == name) {
+            Some(v) => self.directory_manager.root_directory().join(&v.id.path),
+            None => return ExternalChange::Unchanged,
+        };
+
+        // our own resource of another kind has the name, so it's our change against theirs
+        if self.vfs.get_resource_type(name).is_some() {
+            return ExternalChange::Conflict;
+        }
+
+        // gms2 might be halfway through writing, in which case we'll hear about it again
+        let yy: T = match utils::deserialize_json_tc(&yy_path, &TCU) {
+            Ok(v) => v,
+            Err(e) => {
+                log::warn!("couldn't load {}...{}", name, e);
+                return ExternalChange::Unchanged;
+            }
+        };
+        let fingerprint = match Fingerprint::of(yy_path.parent().unwrap()) {
+            Ok(v) => v,
+            Err(e) => {
+                log::warn!("couldn't load {}...{}", name, e);
+                return ExternalChange::Unchanged;
+            }
+        };
+
+        if let Err(e) = self.vfs.load_in_file(&yy) {
+            log::error!("couldn't add {} to the vfs...{}", name, e);
+            return ExternalChange::Unchanged;
+        }
+        T::get_handler_mut(self).load_on_startup(yy, None, fingerprint);
+
+        ExternalChange::Added
+    }
+}
+
 // resource handling!
 impl YypBoss {
     /// Move a resource within the Asset Tree, using the passed in resource type
@@ -429,96 +1287,774 @@ impl YypBoss {
             Resource::Object => self.move_resource::<Object>(name, new_parent),
             Resource::Note => self.move_resource::<Note>(name, new_parent),
             Resource::Shader => self.move_resource::<Shader>(name, new_parent),
-            _ => Err(ResourceManipulationError::ResourceCannotBeManipulated),
+            Resource::Room => self.move_resource::<Room>(name, new_parent),
+            Resource::Font => self.move_resource::<Font>(name, new_parent),
+            Resource::Path => self.move_resource::<YyPath>(name, new_parent),
+            Resource::Sequence => self.move_resource::<Sequence>(name, new_parent),
+            Resource::Timeline => self.move_resource::<Timeline>(name, new_parent),
+            Resource::Extension => self.move_resource::<Extension>(name, new_parent),
+            Resource::AnimationCurve => self.move_resource::<AnimationCurve>(name, new_parent),
+            Resource::Sound => self.move_resource::<Sound>(name, new_parent),
+            Resource::TileSet => self.move_resource::<TileSet>(name, new_parent),
+        }
+    }
+
+    /// Removes a resource, using the passed in resource type. Since we don't know the type, the
+    /// removed resource isn't returned.
+    pub fn remove_resource_dynamic(
+        &mut self,
+        name: &str,
+        resource: Resource,
+    ) -> Result<(), ResourceManipulationError> {
+        match resource {
+            Resource::Sprite => self.remove_resource::<Sprite>(name).map(|_| ()),
+            Resource::Script => self.remove_resource::<Script>(name).map(|_| ()),
+            Resource::Object => self.remove_resource::<Object>(name).map(|_| ()),
+            Resource::Note => self.remove_resource::<Note>(name).map(|_| ()),
+            Resource::Shader => self.remove_resource::<Shader>(name).map(|_| ()),
+            Resource::Room => self.remove_resource::<Room>(name).map(|_| ()),
+            Resource::Font => self.remove_resource::<Font>(name).map(|_| ()),
+            Resource::Path => self.remove_resource::<YyPath>(name).map(|_| ()),
+            Resource::Sequence => self.remove_resource::<Sequence>(name).map(|_| ()),
+            Resource::Timeline => self.remove_resource::<Timeline>(name).map(|_| ()),
+            Resource::Extension => self.remove_resource::<Extension>(name).map(|_| ()),
+            Resource::AnimationCurve => self.remove_resource::<AnimationCurve>(name).map(|_| ()),
+            Resource::Sound => self.remove_resource::<Sound>(name).map(|_| ()),
+            Resource::TileSet => self.remove_resource::<TileSet>(name).map(|_| ()),
+        }
+    }
+
+    /// Renames a resource, using the passed in resource type.
+    pub fn rename_resource_dynamic(
+        &mut self,
+        name: &str,
+        new_name: String,
+        resource: Resource,
+    ) -> Result<(), ResourceManipulationError> {
+        match resource {
+            Resource::Sprite => self.rename_resource::<Sprite>(name, new_name),
+            Resource::Script => self.rename_resource::<Script>(name, new_name),
+            Resource::Object => self.rename_resource::<Object>(name, new_name),
+            Resource::Note => self.rename_resource::<Note>(name, new_name),
+            Resource::Shader => self.rename_resource::<Shader>(name, new_name),
+            Resource::Room => self.rename_resource::<Room>(name, new_name),
+            Resource::Font => self.rename_resource::<Font>(name, new_name),
+            Resource::Path => self.rename_resource::<YyPath>(name, new_name),
+            Resource::Sequence => self.rename_resource::<Sequence>(name, new_name),
+            Resource::Timeline => self.rename_resource::<Timeline>(name, new_name),
+            Resource::Extension => self.rename_resource::<Extension>(name, new_name),
+            Resource::AnimationCurve => self.rename_resource::<AnimationCurve>(name, new_name),
+            Resource::Sound => self.rename_resource::<Sound>(name, new_name),
+            Resource::TileSet => self.rename_resource::<TileSet>(name, new_name),
         }
     }
 
     /// Removes a folder RECURSIVELY. **All resources within will be removed**. Be careful out there.
+    ///
+    /// This is undone as a single step. If anything within can't be removed, everything which was
+    /// removed is put back.
     pub fn remove_folder(
         &mut self,
         folder: &ViewPathLocation,
     ) -> Result<(), ResourceManipulationError> {
-        // easy!
-        if self.vfs.remove_empty_folder(folder).is_ok() {
-            return Ok(());
+        if Vfs::is_root(folder) {
+            return Err(FolderGraphError::CannotEditRootFolder.into());
         }
 
-        // okay okay, more complex operation
-        let deleted_resources = self.vfs.remove_non_empty_folder(folder)?;
+        let folder_graph = self
+            .vfs
+            .get_folder(folder)
+            .ok_or_else(|| FolderGraphError::PathNotFound {
+                path: folder.inner().to_string(),
+            })?
+            .clone();
+
+        // everything goes at once, or nothing does
+        self.history.begin_step();
+        if let Err(e) = self.remove_folder_contents(&folder_graph, folder) {
+            if let Err(e) = self.abort_undo_step() {
+                log::error!("couldn't put back the folder's contents...{}", e);
+            }
 
-        for (fsys, descriptor) in deleted_resources {
-            match descriptor.resource {
-                Resource::Sprite => {
-                    self.sprites
-                        .remove(&fsys.name, self.directory_manager.root_directory(), &TCU);
-                }
-                Resource::Script => {
-                    self.scripts
-                        .remove(&fsys.name, self.directory_manager.root_directory(), &TCU);
-                }
-                Resource::Object => {
-                    self.objects
-                        .remove(&fsys.name, self.directory_manager.root_directory(), &TCU);
-                }
-                Resource::Note => {
-                    self.notes
-                        .remove(&fsys.name, self.directory_manager.root_directory(), &TCU);
-                }
-                Resource::Shader => {
-                    self.shaders
-                        .remove(&fsys.name, self.directory_manager.root_directory(), &TCU);
-                }
-                Resource::AnimationCurve => {
-                    self.animation_curves.remove(
-                        &fsys.name,
-                        self.directory_manager.root_directory(),
-                        &TCU,
-                    );
-                }
-                Resource::Extension => {
-                    self.extensions.remove(
-                        &fsys.name,
-                        self.directory_manager.root_directory(),
-                        &TCU,
-                    );
-                }
-                Resource::Font => {
-                    self.fonts
-                        .remove(&fsys.name, self.directory_manager.root_directory(), &TCU);
-                }
-                Resource::Path => {
-                    self.paths
-                        .remove(&fsys.name, self.directory_manager.root_directory(), &TCU);
-                }
-                Resource::Room => {
-                    self.rooms
-                        .remove(&fsys.name, self.directory_manager.root_directory(), &TCU);
-                }
-                Resource::Sequence => {
-                    self.sequences.remove(
-                        &fsys.name,
-                        self.directory_manager.root_directory(),
-                        &TCU,
-                    );
-                }
-                Resource::Sound => {
-                    self.sounds
-                        .remove(&fsys.name, self.directory_manager.root_directory(), &TCU);
-                }
-                Resource::TileSet => {
-                    self.tilesets
-                        .remove(&fsys.name, self.directory_manager.root_directory(), &TCU);
+            return Err(e);
+        }
+        self.history.end_step();
+
+        Ok(())
+    }
+
+    /// Removes everything in a folder, deepest first, and then the folder itself.
+    fn remove_folder_contents(
+        &mut self,
+        folder_graph: &FolderGraph,
+        location: &ViewPathLocation,
+    ) -> Result<(), ResourceManipulationError> {
+        for subfolder in folder_graph.folders.iter() {
+            self.remove_folder_contents(subfolder, &location.join(&subfolder.name))?;
+        }
+
+        for file in folder_graph.files.inner() {
+            let resource = self
+                .vfs
+                .get_resource_type(&file.name)
+                .ok_or(ResourceManipulationError::InternalError)?;
+
+            self.remove_resource_dynamic(&file.name, resource)?;
+        }
+
+        self.remove_empty_folder(location)?;
+
+        Ok(())
+    }
+}
+
+// folders
+impl YypBoss {
+    /// Adds a new folder to the end of the folder at `parent_path`, as in [`Vfs::new_folder_end`].
+    ///
+    /// Unlike editing `vfs` directly, this can be undone.
+    ///
+    /// [`Vfs::new_folder_end`]: ./struct.Vfs.html#method.new_folder_end
+    pub fn new_folder_end(
+        &mut self,
+        parent_path: &ViewPathLocation,
+        name: &str,
+    ) -> Result<ViewPath, FolderGraphError> {
+        let output = self.vfs.new_folder_end(parent_path, name)?;
+        self.history.record(Operation::RemoveFolder {
+            folder: output.path.clone(),
+        });
+
+        Ok(output)
+    }
+
+    /// Removes an empty folder, as in [`Vfs::remove_empty_folder`].
+    ///
+    /// Unlike editing `vfs` directly, this can be undone.
+    ///
+    /// [`Vfs::remove_empty_folder`]: ./struct.Vfs.html#method.remove_empty_folder
+    pub fn remove_empty_folder(
+        &mut self,
+        folder_path: &ViewPathLocation,
+    ) -> Result<(), FolderGraphError> {
+        let (parent, name) = self.folder_parent_and_name(folder_path)?;
+        self.vfs.remove_empty_folder(folder_path)?;
+
+        if let Some(parent) = parent {
+            self.history
+                .record(Operation::CreateFolder { parent, name });
+        }
+
+        Ok(())
+    }
+
+    /// Renames a folder, as in [`Vfs::rename_folder`].
+    ///
+    /// Unlike editing `vfs` directly, this can be undone.
+    ///
+    /// [`Vfs::rename_folder`]: ./struct.Vfs.html#method.rename_folder
+    pub fn rename_folder(
+        &mut self,
+        folder_path: &ViewPathLocation,
+        new_name: String,
+    ) -> Result<(), FolderGraphError> {
+        let (parent, name) = self.folder_parent_and_name(folder_path)?;
+        self.vfs.rename_folder(folder_path, new_name.clone())?;
+
+        if let Some(parent) = parent {
+            self.history.record(Operation::RenameFolder {
+                folder: parent.join(&new_name),
+                new_name: name,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Moves a folder, as in [`Vfs::move_folder`].
+    ///
+    /// Unlike editing `vfs` directly, this can be undone.
+    ///
+    /// [`Vfs::move_folder`]: ./struct.Vfs.html#method.move_folder
+    pub fn move_folder(
+        &mut self,
+        folder_path: ViewPathLocation,
+        new_parent: &ViewPathLocation,
+    ) -> Result<(), FolderGraphError> {
+        let (parent, name) = self.folder_parent_and_name(&folder_path)?;
+        let moved = folder_path != *new_parent;
+        self.vfs.move_folder(folder_path, new_parent)?;
+
+        if let (Some(parent), true) = (parent, moved) {
+            self.history.record(Operation::MoveFolder {
+                folder: new_parent.join(&name),
+                new_parent: parent,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn folder_parent_and_name(
+        &self,
+        folder_path: &ViewPathLocation,
+    ) -> Result<(Option<ViewPathLocation>, String), FolderGraphError> {
+        self.vfs
+            .get_folder(folder_path)
+            .map(|v| (v.path_to_parent.clone(), v.name.clone()))
+            .ok_or_else(|| FolderGraphError::PathNotFound {
+                path: folder_path.inner().to_string(),
+            })
+    }
+}
+
+// groups, included files and events
+impl YypBoss {
+    /// Changes the texture groups with `edit`, such as with [`TextureGroupManager::rename`], so
+    /// that the change can be undone. Undoing it puts back every sprite and tileset which was in
+    /// the texture group called `group` before the change.
+    ///
+    /// [`TextureGroupManager::rename`]: ./struct.TextureGroupManager.html#method.rename
+    pub fn edit_texture_groups<R>(
+        &mut self,
+        group: Option<&str>,
+        edit: impl FnOnce(
+            &mut TextureGroupManager,
+            &mut YyResourceHandler<Sprite>,
+            &mut YyResourceHandler<TileSet>,
+        ) -> Result<R, GroupError>,
+    ) -> Result<R, GroupError> {
+        let texture_groups = self.texture_groups.clone();
+        let (sprites, tilesets) = match group {
+            Some(group) => (
+                group_members(&mut self.sprites, |v| {
+                    (v.texture_group_id.name == group).then(|| v.texture_group_id.clone())
+                }),
+                group_members(&mut self.tilesets, |v| {
+                    (v.texture_group_id.name == group).then(|| v.texture_group_id.clone())
+                }),
+            ),
+            None => (vec![], vec![]),
+        };
+
+        let output = edit(
+            &mut self.texture_groups,
+            &mut self.sprites,
+            &mut self.tilesets,
+        )?;
+        self.history.record(Operation::RestoreTextureGroups {
+            texture_groups,
+            sprites,
+            tilesets,
+        });
+
+        Ok(output)
+    }
+
+    /// Changes the audio groups with `edit`, as in [`edit_texture_groups`], so that the change can
+    /// be undone. Undoing it puts back every sound which was in the audio group called `group`.
+    ///
+    /// [`edit_texture_groups`]: #method.edit_texture_groups
+    pub fn edit_audio_groups<R>(
+        &mut self,
+        group: Option<&str>,
+        edit: impl FnOnce(
+            &mut AudioGroupManager,
+            &mut YyResourceHandler<Sound>,
+        ) -> Result<R, GroupError>,
+    ) -> Result<R, GroupError> {
+        let audio_groups = self.audio_groups.clone();
+        let sounds = match group {
+            Some(group) => group_members(&mut self.sounds, |v| {
+                (v.audio_group_id.name == group).then(|| v.audio_group_id.clone())
+            }),
+            None => vec![],
+        };
+
+        let output = edit(&mut self.audio_groups, &mut self.sounds)?;
+        self.history.record(Operation::RestoreAudioGroups {
+            audio_groups,
+            sounds,
+        });
+
+        Ok(output)
+    }
+
+    /// Adds an included file, as in [`IncludedFilesHandler::add`], so that it can be undone.
+    ///
+    /// [`IncludedFilesHandler::add`]: ./struct.IncludedFilesHandler.html#method.add
+    pub fn add_included_file(
+        &mut self,
+        path: &Path,
+        data: Vec<u8>,
+    ) -> Result<(), ResourceManipulationError> {
+        self.included_files.add(path, data)?;
+        self.history.record(Operation::RemoveIncludedFile {
+            path: path.to_owned(),
+        });
+
+        Ok(())
+    }
+
+    /// Removes an included file, as in [`IncludedFilesHandler::remove`], so that it can be
+    /// undone. The file is read in first, unless it was never written, so that it can be put back.
+    ///
+    /// [`IncludedFilesHandler::remove`]: ./struct.IncludedFilesHandler.html#method.remove
+    pub fn remove_included_file(
+        &mut self,
+        path: &Path,
+    ) -> Result<YypIncludedFile, ResourceManipulationError> {
+        let position = self.included_files.index(path).unwrap_or_default();
+        let data = self.included_files.data(path, &self.directory_manager);
+        let file = self.included_files.remove(path)?;
+
+        self.history.record(Operation::AddIncludedFile {
+            position,
+            file: file.clone(),
+            data,
+        });
+
+        Ok(file)
+    }
+
+    /// Renames an included file, as in [`IncludedFilesHandler::rename`], so that it can be undone.
+    ///
+    /// [`IncludedFilesHandler::rename`]: ./struct.IncludedFilesHandler.html#method.rename
+    pub fn rename_included_file(
+        &mut self,
+        path: &Path,
+        new_name: &str,
+    ) -> Result<(), ResourceManipulationError> {
+        self.included_files.rename(path, new_name)?;
+        self.history.record(Operation::MoveIncludedFile {
+            path: path.with_file_name(new_name),
+            new_path: path.to_owned(),
+        });
+
+        Ok(())
+    }
+
+    /// Moves an included file, as in [`IncludedFilesHandler::move_file`], so that it can be undone.
+    ///
+    /// [`IncludedFilesHandler::move_file`]: ./struct.IncludedFilesHandler.html#method.move_file
+    pub fn move_included_file(
+        &mut self,
+        path: &Path,
+        new_directory: &Path,
+    ) -> Result<(), ResourceManipulationError> {
+        self.included_files.move_file(path, new_directory)?;
+        let file_name = path.file_name().ok_or(ResourceManipulationError::BadName)?;
+        self.history.record(Operation::MoveIncludedFile {
+            path: new_directory.join(file_name),
+            new_path: path.to_owned(),
+        });
+
+        Ok(())
+    }
+
+    /// Adds an event to an object, as in [`YyResourceHandler::add_event`], so that it can be
+    /// undone. The object's associated data must be loaded. Returns `false` if the object already
+    /// had the event.
+    ///
+    /// [`YyResourceHandler::add_event`]: ./struct.YyResourceHandler.html#method.add_event
+    pub fn add_object_event(&mut self, name: &str, event_type: EventType) -> bool {
+        self.edit_object(name, |objects| objects.add_event(name, event_type))
+    }
+
+    /// Removes an event from an object, as in [`add_object_event`]. Returns `false` if the object
+    /// didn't have the event.
+    ///
+    /// [`add_object_event`]: #method.add_object_event
+    pub fn remove_object_event(&mut self, name: &str, event_type: EventType) -> bool {
+        self.edit_object(name, |objects| objects.remove_event(name, event_type))
+    }
+
+    fn edit_object(
+        &mut self,
+        name: &str,
+        edit: impl FnOnce(&mut YyResourceHandler<Object>) -> bool,
+    ) -> bool {
+        let old = self.objects.get(name).cloned();
+        if edit(&mut self.objects) == false {
+            return false;
+        }
+
+        if let Some(old) = old.and_then(|v| ErasedResource::new(v.yy_resource, v.associated_data)) {
+            self.history.record(Operation::ReplaceResource(old));
+        }
+
+        true
+    }
+}
+
+// sprite frames
+impl YypBoss {
+    /// Inserts a frame at `index` in a sprite's frames, with an image for each layer, and returns
+    /// its id, so that it can be undone. If `index` is past the end, the frame is added at the
+    /// end. The sprite's associated data must be loaded.
+    pub fn insert_sprite_frame(
+        &mut self,
+        name: &str,
+        index: usize,
+        frame_layers: SpriteFrameLayers,
+    ) -> Result<FrameId, YyResourceHandlerError> {
+        self.edit_sprite(name, |sprites| {
+            sprites.insert_frame(name, index, frame_layers)
+        })
+    }
+
+    /// Removes a frame from a sprite, returning its images, so that it can be undone. Its image
+    /// files are removed when the sprite is serialized.
+    pub fn remove_sprite_frame(
+        &mut self,
+        name: &str,
+        frame_id: FrameId,
+    ) -> Result<SpriteFrameLayers, YyResourceHandlerError> {
+        self.edit_sprite(name, |sprites| sprites.remove_frame(name, frame_id))
+    }
+
+    /// Moves a frame to `index` in a sprite's frames, so that it can be undone. If `index` is
+    /// past the end, the frame is moved to the end.
+    pub fn move_sprite_frame(
+        &mut self,
+        name: &str,
+        frame_id: FrameId,
+        index: usize,
+    ) -> Result<(), YyResourceHandlerError> {
+        self.edit_sprite(name, |sprites| sprites.move_frame(name, frame_id, index))
+    }
+
+    /// Copies a frame, and its images, into a new frame just after it, and returns the new
+    /// frame's id, so that it can be undone. The copy is shown for as long as the original.
+    pub fn duplicate_sprite_frame(
+        &mut self,
+        name: &str,
+        frame_id: FrameId,
+    ) -> Result<FrameId, YyResourceHandlerError> {
+        self.edit_sprite(name, |sprites| sprites.duplicate_frame(name, frame_id))
+    }
+
+    fn edit_sprite<R>(
+        &mut self,
+        name: &str,
+        edit: impl FnOnce(&mut YyResourceHandler<Sprite>) -> Result<R, YyResourceHandlerError>,
+    ) -> Result<R, YyResourceHandlerError> {
+        let old = self.sprites.get(name).cloned();
+        let output = edit(&mut self.sprites)?;
+
+        if let Some(old) = old.and_then(|v| ErasedResource::new(v.yy_resource, v.associated_data)) {
+            self.history.record(Operation::ReplaceResource(old));
+        }
+
+        Ok(output)
+    }
+}
+
+// history
+impl YypBoss {
+    /// Undoes the last mutation made through the YypBoss, such as adding, removing, renaming or moving
+    /// a resource, or creating, removing, renaming or moving a folder. Changes to groups, included
+    /// files and events can be undone if they're made through the YypBoss, such as with
+    /// [`edit_texture_groups`], rather than on their handlers. Returns `false` if there was
+    /// nothing to undo.
+    ///
+    /// Undoing an add before serializing means the resource never reaches the disk. Resources and folders
+    /// which are put back are placed at the end of their folder, but rooms keep their place in the
+    /// room order.
+    ///
+    /// If the undo fails, the project is left as it was and the step can be tried again.
+    ///
+    /// [`edit_texture_groups`]: #method.edit_texture_groups
+    pub fn undo(&mut self) -> Result<bool, ResourceManipulationError> {
+        let step = match self.history.pop_undo() {
+            Some(v) => v,
+            None => return Ok(false),
+        };
+
+        match self.replay(step.clone()) {
+            Ok(inverse) => {
+                self.history.push_redo(inverse);
+                Ok(true)
+            }
+            Err(e) => {
+                self.history.push_undo(step);
+                Err(e)
+            }
+        }
+    }
+
+    /// Redoes the last mutation which was undone. Returns `false` if there was nothing to redo.
+    ///
+    /// Any new mutation clears what can be redone.
+    pub fn redo(&mut self) -> Result<bool, ResourceManipulationError> {
+        let step = match self.history.pop_redo() {
+            Some(v) => v,
+            None => return Ok(false),
+        };
+
+        match self.replay(step.clone()) {
+            Ok(inverse) => {
+                self.history.push_undo(inverse);
+                Ok(true)
+            }
+            Err(e) => {
+                self.history.push_redo(step);
+                Err(e)
+            }
+        }
+    }
+
+    /// Returns `true` if there is a mutation which can be undone.
+    pub fn can_undo(&self) -> bool {
+        self.history.can_undo()
+    }
+
+    /// Returns `true` if there is a mutation which can be redone.
+    pub fn can_redo(&self) -> bool {
+        self.history.can_redo()
+    }
+
+    /// Groups every mutation until the matching [`end_undo_step`] into a single step, so they're
+    /// undone together.
+    ///
+    /// [`end_undo_step`]: #method.end_undo_step
+    pub fn begin_undo_step(&mut self) {
+        self.history.begin_step();
+    }
+
+    /// Closes a step opened by [`begin_undo_step`].
+    ///
+    /// [`begin_undo_step`]: #method.begin_undo_step
+    pub fn end_undo_step(&mut self) {
+        self.history.end_step();
+    }
+
+    /// Closes a step opened by [`begin_undo_step`] by undoing every mutation made within it,
+    /// instead of keeping it as a step which can be undone later.
+    ///
+    /// [`begin_undo_step`]: #method.begin_undo_step
+    pub fn abort_undo_step(&mut self) -> Result<(), ResourceManipulationError> {
+        let step = self.history.abort_step();
+        self.replay(step).map(|_| ())
+    }
+
+    /// Runs a step backwards, returning the step which reverses it. If any operation fails, whatever
+    /// already ran is reversed.
+    fn replay(
+        &mut self,
+        step: Vec<Operation>,
+    ) -> Result<Vec<Operation>, ResourceManipulationError> {
+        self.history.start_replay();
+        let mut output = Ok(());
+        for operation in step.into_iter().rev() {
+            output = self.apply(operation);
+            if output.is_err() {
+                break;
+            }
+        }
+        let inverse = self.history.finish_replay();
+
+        if let Err(e) = output {
+            self.history.start_replay();
+            for operation in inverse.into_iter().rev() {
+                if let Err(e) = self.apply(operation) {
+                    log::error!("couldn't reverse a failed undo or redo...{}", e);
                 }
-                Resource::Timeline => {
-                    self.timelines.remove(
-                        &fsys.name,
-                        self.directory_manager.root_directory(),
-                        &TCU,
-                    );
+            }
+            self.history.finish_replay();
+
+            return Err(e);
+        }
+
+        Ok(inverse)
+    }
+
+    fn apply(&mut self, operation: Operation) -> Result<(), ResourceManipulationError> {
+        match operation {
+            Operation::AddResource {
+                resource,
+                room_order,
+            } => resource.add_to(self, room_order),
+            Operation::ReplaceResource(erased) => erased.replace_in(self),
+            Operation::RemoveResource { resource, name } => {
+                self.remove_resource_dynamic(&name, resource)
+            }
+            Operation::RenameResource {
+                resource,
+                name,
+                new_name,
+            } => self.rename_resource_dynamic(&name, new_name, resource),
+            Operation::MoveResource {
+                resource,
+                name,
+                new_parent,
+            } => self.move_resource_dynamic(&name, new_parent, resource),
+            Operation::CreateFolder { parent, name } => {
+                self.new_folder_end(&parent, &name).map(|_| ())?;
+                Ok(())
+            }
+            Operation::RemoveFolder { folder } => Ok(self.remove_empty_folder(&folder)?),
+            Operation::RenameFolder { folder, new_name } => {
+                Ok(self.rename_folder(&folder, new_name)?)
+            }
+            Operation::MoveFolder { folder, new_parent } => {
+                Ok(self.move_folder(folder, &new_parent)?)
+            }
+            Operation::RestoreTextureGroups {
+                texture_groups,
+                sprites,
+                tilesets,
+            } => {
+                let inverse = Operation::RestoreTextureGroups {
+                    texture_groups: std::mem::replace(&mut self.texture_groups, texture_groups),
+                    sprites: regroup(&mut self.sprites, sprites, |v| &mut v.texture_group_id),
+                    tilesets: regroup(&mut self.tilesets, tilesets, |v| &mut v.texture_group_id),
+                };
+                self.history.record(inverse);
+
+                Ok(())
+            }
+            Operation::RestoreAudioGroups {
+                audio_groups,
+                sounds,
+            } => {
+                let inverse = Operation::RestoreAudioGroups {
+                    audio_groups: std::mem::replace(&mut self.audio_groups, audio_groups),
+                    sounds: regroup(&mut self.sounds, sounds, |v| &mut v.audio_group_id),
+                };
+                self.history.record(inverse);
+
+                Ok(())
+            }
+            Operation::AddIncludedFile {
+                position,
+                file,
+                data,
+            } => {
+                let path = file
+                    .file_path
+                    .strip_prefix(crate::DATAFILES_DIRECTORY)
+                    .map_err(|_| ResourceManipulationError::BadName)?
+                    .join(&file.common_data.name);
+                if self.included_files.exists(&path) {
+                    return Err(ResourceManipulationError::NameCollision);
                 }
+
+                self.included_files.restore(position, file, data);
+                self.history.record(Operation::RemoveIncludedFile { path });
+
+                Ok(())
+            }
+            Operation::RemoveIncludedFile { path } => self.remove_included_file(&path).map(|_| ()),
+            Operation::MoveIncludedFile { path, new_path } => {
+                self.included_files.move_to(&path, &new_path)?;
+                self.history.record(Operation::MoveIncludedFile {
+                    path: new_path,
+                    new_path: path,
+                });
+
+                Ok(())
             }
         }
+    }
+}
+
+// room order
+impl YypBoss {
+    /// The rooms of the project, in the order GameMaker will run them. The first room
+    /// is the room the game starts in.
+    pub fn room_order(&self) -> impl Iterator<Item = &str> {
+        self.yyp
+            .room_order_nodes
+            .iter()
+            .map(|v| v.room_id.name.as_str())
+    }
+
+    /// Adds a room at `index` in the room order, or at the end if it's `None` or past the end.
+    fn add_to_room_order(&mut self, name: &str, index: Option<usize>) {
+        let node = RoomOrderId {
+            room_id: FilesystemPath::new(Room::SUBPATH_NAME, name),
+        };
+        let len = self.yyp.room_order_nodes.len();
+        self.yyp
+            .room_order_nodes
+            .insert(index.unwrap_or(len).min(len), node);
+    }
+
+    /// Removes a room from the room order, returning where it was.
+    fn remove_from_room_order(&mut self, name: &str) -> Option<usize> {
+        let index = self
+            .yyp
+            .room_order_nodes
+            .iter()
+            .position(|node| node.room_id.name == name)?;
+        self.yyp.room_order_nodes.remove(index);
 
+        Some(index)
+    }
+
+    fn rename_in_room_order(&mut self, name: &str, new_name: &str) {
+        for node in self.yyp.room_order_nodes.iter_mut() {
+            if node.room_id.name == name {
+                node.room_id = FilesystemPath::new(Room::SUBPATH_NAME, new_name);
+            }
+        }
+    }
+}
+
+/// Every resource of `handler` in a group, with the id it refers to the group by, as given by
+/// `member`. Every resource is read in first in a lazy project.
+fn group_members<T: YyResource, I>(
+    handler: &mut YyResourceHandler<T>,
+    member: impl Fn(&T) -> Option<I>,
+) -> Vec<(String, I)> {
+    handler.load_all();
+    handler
+        .resources()
+        .iter()
+        .filter_map(|(name, v)| Some((name.clone(), member(&v.yy_resource)?)))
+        .collect()
+}
+
+/// Puts each of `members` back into a group by setting its id, as given by `id`, and returns the
+/// ids they had. Resources which are gone are skipped.
+fn regroup<T: YyResource, I>(
+    handler: &mut YyResourceHandler<T>,
+    members: Vec<(String, I)>,
+    id: impl Fn(&mut T) -> &mut I,
+) -> Vec<(String, I)> {
+    let mut output = Vec::with_capacity(members.len());
+    for (name, value) in members {
+        let _ = handler.edit_yy(&name, |v| {
+            output.push((name.clone(), std::mem::replace(id(v), value)));
+        });
+    }
+
+    output
+}
+
+/// Runs every job on the thread pool, and returns their errors in the order of the jobs.
+fn run_serialize_jobs(jobs: Vec<SerializeJob<'_>>) -> Vec<anyhow::Error> {
+    jobs.into_par_iter().filter_map(|job| job().err()).collect()
+}
+
+fn into_serialize_result(errors: Vec<anyhow::Error>) -> AnyResult<()> {
+    if errors.is_empty() {
         Ok(())
+    } else {
+        Err(SerializeErrors(errors).into())
     }
 }
+
+/// Finds which kind of resource a listing in the Yyp is, from the first directory in its path.
+fn resource_of(yyp_resource: &YypResource) -> Result<Resource, StartupError> {
+    let path_as_str = yyp_resource.id.path.to_string_lossy();
+
+    let subpath = path_as_str
+        .split('/')
+        .next()
+        .ok_or_else(|| StartupError::BadResourceListing(yyp_resource.id.path.clone()))?;
+
+    Resource::parse_subpath(subpath)
+        .ok_or_else(|| StartupError::BadResourceListing(yyp_resource.id.path.clone()))
+}